@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::code_block::CodeBlock;
+use super::code_object::{CodeObject, CompiledCode};
+use super::constant_pool::ConstantPool;
+use super::jit::Jit;
+use super::module::{Module, ModuleContent, ModuleID};
+use crate::lang::object::{Object, Value};
+use crate::runtime::op_code::OP_CODE_COUNT;
+use crate::stdlib;
+use crate::utils::byte_code::PTR_SIZE;
+
+
+/// The file extension of compiled WaterScript files
+pub const BYTECODE_EXTENSION: &str = "wsc";
+
+/// The first bytes of every compiled file
+const MAGIC: &[u8; 4] = b"WSC\0";
+
+/// Incremented every time the layout of compiled files changes
+const FORMAT_VERSION: u16 = 9;
+
+
+/// The tags of the serialized constants
+const CONST_INT: u8 = 0;
+const CONST_FLOAT: u8 = 1;
+const CONST_STRING: u8 = 2;
+const CONST_BOOL: u8 = 3;
+const CONST_NONE: u8 = 4;
+
+/// The tags of the serialized modules
+const MODULE_SOURCE: u8 = 0;
+const MODULE_NATIVE: u8 = 1;
+
+/// The size of the integers that lengths, counts and ids are written as
+const USIZE_SIZE: usize = 8;
+/// The smallest size of a serialized code object: a length or count for each of its 7 fields of variable size,
+/// its required parameter count and its line, plus its 2 flags
+const MIN_CODE_OBJECT_SIZE: usize = 9 * USIZE_SIZE + 2;
+
+
+/// Serialize the compiled program.
+///
+/// The layout is, with all integers in little endian:
+/// - the header: magic, format version (u16), op code count (u16) and byte code operand size (u8)
+/// - the constant pool: the number of constants, then each constant's tag and value
+/// - the modules, indexed by module id: name, path and either the source code and the top-level code object, or the name of a native module
+///
+/// Each code object contains its byte code, its line table, its body scope's symbols and, recursively, the functions declared in it.
+/// The source code is kept so that errors can show the lines they happened at.
+/// The program must have been compiled ahead of time, since uncompiled function bodies can't be serialized
+pub fn serialize(jit: &Jit) -> Vec<u8> {
+    let mut writer = Writer { bytes: Vec::new() };
+
+    writer.bytes.extend_from_slice(MAGIC);
+    writer.write_u16(FORMAT_VERSION);
+    writer.write_u16(OP_CODE_COUNT as u16);
+    writer.bytes.push(PTR_SIZE as u8);
+
+    let constants = jit.constants.constants();
+    writer.write_usize(constants.len());
+    for constant in constants {
+        writer.write_constant(constant);
+    }
+
+    writer.write_usize(jit.modules.len());
+    for module in &jit.modules {
+        writer.write_str(&module.name);
+        writer.write_str(&module.path.to_string_lossy());
+
+        match &module.content {
+            ModuleContent::Source { source, root } => {
+                writer.bytes.push(MODULE_SOURCE);
+                writer.write_str(source);
+                writer.write_code_object(root);
+            },
+            ModuleContent::Native { .. } => {
+                writer.bytes.push(MODULE_NATIVE);
+            },
+        }
+    }
+
+    writer.bytes
+}
+
+
+/// Deserialize a compiled program into its modules, indexed by module id, and its constant pool.
+/// Return a description of the problem if the file is not a valid compiled file for this version of the interpreter
+pub fn deserialize(bytes: &[u8]) -> Result<(Vec<Module>, ConstantPool), String> {
+    let mut reader = Reader { bytes, index: 0 };
+
+    if reader.read_bytes(MAGIC.len())? != MAGIC {
+        return Err("not a compiled WaterScript file".to_string());
+    }
+
+    let format_version = reader.read_u16()?;
+    if format_version != FORMAT_VERSION {
+        return Err(format!("format version {} is not supported, expected version {}", format_version, FORMAT_VERSION));
+    }
+
+    let op_code_count = reader.read_u16()? as usize;
+    if op_code_count != OP_CODE_COUNT {
+        return Err(format!("compiled with {} op codes, but this interpreter has {}. Recompile the source file", op_code_count, OP_CODE_COUNT));
+    }
+
+    let operand_size = reader.read_u8()? as usize;
+    if operand_size != PTR_SIZE {
+        return Err(format!("compiled with {}-byte operands, but this interpreter uses {}-byte operands", operand_size, PTR_SIZE));
+    }
+
+    let mut constants = ConstantPool::new();
+    // Each constant has at least its tag
+    for expected_index in 0 .. reader.read_count(1)? {
+        if reader.read_constant(&mut constants)? != expected_index {
+            return Err("duplicate constant in the constant pool".to_string());
+        }
+    }
+
+    let mut modules: Vec<Module> = Vec::new();
+    // Each module has at least its name, its path and its tag
+    for module_id in 0 .. reader.read_count(2 * USIZE_SIZE + 1)? {
+        let name = reader.read_str()?.to_string();
+        let path = PathBuf::from(reader.read_str()?);
+
+        let content = match reader.read_u8()? {
+            MODULE_SOURCE => {
+                let source = reader.read_str()?.to_string();
+                let root = reader.read_code_object(module_id, &mut constants)?;
+                ModuleContent::Source { source, root }
+            },
+            MODULE_NATIVE => {
+                let init = stdlib::get_builtin_module(&name)
+                    .ok_or_else(|| format!("unknown built-in module '{}'", name))?;
+                ModuleContent::Native { init }
+            },
+            tag => return Err(format!("invalid module tag {}", tag)),
+        };
+
+        modules.push(Module { name, path, content });
+    }
+
+    if reader.index != bytes.len() {
+        return Err("unexpected data at the end of the file".to_string());
+    }
+
+    Ok((modules, constants))
+}
+
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+
+impl Writer {
+
+    fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+
+    fn write_usize(&mut self, value: usize) {
+        self.bytes.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+
+    /// Write a length-prefixed UTF-8 string
+    fn write_str(&mut self, value: &str) {
+        self.write_usize(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+
+    fn write_constant(&mut self, constant: &Object) {
+        match &constant.value {
+            Value::Int(value) => {
+                self.bytes.push(CONST_INT);
+                self.bytes.extend_from_slice(&value.to_le_bytes());
+            },
+            Value::Float(value) => {
+                self.bytes.push(CONST_FLOAT);
+                self.bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+            },
+            Value::String(value) => {
+                self.bytes.push(CONST_STRING);
+                self.write_str(value);
+            },
+            Value::Bool(value) => {
+                self.bytes.push(CONST_BOOL);
+                self.bytes.push(*value as u8);
+            },
+            Value::None => {
+                self.bytes.push(CONST_NONE);
+            },
+            _ => unreachable!("The constant pool only contains literals"),
+        }
+    }
+
+
+    fn write_code_object(&mut self, code_object: &CodeObject) {
+        assert!(code_object.is_compiled(), "Functions must be compiled before the program is serialized");
+
+        self.write_str(&code_object.name);
+        self.write_usize(code_object.params.len());
+        for param in &code_object.params {
+            self.write_str(param);
+        }
+        self.write_usize(code_object.required_params);
+        self.bytes.push(code_object.has_rest as u8);
+        self.write_usize(code_object.captures.len());
+        for capture in &code_object.captures {
+            self.write_str(capture);
+        }
+        self.bytes.push(code_object.is_function as u8);
+        self.write_usize(code_object.line);
+
+        self.write_usize(code_object.code().len());
+        self.bytes.extend_from_slice(code_object.code());
+
+        self.write_usize(code_object.lines().len());
+        for (start, line) in code_object.lines() {
+            self.write_usize(*start);
+            self.write_usize(*line);
+        }
+
+        // The body scope's symbols are the module's globals for top-level code, which are exported when it's imported
+        // They are sorted so that compiling the same program always produces the same file
+        let mut symbols: Vec<(String, usize)> = code_object.body_scope().get_local_symbols().iter()
+            .map(|(name, symbol)| (name.clone(), symbol.local_id))
+            .collect();
+        symbols.sort_unstable();
+        self.write_usize(symbols.len());
+        for (name, local_id) in symbols {
+            self.write_str(&name);
+            self.write_usize(local_id);
+        }
+
+        self.write_usize(code_object.functions().len());
+        for function in code_object.functions() {
+            self.write_code_object(function);
+        }
+    }
+
+}
+
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+
+impl<'a> Reader<'a> {
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self.index.checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "unexpected end of file".to_string())?;
+
+        let bytes = &self.bytes[self.index .. end];
+        self.index = end;
+        Ok(bytes)
+    }
+
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+
+    fn read_usize(&mut self) -> Result<usize, String> {
+        usize::try_from(self.read_u64()?).map_err(|_| "size out of range".to_string())
+    }
+
+
+    /// Read the number of elements of a sequence, each taking at least element_size bytes.
+    /// A count whose elements can't fit in the rest of the file is rejected before reading them
+    fn read_count(&mut self, element_size: usize) -> Result<usize, String> {
+        let count = self.read_usize()?;
+        count.checked_mul(element_size)
+            .filter(|size| *size <= self.bytes.len() - self.index)
+            .map(|_| count)
+            .ok_or_else(|| format!("count {} is larger than the rest of the file", count))
+    }
+
+
+    fn read_str(&mut self) -> Result<&'a str, String> {
+        let length = self.read_usize()?;
+        std::str::from_utf8(self.read_bytes(length)?).map_err(|_| "invalid UTF-8 string".to_string())
+    }
+
+
+    /// Read a constant into the pool and return its index
+    fn read_constant(&mut self, constants: &mut ConstantPool) -> Result<usize, String> {
+        Ok(match self.read_u8()? {
+            CONST_INT => constants.add_int(self.read_u64()? as i64),
+            CONST_FLOAT => constants.add_float(f64::from_bits(self.read_u64()?)),
+            CONST_STRING => constants.add_string(self.read_str()?),
+            CONST_BOOL => constants.add_bool(self.read_u8()? != 0),
+            CONST_NONE => constants.add_none(),
+            tag => return Err(format!("invalid constant tag {}", tag)),
+        })
+    }
+
+
+    fn read_code_object(&mut self, module_id: ModuleID, constants: &mut ConstantPool) -> Result<Box<CodeObject>, String> {
+        let name = constants.intern(self.read_str()?);
+        let params = (0 .. self.read_count(USIZE_SIZE)?)
+            .map(|_| self.read_str().map(|param| constants.intern(param)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let required_params = self.read_usize()?;
+        let has_rest = self.read_u8()? != 0;
+        if required_params.checked_add(has_rest as usize).is_none_or(|count| count > params.len()) {
+            return Err(format!("function {} has more required parameters than parameters", name));
+        }
+        let captures = (0 .. self.read_count(USIZE_SIZE)?)
+            .map(|_| self.read_str().map(|capture| constants.intern(capture)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let is_function = self.read_u8()? != 0;
+        let line = self.read_usize()?;
+
+        let code_length = self.read_usize()?;
+        let code = self.read_bytes(code_length)?.to_vec();
+
+        let lines = (0 .. self.read_count(2 * USIZE_SIZE)?)
+            .map(|_| Ok((self.read_usize()?, self.read_usize()?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // The scope's symbol count is one more than the largest id
+        let symbols = (0 .. self.read_count(2 * USIZE_SIZE)?)
+            .map(|_| {
+                let name = self.read_str()?.to_string();
+                match self.read_usize()? {
+                    usize::MAX => Err(format!("symbol {} has an id out of range", name)),
+                    local_id => Ok((name, local_id)),
+                }
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+
+        let mut code_object = Box::new(CodeObject::new(name, params, CodeBlock::with_symbols(symbols, module_id), is_function, line));
+        code_object.required_params = required_params;
+        code_object.has_rest = has_rest;
+        code_object.captures = captures;
+        let functions = (0 .. self.read_count(MIN_CODE_OBJECT_SIZE)?)
+            .map(|_| self.read_code_object(module_id, constants))
+            .collect::<Result<Vec<_>, String>>()?;
+        code_object.compiled = CompiledCode { code, lines, functions, scopes: Vec::new() }.into();
+
+        Ok(code_object)
+    }
+
+}
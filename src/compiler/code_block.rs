@@ -1,14 +1,41 @@
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use super::code_node::CodeNode;
-use super::syntax_tree::SyntaxTree;
+use super::module::ModuleID;
 
 
-/// Represents a scope source code block
+/// Represents the symbol table of a scope source code block
 pub struct CodeBlock {
-    pub nodes: Vec<CodeNode>,
-    local_symbols: HashMap<String, usize>,
-    parent_context: Option<*mut CodeBlock>,
+    /// Every symbol declared with each name, in declaration order.
+    /// A redeclared name shadows the previous symbol, which keeps its slot until the scope is left.
+    /// Symbols are declared through shared references, since the child blocks point to their parent
+    local_symbols: RefCell<HashMap<String, Vec<Symbol>>>,
+    /// The number of symbols declared in this scope, including the shadowed ones, which is the id of the next symbol
+    symbol_count: Cell<usize>,
+    parent_context: Option<*const CodeBlock>,
+    /// Whether this block is the body of a function.
+    /// The locals of the enclosing scopes are not reachable from a function body
+    is_function_body: bool,
+    /// For a function body, the number of symbols declared in each enclosing scope where the function is declared,
+    /// from the parent scope to the global scope. The body is compiled later, but only sees the symbols declared before
+    visible_counts: Vec<usize>,
+    /// The module this block belongs to
+    pub module_id: ModuleID,
+}
+
+
+/// A symbol declared in a scope
+#[derive(Clone)]
+pub struct Symbol {
+    pub local_id: usize,
+    /// Whether the symbol can be assigned to after its declaration, which constants can't
+    pub is_mutable: bool,
+    /// The constant pool index of the value of a constant initialized with a literal.
+    /// Loads of the constant are replaced with the value
+    pub inline_value: Option<usize>,
+    /// For a module bound with import, the names of the module's constants, which can't be assigned to through it
+    pub module_constants: Option<Rc<[String]>>,
 }
 
 
@@ -17,68 +44,164 @@ pub struct CodeBlock {
 pub enum ScopeType {
     Local { local_id: usize },
     Outer { local_id: usize, scope_offset: usize },
-    Global { global_id: usize },
+    Global { module_id: ModuleID, global_id: usize },
+    /// The symbol is a local of a scope enclosing the current function, which cannot be loaded at runtime
+    Enclosing,
 }
 
 
 impl CodeBlock {
 
-    pub fn from_syntax_tree(syntax_tree: &mut SyntaxTree, source: &str, context: Option<*mut CodeBlock>) -> CodeBlock {
-        let mut this = CodeBlock {
-            nodes: Vec::new(),
-            local_symbols: HashMap::new(),
-            parent_context: context,
+    /// The block is boxed so that its child blocks can keep a stable pointer to it
+    pub fn new(parent_context: Option<*const CodeBlock>, is_function_body: bool, module_id: ModuleID) -> Box<CodeBlock> {
+        let visible_counts = match parent_context {
+            Some(context) if is_function_body => unsafe { (*context).symbol_counts() },
+            _ => Vec::new(),
         };
-        
-        for syntax_node in &mut syntax_tree.statements {
-            this.nodes.push(CodeNode::from_syntax_node(syntax_node, source, &this));
-        }
 
-        this
+        Box::new(CodeBlock {
+            local_symbols: RefCell::new(HashMap::new()),
+            symbol_count: Cell::new(0),
+            parent_context,
+            is_function_body,
+            visible_counts,
+            module_id,
+        })
+    }
+
+
+    /// Create a top-level block with already known symbols, such as the body scope of a deserialized code object
+    pub fn with_symbols(local_symbols: HashMap<String, usize>, module_id: ModuleID) -> Box<CodeBlock> {
+        let symbol_count = local_symbols.values().max().map_or(0, |local_id| local_id + 1);
+
+        Box::new(CodeBlock {
+            local_symbols: RefCell::new(local_symbols.into_iter().map(
+                |(name, local_id)| (name, vec![Symbol { local_id, is_mutable: true, inline_value: None, module_constants: None }])
+            ).collect()),
+            symbol_count: Cell::new(symbol_count),
+            parent_context: None,
+            is_function_body: false,
+            visible_counts: Vec::new(),
+            module_id,
+        })
+    }
+
+
+    pub fn parent_context(&self) -> Option<*const CodeBlock> {
+        self.parent_context
+    }
+
+
+    /// Return the latest symbol declared with each name in this scope
+    pub fn get_local_symbols(&self) -> Vec<(String, Symbol)> {
+        self.local_symbols.borrow().iter()
+            .map(|(name, symbols)| (name.clone(), symbols.last().unwrap().clone()))
+            .collect()
     }
 
 
     /// Declare a new symbol in the local scope symbol table
-    /// Local symbols start at 0 and increase by 1 for each new symbol
+    /// Local symbols start at 0 and increase by 1 for each declaration, in the order they are allocated at runtime.
+    /// Declaring a name again gives it a new id, so the previous symbol is only visible to the code compiled before
     pub fn declare_local(&self, name: &str) -> usize {
-        // Interior mutability
-        let self_mut = unsafe {
-            &mut *(self as *const CodeBlock as *mut CodeBlock)
-        };
+        self.declare(name, true, None, None)
+    }
+
+
+    /// Declare a new constant in the local scope symbol table, with the constant pool index of its value if it's a literal
+    pub fn declare_const(&self, name: &str, inline_value: Option<usize>) -> usize {
+        self.declare(name, false, inline_value, None)
+    }
+
+
+    /// Declare a new symbol bound to an imported module in the local scope symbol table, with the names of the module's constants
+    pub fn declare_module(&self, name: &str, module_constants: Rc<[String]>) -> usize {
+        self.declare(name, true, None, Some(module_constants))
+    }
+
 
-        let local_id = self.local_symbols.len();
-        self_mut.local_symbols.insert(name.to_string(), local_id);
+    fn declare(&self, name: &str, is_mutable: bool, inline_value: Option<usize>, module_constants: Option<Rc<[String]>>) -> usize {
+        let local_id = self.symbol_count.get();
+        self.symbol_count.set(local_id + 1);
+        self.local_symbols.borrow_mut().entry(name.to_string()).or_default().push(Symbol { local_id, is_mutable, inline_value, module_constants });
         local_id
     }
 
 
+    /// Return the number of symbols a function declared in this block can see in this block and each enclosing one
+    fn symbol_counts(&self) -> Vec<usize> {
+        let mut counts = vec![self.symbol_count.get()];
+
+        if self.is_function_body {
+            // The enclosing scopes of a function body are only visible up to where the function is declared
+            counts.extend_from_slice(&self.visible_counts);
+        } else if let Some(context) = self.parent_context {
+            counts.extend(unsafe { (*context).symbol_counts() });
+        }
+
+        counts
+    }
+
+
+    /// Return the symbol with the given name declared in this scope or in the closest enclosing scope declaring it.
+    /// Unlike get_symbol_id, the locals of the scopes enclosing a function are found too
+    pub fn find_symbol(&self, name: &str) -> Option<Symbol> {
+        self.resolve(name, 0, None, false).map(|(symbol, _)| symbol)
+    }
+
+
     /// Search for the given symbol id in all the available scopes
     /// Returns the symbol id and the type of scope it was found in
-    pub fn get_symbol_id(&self, name: &str, call_number: usize) -> Option<ScopeType> {
-        
-        if let Some(id) = self.local_symbols.get(name) {
-            // The symbol was found in this scope
+    pub fn get_symbol_id(&self, name: &str) -> Option<ScopeType> {
+        self.resolve(name, 0, None, false).map(|(_, scope_type)| scope_type)
+    }
+
+
+    /// Search for the symbol from the scope scope_offset levels below this one.
+    /// Once a function body is crossed, visible_counts limits the symbols visible in this scope and the enclosing ones
+    fn resolve(&self, name: &str, scope_offset: usize, visible_counts: Option<&[usize]>, crossed_function: bool) -> Option<(Symbol, ScopeType)> {
+        let is_global_scope = self.parent_context.is_none();
 
-            if call_number == 0 {
-                // If this function was called once, this is the local scope
-                Some(ScopeType::Local { local_id: *id })
-            } else if self.parent_context.is_none() {
-                // If this block has no parent context, this is the global scope
-                Some(ScopeType::Global { global_id: *id })
+        if let Some(symbol) = self.lookup(name, visible_counts.map(|counts| counts[0]), is_global_scope) {
+            // The symbol was found in this scope
+            let scope_type = if scope_offset == 0 {
+                ScopeType::Local { local_id: symbol.local_id }
+            } else if is_global_scope {
+                ScopeType::Global { module_id: self.module_id, global_id: symbol.local_id }
+            } else if crossed_function {
+                // Functions are called from anywhere, so only globals can be reached from their body
+                ScopeType::Enclosing
             } else {
-                // Otherwise, this is an outer scope
-                Some(ScopeType::Outer { local_id: *id, scope_offset: call_number })
-            }
+                ScopeType::Outer { local_id: symbol.local_id, scope_offset }
+            };
+            return Some((symbol, scope_type));
+        }
+
+        // The symbol wasn't found in this scope, so search the parent scope
+        let context = self.parent_context?;
+        let visible_counts = match visible_counts {
+            Some(counts) => Some(&counts[1..]),
+            // The innermost function body has the most restrictive counts, since the enclosing functions are declared before it
+            None if self.is_function_body => Some(self.visible_counts.as_slice()),
+            None => None,
+        };
+        unsafe { (*context).resolve(name, scope_offset + 1, visible_counts, crossed_function || self.is_function_body) }
+    }
 
-        } else if let Some(context) = self.parent_context {
-            // The symbol wasn't found in this scope, so search the parent scope
-            unsafe { (*context).get_symbol_id(name, call_number + 1) }
 
-        } else {
-            // The symbol wasn't found in any scope
-            None
+    /// Return the symbol declared with the given name in this scope.
+    /// With a limit, return the latest symbol declared before it. If allow_later is set,
+    /// a name only declared after the limit resolves to its first declaration, so that functions can call each other
+    fn lookup(&self, name: &str, limit: Option<usize>, allow_later: bool) -> Option<Symbol> {
+        let local_symbols = self.local_symbols.borrow();
+        let symbols = local_symbols.get(name)?;
+
+        match limit {
+            Some(limit) => symbols.iter().rev().find(|symbol| symbol.local_id < limit)
+                .or_else(|| symbols.first().filter(|_| allow_later))
+                .cloned(),
+            None => symbols.last().cloned(),
         }
     }
 
 }
-
@@ -0,0 +1,129 @@
+use std::cell::{OnceCell, RefCell};
+use std::rc::Rc;
+
+use super::code_block::CodeBlock;
+use super::module::ModuleID;
+use super::syntax_node::SyntaxNode;
+use super::syntax_tree::SyntaxTree;
+use crate::utils::byte_code::ByteCode;
+
+
+/// A function body or the top-level code of a module, compiled into one contiguous stream of byte code
+pub struct CodeObject {
+    /// The name of the function, or of the module for top-level code
+    pub name: Rc<str>,
+    /// The names of all the parameters, in order
+    pub params: Vec<Rc<str>>,
+    /// The number of parameters without a default value, which come first
+    pub required_params: usize,
+    /// Whether the last parameter collects the extra positional arguments in a list
+    pub has_rest: bool,
+    /// The names of the locals of the enclosing functions that the function uses.
+    /// They are captured by reference when the function object is created, and stay alive after their scope is left
+    pub captures: Vec<Rc<str>>,
+    /// The symbol table of the body scope
+    body_scope: Box<CodeBlock>,
+    /// The compiled body, set once when it's compiled.
+    /// Lazy functions are compiled while other frames hold references to them, so it can't be set through a mutable reference
+    pub compiled: OnceCell<CompiledCode>,
+    /// The body of the function, if it hasn't been compiled yet
+    pub pending_body: RefCell<Option<SyntaxTree>>,
+    /// The default values of the optional parameters, compiled at the start of the body
+    pub pending_defaults: RefCell<Vec<SyntaxNode>>,
+    pub is_function: bool,
+    /// The line the function is declared at, 0 for top-level code
+    pub line: usize,
+    /// The module this code belongs to
+    pub module_id: ModuleID,
+}
+
+
+/// The output of compiling a code object
+#[derive(Default)]
+pub struct CompiledCode {
+    pub code: ByteCode,
+    /// The first instruction of each run of instructions compiled from the same source line, and that line.
+    /// Sorted by instruction
+    pub lines: Vec<(usize, usize)>,
+    /// The functions declared in this code.
+    /// They are boxed so that function objects can keep a stable pointer to them
+    #[allow(clippy::vec_box)] // The pointers must stay valid when more functions are pushed
+    pub functions: Vec<Box<CodeObject>>,
+    /// The symbol tables of the scopes nested in the body scope.
+    /// They are kept alive because the functions declared in them may be compiled later
+    #[allow(clippy::vec_box)] // The nested scopes and functions point to their parent scope while more scopes are pushed
+    pub scopes: Vec<Box<CodeBlock>>,
+}
+
+
+impl CodeObject {
+
+    pub fn new(name: Rc<str>, params: Vec<Rc<str>>, body_scope: Box<CodeBlock>, is_function: bool, line: usize) -> CodeObject {
+        CodeObject {
+            name,
+            required_params: params.len(),
+            has_rest: false,
+            params,
+            captures: Vec::new(),
+            module_id: body_scope.module_id,
+            body_scope,
+            compiled: OnceCell::new(),
+            pending_body: RefCell::new(None),
+            pending_defaults: RefCell::new(Vec::new()),
+            is_function,
+            line,
+        }
+    }
+
+
+    /// Return the symbol table of the body scope.
+    /// For top-level code, these are the module's globals
+    pub fn body_scope(&self) -> &CodeBlock {
+        &self.body_scope
+    }
+
+
+    /// Return the number of parameters that don't collect the extra positional arguments
+    pub fn named_params(&self) -> usize {
+        self.params.len() - self.has_rest as usize
+    }
+
+
+    pub fn is_compiled(&self) -> bool {
+        self.compiled.get().is_some()
+    }
+
+
+    fn compiled(&self) -> &CompiledCode {
+        self.compiled.get().unwrap_or_else(|| panic!("{} is used before it's compiled", self.name))
+    }
+
+
+    pub fn code(&self) -> &ByteCode {
+        &self.compiled().code
+    }
+
+
+    pub fn lines(&self) -> &[(usize, usize)] {
+        &self.compiled().lines
+    }
+
+
+    pub fn functions(&self) -> &[Box<CodeObject>] {
+        &self.compiled().functions
+    }
+
+
+    /// Return the source line the instruction at the given index was compiled from
+    pub fn get_line(&self, pc: usize) -> usize {
+        let Some(compiled) = self.compiled.get() else {
+            return self.line;
+        };
+
+        match compiled.lines.partition_point(|(start, _)| *start <= pc) {
+            0 => self.line,
+            index => compiled.lines[index - 1].1,
+        }
+    }
+
+}
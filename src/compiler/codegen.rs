@@ -0,0 +1,1020 @@
+use super::code_block::{CodeBlock, ScopeType};
+use super::code_object::{CodeObject, CompiledCode};
+use super::constant_pool::ConstantPool;
+use super::error::{self, CompileError};
+use super::module::ModuleID;
+use super::syntax_node::{MatchArm, MatchPattern, Params, Pattern, SyntaxNode};
+use super::syntax_tree::SyntaxTree;
+use crate::runtime::op_code::OpCode;
+use crate::utils::byte_code::{self, JumpOffset, JUMP_OFFSET_SIZE};
+
+
+/// The name of the code objects of anonymous functions, shown in errors, traces and disassembly
+const LAMBDA_NAME: &str = "<lambda>";
+
+/// The name of the hidden local holding the value of a match statement, which can't collide with an identifier
+const MATCH_SUBJECT_NAME: &str = "<match>";
+
+
+/// The options controlling how source code is compiled
+#[derive(Clone, Copy, Default)]
+pub struct CompileOptions {
+    /// Whether function bodies are compiled the first time they are called instead of ahead of time
+    pub lazy: bool,
+    /// Whether to warn when a declaration shadows a symbol of the same or an enclosing scope
+    pub warn_shadowing: bool,
+}
+
+
+/// The jumps of the loop being compiled
+struct Loop {
+    /// The instruction continue jumps to
+    start: usize,
+    /// The scope depth of the loop, which break and continue return to
+    scope_depth: usize,
+    /// The operands of the break jumps, patched once the end of the loop is known
+    breaks: Vec<usize>,
+}
+
+
+/// Compiles syntax trees into the byte code of a single code object
+struct Compiler<'a> {
+    code_object: &'a CodeObject,
+    /// The output of the compilation, set in the code object once it's done
+    compiled: CompiledCode,
+    constants: &'a mut ConstantPool,
+    source: &'a str,
+    errors: &'a mut Vec<CompileError>,
+    options: CompileOptions,
+    /// The symbol table of the scope being compiled
+    scope: *const CodeBlock,
+    /// The number of scopes entered since the body scope
+    scope_depth: usize,
+    loops: Vec<Loop>,
+}
+
+
+/// Compile the top-level code of a module, collecting the compile errors.
+/// Unless the options are lazy, the module's functions are compiled too
+pub fn compile_module(name: &str, syntax_tree: &mut SyntaxTree, source: &str, module_id: ModuleID, options: CompileOptions, constants: &mut ConstantPool, errors: &mut Vec<CompileError>) -> Box<CodeObject> {
+    let root = Box::new(CodeObject::new(constants.intern(name), Vec::new(), CodeBlock::new(None, false, module_id), false, 0));
+    root.pending_body.replace(Some(std::mem::take(syntax_tree)));
+
+    compile_code_object(&root, source, options, constants, errors);
+
+    root
+}
+
+
+/// Compile a function that was left uncompiled in lazy mode
+pub fn compile_function(function: &CodeObject, source: &str, options: CompileOptions, constants: &mut ConstantPool) -> Result<(), Vec<CompileError>> {
+    let mut errors: Vec<CompileError> = Vec::new();
+
+    compile_code_object(function, source, options, constants, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+
+fn compile_code_object(code_object: &CodeObject, source: &str, options: CompileOptions, constants: &mut ConstantPool, errors: &mut Vec<CompileError>) {
+    let mut body = code_object.pending_body.take().unwrap_or_default();
+    let mut defaults = code_object.pending_defaults.take();
+    let scope = code_object.body_scope() as *const CodeBlock;
+
+    let mut compiler = Compiler {
+        code_object,
+        compiled: CompiledCode::default(),
+        constants,
+        source,
+        errors,
+        options,
+        scope,
+        scope_depth: 0,
+        loops: Vec::new(),
+    };
+
+    // The optional parameters that weren't passed are initialized in order, so that a default value can use the previous parameters
+    let required_params = compiler.code_object.required_params;
+    for (index, default) in defaults.iter_mut().enumerate() {
+        compiler.compile_default(required_params + index, default);
+    }
+
+    for statement in &mut body.statements {
+        compiler.compile_statement(statement);
+    }
+
+    if compiler.code_object.is_function {
+        // Return None if the end of the function is reached
+        let line = compiler.code_object.line;
+        compiler.emit(OpCode::Return, line);
+    }
+
+    // Function bodies are compiled after the code declaring them, so that they can use globals declared later.
+    // A global declared again after the function still resolves to the declaration visible where the function is declared
+    let Compiler { compiled, constants, errors, .. } = compiler;
+    assert!(code_object.compiled.set(compiled).is_ok(), "{} is compiled twice", code_object.name);
+
+    if !options.lazy {
+        for function in code_object.functions() {
+            compile_code_object(function, source, options, constants, errors);
+        }
+    }
+}
+
+
+impl Compiler<'_> {
+
+    fn scope(&self) -> &CodeBlock {
+        unsafe { &*self.scope }
+    }
+
+
+    /// Append an instruction, recording the source line it was compiled from
+    fn emit(&mut self, op_code: OpCode, line: usize) {
+        let pc = self.compiled.code.len();
+        let lines = &mut self.compiled.lines;
+
+        match lines.last_mut() {
+            Some((_, last_line)) if *last_line == line => {},
+            // The previous instruction range is empty, so it can be reused
+            Some((start, last_line)) if *start == pc => *last_line = line,
+            _ => lines.push((pc, line)),
+        }
+
+        self.compiled.code.push(op_code as u8);
+    }
+
+
+    fn emit_operand(&mut self, operand: &[u8]) {
+        self.compiled.code.extend_from_slice(operand);
+    }
+
+
+    /// Append a jump instruction whose target is not known yet.
+    /// Return the index of its offset operand, to be patched later
+    fn emit_jump(&mut self, op_code: OpCode, line: usize) -> usize {
+        self.emit(op_code, line);
+        let operand = self.compiled.code.len();
+        self.emit_operand(&byte_code::raw_from_jump(0));
+        operand
+    }
+
+
+    /// Set the target of the jump with the given operand index to the next instruction
+    fn patch_jump(&mut self, operand: usize) {
+        let offset = self.jump_offset(operand + JUMP_OFFSET_SIZE, self.compiled.code.len());
+        self.compiled.code[operand .. operand + JUMP_OFFSET_SIZE].copy_from_slice(&byte_code::raw_from_jump(offset));
+    }
+
+
+    /// Append a jump instruction back to an already compiled instruction
+    fn emit_jump_back(&mut self, op_code: OpCode, target: usize, line: usize) {
+        self.emit(op_code, line);
+        let offset = self.jump_offset(self.compiled.code.len() + JUMP_OFFSET_SIZE, target);
+        self.emit_operand(&byte_code::raw_from_jump(offset));
+    }
+
+
+    fn jump_offset(&self, from: usize, to: usize) -> JumpOffset {
+        JumpOffset::try_from(to as i64 - from as i64).expect("Jump offset doesn't fit in the byte code")
+    }
+
+
+    /// Load the constant with the given index in the constant pool
+    fn emit_const(&mut self, index: usize, line: usize) {
+        self.emit(OpCode::LoadConst, line);
+        self.emit_operand(&byte_code::raw_from_usize(index));
+    }
+
+
+    /// Get the member with the given name of the object on top of the stack.
+    /// The name is stored in the constant pool
+    fn emit_get_member(&mut self, name: &str, line: usize) {
+        let index = self.constants.add_string(name);
+
+        self.emit(OpCode::GetMember, line);
+        self.emit_operand(&byte_code::raw_from_usize(index));
+    }
+
+
+    /// Declare a new local in the current scope, allocate it and load a reference to it
+    fn emit_declaration(&mut self, name: &str, line: usize) {
+        self.check_shadowing(self.scope(), name, line);
+        let local_id = self.scope().declare_local(name);
+        self.emit_allocation(local_id, line);
+    }
+
+
+    /// Allocate the local with the given id, which must be the last one declared, and load a reference to it
+    fn emit_allocation(&mut self, local_id: usize, line: usize) {
+        self.emit(OpCode::Allocate, line);
+        self.emit(OpCode::LoadLocalRef, line);
+        self.emit_operand(&byte_code::raw_from_usize(local_id));
+    }
+
+
+    /// Warn if declaring the name in the given scope would shadow a symbol, when requested by the options
+    fn check_shadowing(&self, scope: &CodeBlock, name: &str, line: usize) {
+        if !self.options.warn_shadowing {
+            return;
+        }
+
+        match scope.get_symbol_id(name) {
+            Some(ScopeType::Local { .. }) => error::shadowed_symbol(name, line, self.source, true),
+            Some(ScopeType::Outer { .. } | ScopeType::Global { .. }) => error::shadowed_symbol(name, line, self.source, false),
+            // The locals of the scopes enclosing a function aren't visible from its body, so they can't be shadowed
+            Some(ScopeType::Enclosing) | None => {},
+        }
+    }
+
+
+    /// Compile the statements of a block in a new scope
+    fn compile_block(&mut self, body: &mut SyntaxTree, line: usize) {
+        self.enter_scope(line);
+
+        for statement in &mut body.statements {
+            self.compile_statement(statement);
+        }
+
+        self.leave_scope(line);
+    }
+
+
+    fn enter_scope(&mut self, line: usize) {
+        let scope = CodeBlock::new(Some(self.scope), false, self.code_object.module_id);
+        self.scope = scope.as_ref() as *const CodeBlock;
+        self.compiled.scopes.push(scope);
+        self.scope_depth += 1;
+
+        self.emit(OpCode::PushScope, line);
+    }
+
+
+    fn leave_scope(&mut self, line: usize) {
+        self.scope = self.scope().parent_context().unwrap();
+        self.scope_depth -= 1;
+
+        self.emit(OpCode::PopScope, line);
+    }
+
+
+    /// Pop the scopes entered inside the innermost loop, before jumping out of it.
+    /// Return false if there's no loop to jump out of
+    fn emit_loop_exit(&mut self, keyword: &str, line: usize) -> bool {
+        let loop_scope_depth = match self.loops.last() {
+            Some(current_loop) => current_loop.scope_depth,
+            None => {
+                self.errors.push(error::outside_loop(keyword, line, self.source));
+                return false;
+            },
+        };
+
+        for _ in loop_scope_depth .. self.scope_depth {
+            self.emit(OpCode::PopScope, line);
+        }
+
+        true
+    }
+
+
+    fn compile_statement(&mut self, node: &mut SyntaxNode) {
+        match node {
+
+            SyntaxNode::Assign { left, right, line, .. } => {
+                self.compile_expression(right);
+                self.compile_store(left, *line);
+            },
+
+            SyntaxNode::AssignAdd { left, right, line, .. } => self.compile_update(OpCode::Add, left, right, *line),
+            SyntaxNode::AssignSub { left, right, line, .. } => self.compile_update(OpCode::Sub, left, right, *line),
+            SyntaxNode::AssignMul { left, right, line, .. } => self.compile_update(OpCode::Mul, left, right, *line),
+            SyntaxNode::AssignDiv { left, right, line, .. } => self.compile_update(OpCode::Div, left, right, *line),
+            SyntaxNode::AssignMod { left, right, line, .. } => self.compile_update(OpCode::Mod, left, right, *line),
+
+            SyntaxNode::Let { pattern, line, .. } => {
+                self.declare_pattern(pattern, *line);
+            },
+
+            SyntaxNode::Const { name, value, line, .. } => {
+                let inline_value = self.literal_constant(value);
+                self.compile_expression(value);
+
+                // The constant is stored like a variable too, so that other modules can access it as a member
+                self.check_shadowing(self.scope(), name, *line);
+                let local_id = self.scope().declare_const(name, inline_value);
+                self.emit_allocation(local_id, *line);
+                self.emit(OpCode::StoreTop, *line);
+            },
+
+            SyntaxNode::Fun { name, params, body, line, .. } => {
+                // Declare and allocate the function first, so that it can call itself
+                self.check_shadowing(self.scope(), name, *line);
+                let local_id = self.scope().declare_local(name);
+                self.emit(OpCode::Allocate, *line);
+
+                self.emit_function(name, params, body, *line);
+                self.emit(OpCode::LoadLocalRef, *line);
+                self.emit_operand(&byte_code::raw_from_usize(local_id));
+                self.emit(OpCode::StoreTop, *line);
+            },
+
+            SyntaxNode::Import { alias, module_id, constants, line, .. } => {
+                // Bind the module namespace to a new local symbol
+                self.emit(OpCode::ImportModule, *line);
+                self.emit_operand(&byte_code::raw_from_usize(*module_id));
+                self.check_shadowing(self.scope(), alias, *line);
+                let local_id = self.scope().declare_module(alias, constants.as_slice().into());
+                self.emit_allocation(local_id, *line);
+                self.emit(OpCode::StoreTop, *line);
+            },
+
+            SyntaxNode::ImportFrom { names, module_id, constants, line, .. } => {
+                // Bind each imported member to a new local symbol, which stays a constant if the member is one
+                for name in names.iter() {
+                    self.emit(OpCode::ImportModule, *line);
+                    self.emit_operand(&byte_code::raw_from_usize(*module_id));
+                    self.emit_get_member(name, *line);
+                    if constants.contains(name) {
+                        self.check_shadowing(self.scope(), name, *line);
+                        let local_id = self.scope().declare_const(name, None);
+                        self.emit_allocation(local_id, *line);
+                    } else {
+                        self.emit_declaration(name, *line);
+                    }
+                    self.emit(OpCode::StoreTop, *line);
+                }
+            },
+
+            SyntaxNode::If { condition, body, else_node, line, .. } |
+            SyntaxNode::Elif { condition, body, else_node, line, .. } => {
+                self.compile_expression(condition);
+                let to_else = self.emit_jump(OpCode::JumpIfFalse, *line);
+
+                self.compile_block(body, *line);
+
+                if let Some(else_node) = else_node {
+                    let to_end = self.emit_jump(OpCode::Jump, *line);
+                    self.patch_jump(to_else);
+                    self.compile_statement(else_node);
+                    self.patch_jump(to_end);
+                } else {
+                    self.patch_jump(to_else);
+                }
+            },
+
+            SyntaxNode::Else { body, line, .. } |
+            SyntaxNode::Scope { body, line, .. } => {
+                self.compile_block(body, *line);
+            },
+
+            SyntaxNode::While { condition, body, line, .. } => {
+                let start = self.compiled.code.len();
+                self.compile_expression(condition);
+                let to_end = self.emit_jump(OpCode::JumpIfFalse, *line);
+
+                self.loops.push(Loop { start, scope_depth: self.scope_depth, breaks: Vec::new() });
+                self.compile_block(body, *line);
+                self.emit_jump_back(OpCode::Jump, start, *line);
+
+                self.patch_jump(to_end);
+                for break_jump in self.loops.pop().unwrap().breaks {
+                    self.patch_jump(break_jump);
+                }
+            },
+
+            SyntaxNode::For { pattern, iterable, body, line, .. } => {
+                self.compile_expression(iterable);
+                self.emit(OpCode::GetIter, *line);
+
+                // The loop variables live in their own scope around the body, entered again for each element,
+                // so that the functions declared in the body capture the variables of their iteration
+                let start = self.compiled.code.len();
+                self.enter_scope(*line);
+                let to_end = self.emit_jump(OpCode::ForIter, *line);
+                let local_ids = self.declare_pattern(pattern, *line);
+                self.store_pattern(pattern, &mut local_ids.into_iter(), *line);
+
+                // Jumping out of the loop leaves the scope of the iteration too
+                self.loops.push(Loop { start, scope_depth: self.scope_depth - 1, breaks: Vec::new() });
+                self.compile_block(body, *line);
+                self.emit(OpCode::PopScope, *line);
+                self.emit_jump_back(OpCode::Jump, start, *line);
+
+                // The last iteration scope is entered before the iterator turns out to be exhausted
+                self.patch_jump(to_end);
+                self.leave_scope(*line);
+                for break_jump in self.loops.pop().unwrap().breaks {
+                    self.patch_jump(break_jump);
+                }
+
+                // Discard the exhausted iterator
+                self.emit(OpCode::Pop, *line);
+            },
+
+            SyntaxNode::Match { value, arms, line, .. } => {
+                // The matched value lives in its own scope around the arms, so that each arm can test it
+                self.enter_scope(*line);
+                self.compile_expression(value);
+                // The hidden local is declared in a new scope, so it can't shadow anything
+                let local_id = self.scope().declare_local(MATCH_SUBJECT_NAME);
+                self.emit_allocation(local_id, *line);
+                self.emit(OpCode::StoreTop, *line);
+
+                let mut to_end: Vec<usize> = Vec::new();
+                for arm in arms.iter_mut() {
+                    self.compile_match_arm(arm, &mut to_end);
+                }
+
+                for jump in to_end {
+                    self.patch_jump(jump);
+                }
+                self.leave_scope(*line);
+            },
+
+            SyntaxNode::Break { line, .. } => {
+                if self.emit_loop_exit("break", *line) {
+                    let break_jump = self.emit_jump(OpCode::Jump, *line);
+                    self.loops.last_mut().unwrap().breaks.push(break_jump);
+                }
+            },
+
+            SyntaxNode::Continue { line, .. } => {
+                if self.emit_loop_exit("continue", *line) {
+                    let start = self.loops.last().unwrap().start;
+                    self.emit_jump_back(OpCode::Jump, start, *line);
+                }
+            },
+
+            SyntaxNode::Return { value, line, .. } => {
+                if !self.code_object.is_function {
+                    self.errors.push(error::return_outside_function(*line, self.source));
+                    return;
+                }
+
+                if let Some(value) = value {
+                    // A call in tail position reuses the frame of the function
+                    if let SyntaxNode::Call { function, arguments, line: call_line, .. } = value.as_mut() {
+                        self.compile_call(function, arguments, *call_line, true);
+                        return;
+                    }
+
+                    self.compile_expression(value);
+                    self.emit(OpCode::ReturnValue, *line);
+                } else {
+                    self.emit(OpCode::Return, *line);
+                }
+            },
+
+            _ => {
+                // The value of an expression statement is not used
+                self.compile_expression(node);
+                self.emit(OpCode::Pop, node.get_line());
+            },
+        }
+    }
+
+
+    /// Compile an assignment that applies the operation to the target's current value.
+    /// The target is evaluated twice, once to load its value and once to store the result
+    fn compile_update(&mut self, op_code: OpCode, target: &mut SyntaxNode, value: &mut SyntaxNode, line: usize) {
+        self.compile_expression(target);
+        self.compile_expression(value);
+        self.emit(op_code, line);
+        self.compile_store(target, line);
+    }
+
+
+    /// Compile the target of an assignment, which consumes the value on top of the stack.
+    /// A list of targets unpacks the value into them
+    fn compile_store(&mut self, target: &mut SyntaxNode, line: usize) {
+        match target {
+            SyntaxNode::Let { pattern, line, .. } => {
+                let local_ids = self.declare_pattern(pattern, *line);
+                self.store_pattern(pattern, &mut local_ids.into_iter(), *line);
+            },
+            SyntaxNode::List { elements, line, .. } => {
+                self.emit_unpack(elements.len(), *line);
+                for element in elements.iter_mut() {
+                    self.compile_store(element, *line);
+                }
+            },
+            SyntaxNode::Subscript { .. } => {
+                let count = self.compile_element_path(target);
+                self.emit(OpCode::StoreSubscript, line);
+                self.emit_operand(&byte_code::raw_from_usize(count));
+            },
+            _ => {
+                self.check_mutable(target);
+                self.compile_expression(target);
+                self.emit(OpCode::StoreTop, line);
+            },
+        }
+    }
+
+
+    /// Push the list a subscript target is part of, then the indices leading to the element.
+    /// Return the number of indices
+    fn compile_element_path(&mut self, target: &mut SyntaxNode) -> usize {
+        match target {
+            SyntaxNode::Subscript { iterable, index, .. } => {
+                let count = self.compile_element_path(iterable);
+                self.compile_expression(index);
+                count + 1
+            },
+            _ => {
+                self.compile_expression(target);
+                0
+            },
+        }
+    }
+
+
+    /// Report an error if the target of an assignment is a constant, or a constant of a module bound with import
+    fn check_mutable(&mut self, target: &SyntaxNode) {
+        match target {
+            SyntaxNode::Identifier { value: name, line, .. } if self.scope().find_symbol(name).is_some_and(|symbol| !symbol.is_mutable) => {
+                self.errors.push(error::assign_to_constant(name, *line, self.source));
+            },
+            SyntaxNode::Member { object, name, line, .. } => {
+                if let SyntaxNode::Identifier { value: module, .. } = object.as_ref() {
+                    let is_constant = self.scope().find_symbol(module)
+                        .and_then(|symbol| symbol.module_constants)
+                        .is_some_and(|constants| constants.contains(name));
+                    if is_constant {
+                        self.errors.push(error::assign_to_constant(&format!("{}.{}", module, name), *line, self.source));
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+
+    /// Add the value of a literal to the constant pool and return its index.
+    /// Return None if the node is not a literal
+    fn literal_constant(&mut self, node: &SyntaxNode) -> Option<usize> {
+        match node {
+            SyntaxNode::Int { value, .. } => Some(self.constants.add_int(*value)),
+            SyntaxNode::Float { value, .. } => Some(self.constants.add_float(*value)),
+            SyntaxNode::String { value, .. } => Some(self.constants.add_string(value)),
+            SyntaxNode::Boolean { value, .. } => Some(self.constants.add_bool(*value)),
+            SyntaxNode::None { .. } => Some(self.constants.add_none()),
+            SyntaxNode::Neg { operand, .. } => match operand.as_ref() {
+                SyntaxNode::Int { value, .. } => value.checked_neg().map(|value| self.constants.add_int(value)),
+                SyntaxNode::Float { value, .. } => Some(self.constants.add_float(-value)),
+                _ => None,
+            },
+            SyntaxNode::Parenthesis { child, .. } => self.literal_constant(child),
+            _ => None,
+        }
+    }
+
+
+    /// Declare and allocate the locals bound by the pattern.
+    /// Return their local ids, in the order of the pattern's names
+    fn declare_pattern(&mut self, pattern: &Pattern, line: usize) -> Vec<usize> {
+        pattern.names().into_iter().map(|name| {
+            self.check_shadowing(self.scope(), name, line);
+            let local_id = self.scope().declare_local(name);
+            self.emit(OpCode::Allocate, line);
+            local_id
+        }).collect()
+    }
+
+
+    /// Store the value on top of the stack into the locals of the pattern, which are already declared
+    fn store_pattern(&mut self, pattern: &Pattern, local_ids: &mut impl Iterator<Item = usize>, line: usize) {
+        match pattern {
+            Pattern::Name(_) => {
+                self.emit(OpCode::LoadLocalRef, line);
+                self.emit_operand(&byte_code::raw_from_usize(local_ids.next().unwrap()));
+                self.emit(OpCode::StoreTop, line);
+            },
+            Pattern::List(elements) => {
+                self.emit_unpack(elements.len(), line);
+                for element in elements {
+                    self.store_pattern(element, local_ids, line);
+                }
+            },
+        }
+    }
+
+
+    /// Unpack the list on top of the stack into its elements, the first one on top
+    fn emit_unpack(&mut self, count: usize, line: usize) {
+        self.emit(OpCode::UnpackList, line);
+        self.emit_operand(&byte_code::raw_from_usize(count));
+    }
+
+
+    /// Compile a logical operator whose right operand is skipped if the left one decides the result.
+    /// The deciding operand is the result
+    fn compile_short_circuit(&mut self, left: &mut SyntaxNode, right: &mut SyntaxNode, op_code: OpCode, line: usize) {
+        self.compile_expression(left);
+        let to_end = self.emit_jump(op_code, line);
+        self.compile_expression(right);
+        self.patch_jump(to_end);
+    }
+
+
+    /// Declare a function nested in the code object and push a new function object for it.
+    /// Its body is compiled after the code object's, or when it's first called in lazy mode
+    fn emit_function(&mut self, name: &str, params: &mut Params, body: &mut SyntaxTree, line: usize) {
+        // The parameters are the first locals of the function body
+        let body_scope = CodeBlock::new(Some(self.scope), true, self.code_object.module_id);
+        for param in params.names.iter() {
+            self.check_shadowing(&body_scope, param, line);
+            body_scope.declare_local(param);
+        }
+
+        // The locals of the enclosing functions are captured by reference when the function object is created.
+        // The function's own name is declared before, so it's captured too and the function can call itself
+        let mut used: Vec<String> = Vec::new();
+        for node in params.defaults.iter_mut().chain(body.statements.iter_mut()) {
+            collect_identifiers(node, &mut used);
+        }
+        let captures: Vec<String> = used.into_iter()
+            .filter(|used| matches!(body_scope.get_symbol_id(used), Some(ScopeType::Enclosing)))
+            .filter(|used| body_scope.find_symbol(used).is_some_and(|symbol| symbol.inline_value.is_none()))
+            .collect();
+        for capture in &captures {
+            self.compile_expression(&mut SyntaxNode::Identifier { value: capture.clone(), line });
+        }
+
+        let name = self.constants.intern(name);
+        let param_names = params.names.iter().map(|param| self.constants.intern(param)).collect();
+        let mut function = Box::new(CodeObject::new(name, param_names, body_scope, true, line));
+        function.required_params = params.required_count();
+        function.has_rest = params.has_rest;
+        function.captures = captures.iter().map(|capture| self.constants.intern(capture)).collect();
+        function.pending_defaults.replace(std::mem::take(&mut params.defaults));
+        function.pending_body.replace(Some(std::mem::take(body)));
+        let function_index = self.compiled.functions.len();
+        self.compiled.functions.push(function);
+
+        self.emit(OpCode::MakeFunction, line);
+        self.emit_operand(&byte_code::raw_from_usize(function_index));
+    }
+
+
+    /// Store the default value in the parameter with the given index, unless it was passed
+    fn compile_default(&mut self, param_index: usize, default: &mut SyntaxNode) {
+        let line = default.get_line();
+
+        self.emit(OpCode::MissingArgument, line);
+        self.emit_operand(&[param_index as u8]);
+        let to_end = self.emit_jump(OpCode::JumpIfFalse, line);
+
+        self.compile_expression(default);
+        let param = self.code_object.params[param_index].to_string();
+        self.compile_expression(&mut SyntaxNode::Identifier { value: param, line });
+        self.emit(OpCode::StoreTop, line);
+
+        self.patch_jump(to_end);
+    }
+
+
+    /// Compile an arm of a match statement, which jumps to the next arm if the value doesn't match it.
+    /// The bindings of the arm live in their own scope, which both paths leave
+    fn compile_match_arm(&mut self, arm: &mut MatchArm, to_end: &mut Vec<usize>) {
+        let line = arm.line;
+        let mut to_next_arm: Vec<usize> = Vec::new();
+
+        self.enter_scope(line);
+        self.compile_pattern_test(&mut arm.pattern, &mut Vec::new(), &mut to_next_arm, line);
+        self.compile_pattern_bindings(&arm.pattern, &mut Vec::new(), line);
+
+        if let Some(guard) = &mut arm.guard {
+            self.compile_expression(guard);
+            to_next_arm.push(self.emit_jump(OpCode::JumpIfFalse, line));
+        }
+
+        self.compile_block(&mut arm.body, line);
+        self.leave_scope(line);
+        to_end.push(self.emit_jump(OpCode::Jump, line));
+
+        for jump in to_next_arm {
+            self.patch_jump(jump);
+        }
+        self.emit(OpCode::PopScope, line);
+    }
+
+
+    /// Compile the tests of the pattern against the element of the matched value at the path of list indices.
+    /// The code falls through if the element matches, and jumps to one of the failure jumps otherwise
+    fn compile_pattern_test(&mut self, pattern: &mut MatchPattern, path: &mut Vec<usize>, failures: &mut Vec<usize>, line: usize) {
+        match pattern {
+            MatchPattern::Wildcard |
+            MatchPattern::Binding(_) => {},
+
+            MatchPattern::Literal(literal) => {
+                self.emit_match_subject(path, line);
+                self.compile_expression(literal);
+                self.emit(OpCode::Equal, line);
+                failures.push(self.emit_jump(OpCode::JumpIfFalse, line));
+            },
+
+            MatchPattern::List(elements) => {
+                self.emit_match_subject(path, line);
+                self.emit(OpCode::MatchList, line);
+                self.emit_operand(&byte_code::raw_from_usize(elements.len()));
+                failures.push(self.emit_jump(OpCode::JumpIfFalse, line));
+
+                for (index, element) in elements.iter_mut().enumerate() {
+                    path.push(index);
+                    self.compile_pattern_test(element, path, failures, line);
+                    path.pop();
+                }
+            },
+
+            MatchPattern::Alternatives(alternatives) => {
+                // An alternative that matches skips the next ones, and the last one fails the whole pattern
+                let (last, others) = alternatives.split_last_mut().unwrap();
+                let mut to_matched: Vec<usize> = Vec::new();
+
+                for alternative in others {
+                    let mut to_next_alternative: Vec<usize> = Vec::new();
+                    self.compile_pattern_test(alternative, path, &mut to_next_alternative, line);
+                    to_matched.push(self.emit_jump(OpCode::Jump, line));
+
+                    for jump in to_next_alternative {
+                        self.patch_jump(jump);
+                    }
+                }
+
+                self.compile_pattern_test(last, path, failures, line);
+                for jump in to_matched {
+                    self.patch_jump(jump);
+                }
+            },
+        }
+    }
+
+
+    /// Declare the names bound by the pattern and store the matching elements of the matched value into them
+    fn compile_pattern_bindings(&mut self, pattern: &MatchPattern, path: &mut Vec<usize>, line: usize) {
+        match pattern {
+            MatchPattern::Binding(name) => {
+                self.emit_match_subject(path, line);
+                self.emit_declaration(name, line);
+                self.emit(OpCode::StoreTop, line);
+            },
+
+            MatchPattern::List(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    path.push(index);
+                    self.compile_pattern_bindings(element, path, line);
+                    path.pop();
+                }
+            },
+
+            // Alternatives can't bind names
+            MatchPattern::Wildcard |
+            MatchPattern::Literal(_) |
+            MatchPattern::Alternatives(_) => {},
+        }
+    }
+
+
+    /// Push a reference to the element of the matched value at the path of list indices
+    fn emit_match_subject(&mut self, path: &[usize], line: usize) {
+        self.compile_expression(&mut SyntaxNode::Identifier { value: MATCH_SUBJECT_NAME.to_string(), line });
+
+        for &index in path {
+            let index = self.constants.add_int(index as i64);
+            self.emit_const(index, line);
+            self.emit(OpCode::Subscript, line);
+        }
+    }
+
+
+    /// Compile a call of the function with the arguments.
+    /// A tail call returns the return value of the call from the current function
+    fn compile_call(&mut self, function: &mut SyntaxNode, arguments: &mut [SyntaxNode], line: usize, is_tail: bool) {
+        self.compile_expression(function);
+
+        // The argument count of CallFunction is a single byte
+        let is_plain = arguments.len() <= u8::MAX as usize && arguments.iter().all(
+            |argument| !matches!(argument, SyntaxNode::SpreadArgument { .. } | SyntaxNode::KeywordArgument { .. })
+        );
+        if !is_plain {
+            self.compile_call_ex(arguments, line, is_tail);
+            return;
+        }
+
+        for argument in arguments.iter_mut() {
+            self.compile_expression(argument);
+        }
+
+        // Call the function with n arguments
+        self.emit(if is_tail { OpCode::TailCall } else { OpCode::CallFunction }, line);
+        self.emit_operand(&[arguments.len() as u8]);
+    }
+
+
+    /// Compile a call with spread or keyword arguments.
+    /// The positional arguments are collected in a list, which the spread lists are appended to
+    fn compile_call_ex(&mut self, arguments: &mut [SyntaxNode], line: usize, is_tail: bool) {
+        let keywords_start = arguments.iter().position(|argument| matches!(argument, SyntaxNode::KeywordArgument { .. })).unwrap_or(arguments.len());
+        let (positional, keywords) = arguments.split_at_mut(keywords_start);
+
+        let leading = positional.iter().position(|argument| matches!(argument, SyntaxNode::SpreadArgument { .. })).unwrap_or(positional.len());
+        for argument in positional[.. leading].iter_mut() {
+            self.compile_expression(argument);
+        }
+        self.emit(OpCode::MakeList, line);
+        self.emit_operand(&byte_code::raw_from_usize(leading));
+
+        for argument in positional[leading ..].iter_mut() {
+            match argument {
+                SyntaxNode::SpreadArgument { value, .. } => self.compile_expression(value),
+                _ => {
+                    let argument_line = argument.get_line();
+                    self.compile_expression(argument);
+                    self.emit(OpCode::MakeList, argument_line);
+                    self.emit_operand(&byte_code::raw_from_usize(1));
+                },
+            }
+            self.emit(OpCode::ExtendList, line);
+        }
+
+        for keyword in keywords.iter_mut() {
+            if let SyntaxNode::KeywordArgument { name, value, line, .. } = keyword {
+                let index = self.constants.add_string(name);
+                self.emit_const(index, *line);
+                self.compile_expression(value);
+            }
+        }
+
+        self.emit(if is_tail { OpCode::TailCallEx } else { OpCode::CallFunctionEx }, line);
+        self.emit_operand(&byte_code::raw_from_usize(keywords.len()));
+    }
+
+
+    fn compile_expression(&mut self, node: &mut SyntaxNode) {
+        match node {
+
+            SyntaxNode::Add { left, right, line, .. } |
+            SyntaxNode::Sub { left, right, line, .. } |
+            SyntaxNode::Mul { left, right, line, .. } |
+            SyntaxNode::Div { left, right, line, .. } |
+            SyntaxNode::Mod { left, right, line, .. } |
+            SyntaxNode::Less { left, right, line, .. } |
+            SyntaxNode::Greater { left, right, line, .. } |
+            SyntaxNode::LessEqual { left, right, line, .. } |
+            SyntaxNode::GreaterEqual { left, right, line, .. } |
+            SyntaxNode::Equal { left, right, line, .. } |
+            SyntaxNode::NotEqual { left, right, line, .. } |
+            SyntaxNode::Subscript { iterable: left, index: right, line, .. } => {
+                let line = *line;
+                self.compile_expression(left);
+                self.compile_expression(right);
+
+                let op_code = match node {
+                    SyntaxNode::Add { .. } => OpCode::Add,
+                    SyntaxNode::Sub { .. } => OpCode::Sub,
+                    SyntaxNode::Mul { .. } => OpCode::Mul,
+                    SyntaxNode::Div { .. } => OpCode::Div,
+                    SyntaxNode::Mod { .. } => OpCode::Mod,
+                    SyntaxNode::Less { .. } => OpCode::Less,
+                    SyntaxNode::Greater { .. } => OpCode::Greater,
+                    SyntaxNode::LessEqual { .. } => OpCode::LessEqual,
+                    SyntaxNode::GreaterEqual { .. } => OpCode::GreaterEqual,
+                    SyntaxNode::Equal { .. } => OpCode::Equal,
+                    SyntaxNode::NotEqual { .. } => OpCode::NotEqual,
+                    _ => OpCode::Subscript,
+                };
+                self.emit(op_code, line);
+            },
+
+            SyntaxNode::And { left, right, line, .. } => {
+                self.compile_short_circuit(left, right, OpCode::JumpIfFalseOrPop, *line);
+            },
+
+            SyntaxNode::Or { left, right, line, .. } => {
+                self.compile_short_circuit(left, right, OpCode::JumpIfTrueOrPop, *line);
+            },
+
+            // Anonymous functions aren't bound to a name, so they can't call themselves
+            SyntaxNode::Lambda { params, body, line, .. } => {
+                self.emit_function(LAMBDA_NAME, params, body, *line);
+            },
+
+            SyntaxNode::Not { operand, line, .. } => {
+                self.compile_expression(operand);
+                self.emit(OpCode::Not, *line);
+            },
+
+            SyntaxNode::Neg { operand, line, .. } => {
+                self.compile_expression(operand);
+                self.emit(OpCode::Negate, *line);
+            },
+
+            SyntaxNode::Parenthesis { child, .. } => {
+                // Parentheses are just a wrapper for the child node
+                self.compile_expression(child);
+            },
+
+            SyntaxNode::Call { function, arguments, line, .. } => {
+                self.compile_call(function, arguments, *line, false);
+            },
+
+            SyntaxNode::Member { object, name, line, .. } => {
+                self.compile_expression(object);
+                self.emit_get_member(name, *line);
+            },
+
+            SyntaxNode::Int { value, line, .. } => {
+                let index = self.constants.add_int(*value);
+                self.emit_const(index, *line);
+            },
+
+            SyntaxNode::Float { value, line, .. } => {
+                let index = self.constants.add_float(*value);
+                self.emit_const(index, *line);
+            },
+
+            SyntaxNode::String { value, line, .. } => {
+                let index = self.constants.add_string(value);
+                self.emit_const(index, *line);
+            },
+
+            SyntaxNode::Boolean { value, line, .. } => {
+                let index = self.constants.add_bool(*value);
+                self.emit_const(index, *line);
+            },
+
+            SyntaxNode::None { line, .. } => {
+                let index = self.constants.add_none();
+                self.emit_const(index, *line);
+            },
+
+            SyntaxNode::List { elements, line, .. } => {
+                for element in elements.iter_mut() {
+                    self.compile_expression(element);
+                }
+
+                self.emit(OpCode::MakeList, *line);
+                self.emit_operand(&byte_code::raw_from_usize(elements.len()));
+            },
+
+            SyntaxNode::Identifier { value: name, line, .. } => {
+                let line = *line;
+
+                // Literal constants are loaded directly from the constant pool, even from the functions declared in their scope
+                if let Some(index) = self.scope().find_symbol(name).and_then(|symbol| symbol.inline_value) {
+                    self.emit_const(index, line);
+                    return;
+                }
+
+                match self.scope().get_symbol_id(name) {
+                    Some(ScopeType::Local { local_id }) => {
+                        self.emit(OpCode::LoadLocalRef, line);
+                        self.emit_operand(&byte_code::raw_from_usize(local_id));
+                    },
+                    Some(ScopeType::Global { module_id, global_id }) => {
+                        self.emit(OpCode::LoadGlobalRef, line);
+                        self.emit_operand(&byte_code::raw_from_usize(module_id));
+                        self.emit_operand(&byte_code::raw_from_usize(global_id));
+                    },
+                    Some(ScopeType::Outer { local_id, scope_offset }) => {
+                        self.emit(OpCode::LoadOffsetRef, line);
+                        self.emit_operand(&byte_code::raw_from_usize(local_id));
+                        self.emit_operand(&byte_code::raw_from_usize(scope_offset));
+                    },
+                    Some(ScopeType::Enclosing) => match self.code_object.captures.iter().position(|capture| **capture == **name) {
+                        Some(index) => {
+                            self.emit(OpCode::LoadCapture, line);
+                            self.emit_operand(&byte_code::raw_from_usize(index));
+                        },
+                        None => self.errors.push(error::enclosing_symbol(name, line, self.source)),
+                    },
+                    None => {
+                        self.errors.push(error::undeclared_symbol(name, line, self.source));
+                    },
+                }
+            },
+
+            _ => self.errors.push(error::not_an_expression(node.get_name(), node.get_line(), self.source)),
+        }
+    }
+
+}
+
+
+/// Add the names of the identifiers used in the node, including in the functions nested in it, to names, in order of first use
+fn collect_identifiers(node: &mut SyntaxNode, names: &mut Vec<String>) {
+    if let SyntaxNode::Identifier { value, .. } = node {
+        if !names.contains(value) {
+            names.push(value.clone());
+        }
+    }
+
+    for child in node.children_mut() {
+        collect_identifiers(child, names);
+    }
+}
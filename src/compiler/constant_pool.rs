@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::lang::object::{Object, TypeCode, Value};
+use crate::utils::interner::Interner;
+
+
+/// Identifies a constant by value, so that identical constants are stored once.
+/// Floats are compared by their bits
+#[derive(PartialEq, Eq, Hash)]
+enum ConstantKey {
+    Int(i64),
+    Float(u64),
+    String(Rc<str>),
+    Bool(bool),
+    None,
+}
+
+
+/// The constants of the whole program, shared by the code objects of all its modules.
+/// LoadConst references the constants by index
+#[derive(Default)]
+pub struct ConstantPool {
+    constants: Vec<Object>,
+    indices: HashMap<ConstantKey, usize>,
+    /// Shares the storage of identical string literals and identifiers
+    interner: Interner,
+}
+
+
+impl ConstantPool {
+
+    pub fn new() -> ConstantPool {
+        ConstantPool::default()
+    }
+
+
+    pub fn get(&self, index: usize) -> &Object {
+        &self.constants[index]
+    }
+
+
+    /// Return all the constants, ordered by index
+    pub fn constants(&self) -> &[Object] {
+        &self.constants
+    }
+
+
+    /// Return the shared copy of the identifier
+    pub fn intern(&mut self, name: &str) -> Rc<str> {
+        self.interner.intern(name)
+    }
+
+
+    pub fn add_int(&mut self, value: i64) -> usize {
+        self.add(ConstantKey::Int(value), || Object::new(TypeCode::Int, Value::Int(value)))
+    }
+
+
+    pub fn add_float(&mut self, value: f64) -> usize {
+        self.add(ConstantKey::Float(value.to_bits()), || Object::new(TypeCode::Float, Value::Float(value)))
+    }
+
+
+    pub fn add_string(&mut self, value: &str) -> usize {
+        let value = self.interner.intern(value);
+        self.add(ConstantKey::String(Rc::clone(&value)), || Object::new(TypeCode::String, Value::String(value)))
+    }
+
+
+    pub fn add_bool(&mut self, value: bool) -> usize {
+        self.add(ConstantKey::Bool(value), || Object::new(TypeCode::Bool, Value::Bool(value)))
+    }
+
+
+    pub fn add_none(&mut self) -> usize {
+        self.add(ConstantKey::None, Object::none)
+    }
+
+
+    /// Return the index of the constant, adding it to the pool if it's not there yet
+    fn add(&mut self, key: ConstantKey, make_constant: impl FnOnce() -> Object) -> usize {
+        if let Some(index) = self.indices.get(&key) {
+            return *index;
+        }
+
+        let index = self.constants.len();
+        self.constants.push(make_constant());
+        self.indices.insert(key, index);
+        index
+    }
+
+}
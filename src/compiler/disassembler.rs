@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+use super::code_object::CodeObject;
+use super::jit::Jit;
+use super::module::{ModuleContent, ModuleID};
+use crate::runtime::op_code::OpCode;
+use crate::utils::byte_code::{self, ByteCode};
+
+
+/// Return a human-readable listing of the byte code of all the modules of the program.
+///
+/// Each instruction is listed with its index, its decoded operands and, when it changes, the source line it was compiled from.
+/// The functions of a code object are listed after it
+pub fn disassemble(jit: &Jit) -> String {
+    let mut output = String::new();
+
+    for (module_id, module) in jit.modules.iter().enumerate() {
+        match &module.content {
+            ModuleContent::Source { root, .. } => {
+                writeln!(output, "Module {} ({})", module.name, module.path.display()).unwrap();
+                disassemble_code_object(&mut output, root, jit, module_id);
+            },
+            ModuleContent::Native { .. } => {
+                writeln!(output, "Module {} (built-in)\n", module.name).unwrap();
+            },
+        }
+    }
+
+    output
+}
+
+
+fn disassemble_code_object(output: &mut String, code_object: &CodeObject, jit: &Jit, module_id: ModuleID) {
+    if code_object.is_function {
+        write!(output, "Function {}({}) at line {}", code_object.name, code_object.params.join(", "), code_object.line).unwrap();
+        if !code_object.captures.is_empty() {
+            write!(output, ", capturing {}", code_object.captures.join(", ")).unwrap();
+        }
+        writeln!(output).unwrap();
+    } else {
+        writeln!(output, "Top-level code of {}", code_object.name).unwrap();
+    }
+
+    if !code_object.is_compiled() {
+        writeln!(output, "    <not compiled yet>\n").unwrap();
+        return;
+    }
+
+    let code: &ByteCode = code_object.code();
+    let mut last_line: Option<usize> = None;
+    let mut pc: usize = 0;
+
+    while pc < code.len() {
+        let instruction_pc = pc;
+        let op_code = OpCode::from(code[pc]);
+        pc += 1;
+
+        let operands = describe_operands(op_code, pc, code_object, jit, module_id);
+        pc += op_code.operand_size();
+
+        // Only show the line when it changes, so that the instructions of a line are grouped together
+        let line = code_object.get_line(instruction_pc);
+        let line_column = if last_line == Some(line) { String::new() } else { line.to_string() };
+        last_line = Some(line);
+
+        let instruction = format!("{:>6} {:>6}  {:<16} {}", line_column, instruction_pc, op_code.to_string(), operands);
+        // Instructions without operands would leave trailing spaces
+        writeln!(output, "{}", instruction.trim_end()).unwrap();
+    }
+
+    output.push('\n');
+
+    for function in code_object.functions() {
+        disassemble_code_object(output, function, jit, module_id);
+    }
+}
+
+
+/// Decode the operands of the instruction whose operands start at the given index
+pub fn describe_operands(op_code: OpCode, operand: usize, code_object: &CodeObject, jit: &Jit, module_id: ModuleID) -> String {
+    let code = code_object.code();
+
+    match op_code {
+        OpCode::LoadLocalRef => {
+            let (local_id, _) = byte_code::get_raw_id(operand, code);
+            format!("local {}", local_id)
+        },
+
+        OpCode::LoadOffsetRef => {
+            let (local_id, to_add) = byte_code::get_raw_id(operand, code);
+            let (scope_offset, _) = byte_code::get_raw_id(operand + to_add, code);
+            format!("local {}, {} scope{} up", local_id, scope_offset, if scope_offset == 1 { "" } else { "s" })
+        },
+
+        OpCode::LoadGlobalRef => {
+            let (global_module_id, to_add) = byte_code::get_raw_id(operand, code);
+            let (global_id, _) = byte_code::get_raw_id(operand + to_add, code);
+            match global_name(jit, global_module_id, global_id) {
+                Some(name) if global_module_id == module_id => format!("global {} ({})", global_id, name),
+                Some(name) => format!("module {}, global {} ({}.{})", global_module_id, global_id, jit.get_module(global_module_id).name, name),
+                None => format!("module {}, global {}", global_module_id, global_id),
+            }
+        },
+
+        OpCode::LoadConst => {
+            let (index, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ({})", index, jit.constants.get(index).describe())
+        },
+
+        OpCode::GetMember => {
+            let (index, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ({})", index, jit.constants.get(index).describe())
+        },
+
+        OpCode::CallFunction | OpCode::TailCall => {
+            let arg_count = code[operand];
+            format!("{} argument{}", arg_count, if arg_count == 1 { "" } else { "s" })
+        },
+
+        OpCode::CallFunctionEx | OpCode::TailCallEx => {
+            let (keyword_count, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} keyword argument{}", keyword_count, if keyword_count == 1 { "" } else { "s" })
+        },
+
+        OpCode::MissingArgument => {
+            let param_index = code[operand] as usize;
+            match code_object.params.get(param_index) {
+                Some(param) => format!("{} ({})", param_index, param),
+                None => param_index.to_string(),
+            }
+        },
+
+        OpCode::MakeFunction => {
+            let (index, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ({})", index, code_object.functions()[index].name)
+        },
+
+        OpCode::LoadCapture => {
+            let (index, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ({})", index, code_object.captures[index])
+        },
+
+        OpCode::MakeList | OpCode::UnpackList | OpCode::MatchList => {
+            let (count, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} element{}", count, if count == 1 { "" } else { "s" })
+        },
+
+        OpCode::StoreSubscript => {
+            let (count, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ind{}", count, if count == 1 { "ex" } else { "ices" })
+        },
+
+        OpCode::ImportModule => {
+            let (imported_id, _) = byte_code::get_raw_usize(operand, code);
+            format!("{} ({})", imported_id, jit.get_module(imported_id).name)
+        },
+
+        OpCode::Jump | OpCode::JumpIfFalse | OpCode::JumpIfFalseOrPop | OpCode::JumpIfTrueOrPop | OpCode::ForIter => {
+            let (offset, to_add) = byte_code::get_raw_jump(operand, code);
+            format!("to {}", (operand + to_add) as i64 + offset as i64)
+        },
+
+        _ => String::new(),
+    }
+}
+
+
+/// Return the name of the global with the given id in the given module, if the module was compiled from source
+fn global_name(jit: &Jit, module_id: ModuleID, global_id: usize) -> Option<String> {
+    match &jit.get_module(module_id).content {
+        ModuleContent::Source { root, .. } => root.body_scope().get_local_symbols().iter()
+            .find(|(_, symbol)| symbol.local_id == global_id)
+            .map(|(name, _)| name.clone()),
+        ModuleContent::Native { .. } => None,
+    }
+}
+
@@ -0,0 +1,334 @@
+use std::fmt::Write;
+
+use super::syntax_node::{MatchArm, Pattern, SyntaxNode};
+use super::syntax_tree::SyntaxTree;
+use super::token::Token;
+use crate::stdlib::json;
+
+
+/// A field of a syntax node, used to print the node generically
+enum Field<'a> {
+    Int(i64),
+    Float(f64),
+    String(&'a str),
+    Bool(bool),
+    Strings(&'a [String]),
+    Pattern(&'a Pattern),
+    Arms(&'a [MatchArm]),
+    Node(&'a SyntaxNode),
+    OptionalNode(Option<&'a SyntaxNode>),
+    Nodes(&'a [SyntaxNode]),
+    Tree(&'a SyntaxTree),
+}
+
+
+/// Return the token stream, one token per line, with the line of each token
+pub fn tokens_to_text(tokens: &[Token]) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "{:>6}  token", "line").unwrap();
+    for token in tokens {
+        writeln!(output, "{:>6}  {}", token.get_line(), token).unwrap();
+    }
+
+    output
+}
+
+
+/// Return the token stream as a JSON array of objects with the kind, value and line of each token
+pub fn tokens_to_json(tokens: &[Token]) -> String {
+    let mut output = String::from("[\n");
+
+    for (index, token) in tokens.iter().enumerate() {
+        output.push_str("  {\"token\": ");
+        json::write_string(&token_kind(token), &mut output);
+
+        if let Some(value) = token_value(token) {
+            output.push_str(", \"value\": ");
+            write_json_field(&value, 1, &mut output);
+        }
+
+        write!(output, ", \"line\": {}}}", token.get_line()).unwrap();
+
+        output.push_str(if index + 1 < tokens.len() { ",\n" } else { "\n" });
+    }
+
+    output.push_str("]\n");
+    output
+}
+
+
+/// Return an indented tree of the statements' syntax nodes, with the line of each node
+pub fn syntax_tree_to_text(syntax_tree: &SyntaxTree) -> String {
+    let mut output = String::new();
+
+    for statement in &syntax_tree.statements {
+        write_text_node(statement, 0, &mut output);
+    }
+
+    output
+}
+
+
+/// Return the statements' syntax nodes as a JSON array.
+/// Each node is an object with its kind, its line and its fields
+pub fn syntax_tree_to_json(syntax_tree: &SyntaxTree) -> String {
+    let mut output = String::new();
+    write_json_nodes(&syntax_tree.statements, 0, &mut output);
+    output.push('\n');
+    output
+}
+
+
+/// Return the kind of the token.
+/// The token's Display representation is its kind, followed by its value in parentheses if it has one
+fn token_kind(token: &Token) -> String {
+    let display = token.to_string();
+    match display.split_once('(') {
+        Some((kind, _)) => kind.to_string(),
+        None => display,
+    }
+}
+
+
+fn token_value(token: &Token) -> Option<Field<'_>> {
+    match token {
+        Token::Numeric { value, .. } => Some(Field::String(value)),
+        Token::Integer { value, .. } => Some(Field::Int(*value)),
+        Token::Float { value, .. } => Some(Field::Float(*value)),
+        Token::String { value, .. } => Some(Field::String(value)),
+        Token::Boolean { value, .. } => Some(Field::Bool(*value)),
+        Token::Identifier { value, .. } => Some(Field::String(value)),
+        _ => None,
+    }
+}
+
+
+/// Return the fields of the node, except its line.
+/// The module ids of imports are omitted, since they are only resolved after parsing
+fn node_fields(node: &SyntaxNode) -> Vec<(&'static str, Field<'_>)> {
+    match node {
+        SyntaxNode::Add { left, right, .. } |
+        SyntaxNode::Sub { left, right, .. } |
+        SyntaxNode::Mul { left, right, .. } |
+        SyntaxNode::Div { left, right, .. } |
+        SyntaxNode::Mod { left, right, .. } |
+        SyntaxNode::Assign { left, right, .. } |
+        SyntaxNode::AssignAdd { left, right, .. } |
+        SyntaxNode::AssignSub { left, right, .. } |
+        SyntaxNode::AssignMul { left, right, .. } |
+        SyntaxNode::AssignDiv { left, right, .. } |
+        SyntaxNode::AssignMod { left, right, .. } |
+        SyntaxNode::And { left, right, .. } |
+        SyntaxNode::Or { left, right, .. } |
+        SyntaxNode::Less { left, right, .. } |
+        SyntaxNode::Greater { left, right, .. } |
+        SyntaxNode::LessEqual { left, right, .. } |
+        SyntaxNode::GreaterEqual { left, right, .. } |
+        SyntaxNode::Equal { left, right, .. } |
+        SyntaxNode::NotEqual { left, right, .. } => vec![("left", Field::Node(left)), ("right", Field::Node(right))],
+
+        SyntaxNode::Not { operand, .. } |
+        SyntaxNode::Neg { operand, .. } => vec![("operand", Field::Node(operand))],
+        SyntaxNode::Subscript { iterable, index, .. } => vec![("iterable", Field::Node(iterable)), ("index", Field::Node(index))],
+        SyntaxNode::Call { function, arguments, .. } => vec![("function", Field::Node(function)), ("arguments", Field::Nodes(arguments))],
+        SyntaxNode::Member { object, name, .. } => vec![("object", Field::Node(object)), ("name", Field::String(name))],
+        SyntaxNode::SpreadArgument { value, .. } => vec![("value", Field::Node(value))],
+        SyntaxNode::KeywordArgument { name, value, .. } => vec![("name", Field::String(name)), ("value", Field::Node(value))],
+
+        SyntaxNode::Int { value, .. } => vec![("value", Field::Int(*value))],
+        SyntaxNode::Float { value, .. } => vec![("value", Field::Float(*value))],
+        SyntaxNode::String { value, .. } => vec![("value", Field::String(value))],
+        SyntaxNode::Boolean { value, .. } => vec![("value", Field::Bool(*value))],
+        SyntaxNode::List { elements, .. } => vec![("elements", Field::Nodes(elements))],
+        SyntaxNode::Identifier { value, .. } => vec![("value", Field::String(value))],
+        SyntaxNode::None { .. } => vec![],
+
+        SyntaxNode::Fun { name, params, body, .. } => vec![("name", Field::String(name)), ("params", Field::Strings(&params.names)), ("defaults", Field::Nodes(&params.defaults)), ("rest", Field::Bool(params.has_rest)), ("body", Field::Tree(body))],
+        SyntaxNode::Lambda { params, body, .. } => vec![("params", Field::Strings(&params.names)), ("defaults", Field::Nodes(&params.defaults)), ("rest", Field::Bool(params.has_rest)), ("body", Field::Tree(body))],
+        SyntaxNode::Return { value, .. } => vec![("value", Field::OptionalNode(value.as_deref()))],
+        SyntaxNode::If { condition, body, else_node, .. } |
+        SyntaxNode::Elif { condition, body, else_node, .. } => vec![("condition", Field::Node(condition)), ("body", Field::Tree(body)), ("else", Field::OptionalNode(else_node.as_deref()))],
+        SyntaxNode::Else { body, .. } => vec![("body", Field::Tree(body))],
+        SyntaxNode::While { condition, body, .. } => vec![("condition", Field::Node(condition)), ("body", Field::Tree(body))],
+        SyntaxNode::For { pattern, iterable, body, .. } => vec![("pattern", Field::Pattern(pattern)), ("iterable", Field::Node(iterable)), ("body", Field::Tree(body))],
+        SyntaxNode::Match { value, arms, .. } => vec![("value", Field::Node(value)), ("arms", Field::Arms(arms))],
+        SyntaxNode::Break { .. } => vec![],
+        SyntaxNode::Continue { .. } => vec![],
+        SyntaxNode::Let { pattern, .. } => vec![("pattern", Field::Pattern(pattern))],
+        SyntaxNode::Const { name, value, .. } => vec![("name", Field::String(name)), ("value", Field::Node(value))],
+        SyntaxNode::Import { path, alias, .. } => vec![("path", Field::String(path)), ("alias", Field::String(alias))],
+        SyntaxNode::ImportFrom { path, names, .. } => vec![("path", Field::String(path)), ("names", Field::Strings(names))],
+
+        SyntaxNode::Scope { body, .. } => vec![("body", Field::Tree(body))],
+        SyntaxNode::Parenthesis { child, .. } => vec![("child", Field::Node(child))],
+    }
+}
+
+
+fn write_text_node(node: &SyntaxNode, depth: usize, output: &mut String) {
+    let fields = node_fields(node);
+    let indent = "  ".repeat(depth);
+
+    // Scalar fields are shown on the node's line, nested nodes below it
+    let scalars: Vec<String> = fields.iter().filter_map(|(name, field)| match field {
+        Field::Int(value) => Some(format!("{}={}", name, value)),
+        Field::Float(value) => Some(format!("{}={:?}", name, value)),
+        Field::String(value) => Some(format!("{}={:?}", name, value)),
+        Field::Bool(value) => Some(format!("{}={}", name, value)),
+        Field::Strings(values) => Some(format!("{}={:?}", name, values)),
+        Field::Pattern(pattern) => Some(format!("{}={}", name, pattern)),
+        _ => None,
+    }).collect();
+
+    write!(output, "{}{}", indent, node.get_name()).unwrap();
+    for scalar in &scalars {
+        write!(output, " {}", scalar).unwrap();
+    }
+    writeln!(output, " (line {})", node.get_line()).unwrap();
+
+    for (name, field) in &fields {
+        match field {
+            Field::Node(child) => {
+                writeln!(output, "{}  {}:", indent, name).unwrap();
+                write_text_node(child, depth + 2, output);
+            },
+            Field::OptionalNode(Some(child)) => {
+                writeln!(output, "{}  {}:", indent, name).unwrap();
+                write_text_node(child, depth + 2, output);
+            },
+            Field::Nodes(children) => {
+                writeln!(output, "{}  {}: {} node{}", indent, name, children.len(), if children.len() == 1 { "" } else { "s" }).unwrap();
+                for child in children.iter() {
+                    write_text_node(child, depth + 2, output);
+                }
+            },
+            Field::Tree(tree) => {
+                writeln!(output, "{}  {}: {} statement{}", indent, name, tree.statements.len(), if tree.statements.len() == 1 { "" } else { "s" }).unwrap();
+                for child in &tree.statements {
+                    write_text_node(child, depth + 2, output);
+                }
+            },
+            Field::Arms(arms) => {
+                writeln!(output, "{}  {}: {} arm{}", indent, name, arms.len(), if arms.len() == 1 { "" } else { "s" }).unwrap();
+                for arm in arms.iter() {
+                    write_text_arm(arm, depth + 2, output);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+fn write_text_arm(arm: &MatchArm, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    writeln!(output, "{}Arm pattern={} (line {})", indent, arm.pattern, arm.line).unwrap();
+    if let Some(guard) = &arm.guard {
+        writeln!(output, "{}  guard:", indent).unwrap();
+        write_text_node(guard, depth + 2, output);
+    }
+    writeln!(output, "{}  body: {} statement{}", indent, arm.body.statements.len(), if arm.body.statements.len() == 1 { "" } else { "s" }).unwrap();
+    for child in &arm.body.statements {
+        write_text_node(child, depth + 2, output);
+    }
+}
+
+
+fn write_json_node(node: &SyntaxNode, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth + 1);
+
+    output.push_str("{\n");
+    write!(output, "{}\"node\": ", indent).unwrap();
+    json::write_string(node.get_name(), output);
+    write!(output, ",\n{}\"line\": {}", indent, node.get_line()).unwrap();
+
+    for (name, field) in node_fields(node) {
+        write!(output, ",\n{}\"{}\": ", indent, name).unwrap();
+        write_json_field(&field, depth + 1, output);
+    }
+
+    write!(output, "\n{}}}", "  ".repeat(depth)).unwrap();
+}
+
+
+fn write_json_nodes(nodes: &[SyntaxNode], depth: usize, output: &mut String) {
+    if nodes.is_empty() {
+        output.push_str("[]");
+        return;
+    }
+
+    output.push_str("[\n");
+    for (index, node) in nodes.iter().enumerate() {
+        output.push_str(&"  ".repeat(depth + 1));
+        write_json_node(node, depth + 1, output);
+        output.push_str(if index + 1 < nodes.len() { ",\n" } else { "\n" });
+    }
+    write!(output, "{}]", "  ".repeat(depth)).unwrap();
+}
+
+
+fn write_json_field(field: &Field, depth: usize, output: &mut String) {
+    match field {
+        Field::Int(value) => write!(output, "{}", value).unwrap(),
+        // JSON has no representation for infinite or NaN floats
+        Field::Float(value) if !value.is_finite() => output.push_str("null"),
+        Field::Float(value) => write!(output, "{:?}", value).unwrap(),
+        Field::String(value) => json::write_string(value, output),
+        Field::Bool(value) => write!(output, "{}", value).unwrap(),
+        Field::Strings(values) => {
+            output.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                json::write_string(value, output);
+            }
+            output.push(']');
+        },
+        // A name is a string and a list pattern is an array of patterns
+        Field::Pattern(Pattern::Name(name)) => json::write_string(name, output),
+        Field::Pattern(Pattern::List(elements)) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_json_field(&Field::Pattern(element), depth, output);
+            }
+            output.push(']');
+        },
+        Field::Node(node) => write_json_node(node, depth, output),
+        Field::OptionalNode(Some(node)) => write_json_node(node, depth, output),
+        Field::OptionalNode(None) => output.push_str("null"),
+        Field::Nodes(nodes) => write_json_nodes(nodes, depth, output),
+        Field::Tree(tree) => write_json_nodes(&tree.statements, depth, output),
+        Field::Arms(arms) => write_json_arms(arms, depth, output),
+    }
+}
+
+
+/// Write the arms as a JSON array of objects with the pattern, the line, the guard and the body of each arm
+fn write_json_arms(arms: &[MatchArm], depth: usize, output: &mut String) {
+    if arms.is_empty() {
+        output.push_str("[]");
+        return;
+    }
+
+    let indent = "  ".repeat(depth + 2);
+
+    output.push_str("[\n");
+    for (index, arm) in arms.iter().enumerate() {
+        write!(output, "{}{{\n{}\"pattern\": ", "  ".repeat(depth + 1), indent).unwrap();
+        json::write_string(&arm.pattern.to_string(), output);
+        write!(output, ",\n{}\"line\": {}", indent, arm.line).unwrap();
+        write!(output, ",\n{}\"guard\": ", indent).unwrap();
+        write_json_field(&Field::OptionalNode(arm.guard.as_ref()), depth + 2, output);
+        write!(output, ",\n{}\"body\": ", indent).unwrap();
+        write_json_nodes(&arm.body.statements, depth + 2, output);
+        write!(output, "\n{}}}", "  ".repeat(depth + 1)).unwrap();
+        output.push_str(if index + 1 < arms.len() { ",\n" } else { "\n" });
+    }
+    write!(output, "{}]", "  ".repeat(depth)).unwrap();
+}
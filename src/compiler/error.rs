@@ -1,5 +1,7 @@
 use super::token::Token;
+use std::path::{Path, PathBuf};
 use crate::utils::string::get_lines;
+use crate::runtime::verifier::VerifyError;
 
 
 fn error(message: String) -> ! {
@@ -8,6 +10,28 @@ fn error(message: String) -> ! {
 }
 
 
+/// Print a warning about code that compiles but is likely a mistake
+fn warning(message: String) {
+    eprintln!("Warning: {}", message);
+}
+
+
+/// An error found while compiling the program ahead of time.
+/// Compile errors are collected so that they can all be reported at once
+pub struct CompileError {
+    pub message: String,
+}
+
+
+/// Print all the compile errors and exit
+pub fn report_compile_errors(errors: &[CompileError]) -> ! {
+    for compile_error in errors {
+        eprintln!("{}", compile_error.message);
+    }
+    error(format!("Compilation failed with {} error{}", errors.len(), if errors.len() == 1 { "" } else { "s" }));
+}
+
+
 pub fn invalid_character(c: char, line: usize, source: &str, hint: &str) -> ! {
     error(format!("Invalid character '{}' at line {}:\n{}\n\n{}", c, line, get_lines(source, line, 1), hint));
 }
@@ -18,13 +42,19 @@ pub fn invalid_escape_sequence(c: char, line: usize, source: &str, hint: &str) -
 }
 
 
-pub fn expected_operand(line: usize, operator: &str, source: &str) -> ! {
-    error(format!("Expected operand for operator '{}' at line {}:\n{}\n\n", operator, line, get_lines(source, line, 1)));
+pub fn invalid_numeric_literal(literal: &str, line: usize, source: &str, hint: &str) -> ! {
+    error(format!("Invalid numeric literal '{}' at line {}:\n{}\n\n{}", literal, line, get_lines(source, line, 1), hint));
+}
+
+
+pub fn unexpected_token(expected: &str, found: Option<&Token>, line: usize, source: &str) -> ! {
+    let found = found.map_or("the end of the file".to_string(), Token::describe);
+    error(format!("Expected {}, found {} at line {}:\n{}\n\n", expected, found, line, get_lines(source, line, 1)));
 }
 
 
-pub fn invalid_token_to_syntax_node_conversion(token: &Token, source: &str) -> ! {
-    error(format!("Cannot convert token '{}' at line {} to syntax node:\n{}\n\n", token, token.get_line(), get_lines(source, token.get_line(), 1)));
+pub fn invalid_assignment_target(target: &str, line: usize, source: &str) -> ! {
+    error(format!("Cannot assign to {} at line {}:\n{}\n\nOnly variables, subscripts and members, or lists of them with '=', can be assigned to.", target, line, get_lines(source, line, 1)));
 }
 
 
@@ -43,57 +73,131 @@ pub fn unmatched_curly_brace(line: usize, source: &str) -> ! {
 }
 
 
-pub fn too_many_statements_in_parentheses(line: usize, source: &str) -> ! {
-    error(format!("Parentheses can only contain one statement at line {}:\n{}\n\n", line, get_lines(source, line, 1)));
+pub fn too_many_parameters(line: usize, source: &str, max: usize) -> ! {
+    error(format!("Function can only have {} parameters at line {}:\n{}\n\n", max, line, get_lines(source, line, 1)));
 }
 
 
-pub fn too_many_statements_in_square_brackets(line: usize, source: &str) -> ! {
-    error(format!("Square brackets can only contain one statement at line {}:\n{}\n\n", line, get_lines(source, line, 1)));
+/// Report a parameter declared twice, or a keyword argument passed twice if is_keyword is set
+pub fn duplicate_parameter(line: usize, source: &str, param_name: &str, is_keyword: bool) -> ! {
+    let kind = if is_keyword { "keyword argument" } else { "parameter" };
+    error(format!("Duplicate {} '{}' at line {}:\n{}\n\n", kind, param_name, line, get_lines(source, line, 1)));
 }
 
 
-pub fn too_many_parameters(line: usize, source: &str, max: usize) -> ! {
-    error(format!("Function can only have {} parameters at line {}:\n{}\n\n", max, line, get_lines(source, line, 1)));
+pub fn duplicate_binding(name: &str, line: usize, source: &str) -> ! {
+    error(format!("Duplicate variable '{}' at line {}:\n{}\n\nA name can only be bound once by the same pattern.", name, line, get_lines(source, line, 1)));
+}
+
+
+pub fn binding_in_alternatives(name: &str, line: usize, source: &str) -> ! {
+    error(format!("Cannot bind '{}' in alternative patterns at line {}:\n{}\n\nThe alternatives could bind different names. Use a separate arm for each alternative instead.", name, line, get_lines(source, line, 1)));
+}
+
+
+pub fn match_without_wildcard(line: usize, source: &str) {
+    warning(format!("Match without a wildcard arm at line {}:\n{}\n\nValues that match none of the arms are ignored. Add a `_ => ...` arm to handle them.\n", line, get_lines(source, line, 1)));
+}
+
+
+pub fn shadowed_symbol(name: &str, line: usize, source: &str, is_same_scope: bool) {
+    let shadowed = if is_same_scope { "a previous declaration in the same scope" } else { "a declaration of an enclosing scope" };
+    warning(format!("Declaration of '{}' shadows {} at line {}:\n{}\n\nThe previous symbol is no longer reachable by name from here. Rename one of them if that's not intended.\n", name, shadowed, line, get_lines(source, line, 1)));
+}
+
+
+pub fn missing_default_value(param_name: &str, line: usize, source: &str) -> ! {
+    error(format!("Parameter '{}' has no default value at line {}:\n{}\n\nParameters without a default value must come before the ones with a default value.", param_name, line, get_lines(source, line, 1)));
+}
+
+
+pub fn positional_after_keyword(line: usize, source: &str) -> ! {
+    error(format!("Positional argument after a keyword argument at line {}:\n{}\n\nPositional and spread arguments must come before the keyword arguments.", line, get_lines(source, line, 1)));
+}
+
+
+pub fn assign_to_constant(name: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("Cannot assign to constant '{}' at line {}:\n{}\n\nConstants declared with 'const' cannot be reassigned. Declare it with 'let' instead.", name, line, get_lines(source, line, 1)),
+    }
+}
+
+
+pub fn undeclared_symbol(symbol: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("Undeclared symbol '{}' at line {}:\n{}\n\n", symbol, line, get_lines(source, line, 1)),
+    }
+}
+
+
+pub fn enclosing_symbol(symbol: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("Cannot use '{}' at line {}:\n{}\n\nA function declared inside another function or a block can only use the variables around it that are declared before the function.", symbol, line, get_lines(source, line, 1)),
+    }
+}
+
+
+pub fn not_an_expression(node_name: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("{} cannot be used as an expression at line {}:\n{}\n\n", node_name, line, get_lines(source, line, 1)),
+    }
+}
+
+
+pub fn return_outside_function(line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("Cannot return outside of a function at line {}:\n{}\n\n", line, get_lines(source, line, 1)),
+    }
 }
 
 
-pub fn duplicate_parameter(line: usize, source: &str, param_name: &str) -> ! {
-    error(format!("Duplicate parameter '{}' at line {}:\n{}\n\n", param_name, line, get_lines(source, line, 1)));
+pub fn outside_loop(keyword: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("'{}' outside of a loop at line {}:\n{}\n\n", keyword, line, get_lines(source, line, 1)),
+    }
 }
 
 
-pub fn empty_subscription(line: usize, source: &str) -> ! {
-    error(format!("Missing index for subscript operator at line {}:\n{}\n\n", line, get_lines(source, line, 1)));
+pub fn missing_import_member(module: &str, name: &str, line: usize, source: &str) -> CompileError {
+    CompileError {
+        message: format!("Module '{}' has no member '{}' at line {}:\n{}\n\n", module, name, line, get_lines(source, line, 1)),
+    }
 }
 
 
-pub fn empty_list_element(line: usize, source: &str) -> ! {
-    error(format!("Missing element for list at line {}:\n{}\n\nYou probably have an unwanted comma.", line, get_lines(source, line, 1)));
+pub fn duplicate_import(line: usize, source: &str, name: &str) -> ! {
+    error(format!("Duplicate imported name '{}' at line {}:\n{}\n\n", name, line, get_lines(source, line, 1)));
 }
 
 
-pub fn empty_function_argument(line: usize, source: &str) -> ! {
-    error(format!("Missing argument for function at line {}:\n{}\n\nYou probably have an unwanted comma.", line, get_lines(source, line, 1)));
+pub fn module_not_found(module: &str, line: usize, source: &str, searched: &[PathBuf]) -> ! {
+    let searched = searched.iter().map(|dir| format!("  {}", dir.display())).collect::<Vec<String>>().join("\n");
+    error(format!("Could not find module '{}' at line {}:\n{}\n\nSearched in:\n{}", module, line, get_lines(source, line, 1), searched));
 }
 
 
-pub fn wrong_operand_type(line: usize, operator: &str, got: &str, expected: &str, source: &str) -> ! {
-    error(format!("Wrong operand type for operator '{}' at line {}:\n{}\n\nExpected {}, got {}.", operator, line, get_lines(source, line, 1), expected, got));
+pub fn import_not_source(module: &str, line: usize, source: &str, extension: &str) -> ! {
+    error(format!("Cannot import '{}' at line {}:\n{}\n\nOnly source files with the .{} extension can be imported.", module, line, get_lines(source, line, 1), extension));
 }
 
 
-pub fn invalid_statement(line: usize, source: &str) -> ! {
-    error(format!("Invalid statement at line {}:\n{}\n\n", line, get_lines(source, line, 1)));
+pub fn import_outside_module_paths(module: &str, line: usize, source: &str, allowed: &[PathBuf]) -> ! {
+    let allowed = allowed.iter().map(|dir| format!("  {}", dir.display())).collect::<Vec<String>>().join("\n");
+    error(format!("Cannot import '{}' at line {}:\n{}\n\nModules can only be imported from the directory of the script and the module paths:\n{}", module, line, get_lines(source, line, 1), allowed));
 }
 
 
-pub fn empty_parentheses(line: usize, source: &str) -> ! {
-    error(format!("Empty parentheses at line {}:\n{}\n\n", line, get_lines(source, line, 1)));
+pub fn circular_import(chain: &[PathBuf], line: usize, source: &str) -> ! {
+    let chain = chain.iter().map(|path| path.display().to_string()).collect::<Vec<String>>().join("\n  -> ");
+    error(format!("Circular import at line {}:\n{}\n\nImport chain:\n  {}", line, get_lines(source, line, 1), chain));
 }
 
 
-pub fn undeclared_symbol(symbol: &str, line: usize, source: &str) -> ! {
-    error(format!("Undeclared symbol '{}' at line {}:\n{}\n\n", symbol, line, get_lines(source, line, 1)));
+pub fn invalid_bytecode_file(path: &Path, message: &str) -> ! {
+    error(format!("Could not load compiled file {}: {}", path.display(), message));
 }
 
+
+pub fn verify_error(path: &Path, verify_error: &VerifyError) -> ! {
+    error(format!("Invalid byte code in {}:\n{}", path.display(), verify_error));
+}
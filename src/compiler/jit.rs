@@ -1,19 +1,88 @@
-use super::code_block::CodeBlock;
+use std::path::{Path, PathBuf};
+
+use super::bytecode_file;
+use super::constant_pool::ConstantPool;
+use super::code_object::CodeObject;
+use super::codegen::CompileOptions;
+use super::module::{Module, ModuleContent, ModuleLoader, ModuleID, MAIN_MODULE_ID};
+use super::error;
 use super::syntax_tree::SyntaxTree;
+use crate::runtime::verifier;
+use crate::utils::files;
 
 
 pub struct Jit {
-    pub root: CodeBlock,
+    /// All the modules of the program, indexed by module id
+    pub modules: Vec<Module>,
+    /// The constants loaded by LoadConst and the member names used by GetMember, shared by all the modules
+    pub constants: ConstantPool,
+    /// The options the functions left uncompiled in lazy mode are compiled with
+    pub options: CompileOptions,
 }
 
 
 impl Jit {
 
-    pub fn from_syntax_tree(mut syntax_tree: SyntaxTree, source: &str) -> Jit {
-        Jit {
-            root: CodeBlock::from_syntax_tree(&mut syntax_tree, source, None),
+    /// Load and compile the program and all its imported modules, reporting all the compile errors before running it.
+    /// If the options are lazy, function bodies are compiled the first time they are called instead
+    pub fn from_syntax_tree(syntax_tree: SyntaxTree, source: &str, path: &Path, search_paths: Vec<PathBuf>, options: CompileOptions) -> Jit {
+        let mut loader = ModuleLoader::new(search_paths, options);
+        loader.load_main(path, source, syntax_tree);
+
+        let jit = match loader.into_modules() {
+            Ok((modules, constants)) => Jit { modules, constants, options },
+            Err(errors) => error::report_compile_errors(&errors),
+        };
+
+        jit.verify();
+        jit
+    }
+
+
+    /// Load a program compiled ahead of time into a bytecode file
+    pub fn from_bytecode_file(path: &Path) -> Jit {
+        let jit = match bytecode_file::deserialize(&files::load_bytes(path)) {
+            Ok((modules, constants)) => Jit { modules, constants, options: CompileOptions::default() },
+            Err(message) => error::invalid_bytecode_file(path, &message),
+        };
+
+        jit.verify();
+        jit
+    }
+
+
+    /// Verify the byte code of all the modules before running it, exiting if it's invalid
+    pub fn verify(&self) {
+        for module in &self.modules {
+            if let ModuleContent::Source { root, .. } = &module.content {
+                self.verify_code_object(root, &module.path);
+            }
         }
     }
 
-}
 
+    /// Verify a code object and its compiled functions, exiting if they are invalid
+    pub fn verify_code_object(&self, code_object: &CodeObject, path: &Path) {
+        if let Err(verify_error) = verifier::verify(code_object, &self.constants, self.modules.len()) {
+            error::verify_error(path, &verify_error);
+        }
+    }
+
+
+    /// Save the program into a bytecode file, so that it can be run without compiling it again.
+    /// The program must not have been compiled lazily
+    pub fn save_bytecode_file(&self, path: &Path) {
+        files::save_bytes(path, &bytecode_file::serialize(self));
+    }
+
+
+    pub fn get_module(&self, module_id: ModuleID) -> &Module {
+        &self.modules[module_id]
+    }
+
+
+    pub fn main_module(&self) -> &Module {
+        self.get_module(MAIN_MODULE_ID)
+    }
+
+}
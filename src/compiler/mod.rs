@@ -1,7 +1,14 @@
+pub mod bytecode_file;
 pub mod code_block;
-pub mod code_node;
+pub mod code_object;
+pub mod constant_pool;
+pub mod codegen;
+pub mod disassembler;
+pub mod dump;
 pub mod error;
 pub mod jit;
+pub mod module;
+pub mod parser;
 pub mod syntax_tree;
 pub mod token;
 pub mod tokenizer;
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::code_object::CodeObject;
+use super::codegen::{self, CompileOptions};
+use super::constant_pool::ConstantPool;
+use super::syntax_node::SyntaxNode;
+use super::syntax_tree::SyntaxTree;
+use super::tokenizer;
+use super::error::{self, CompileError};
+use crate::utils::files;
+use crate::stdlib::{self, NativeModuleInit};
+
+
+/// The file extension of WaterScript source files
+pub const SOURCE_EXTENSION: &str = "ws";
+
+pub type ModuleID = usize;
+
+/// The id of the module the program is started from
+pub const MAIN_MODULE_ID: ModuleID = 0;
+
+
+/// Represents a unit of code with its own global scope
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub content: ModuleContent,
+}
+
+
+pub enum ModuleContent {
+    /// The module was compiled from a source file
+    Source { source: String, root: Box<CodeObject> },
+    /// The module is built into the interpreter and implemented natively
+    Native { init: NativeModuleInit },
+}
+
+
+impl Module {
+
+    /// Return the source code of the module.
+    /// Native modules have no source code.
+    pub fn get_source(&self) -> &str {
+        match &self.content {
+            ModuleContent::Source { source, .. } => source,
+            ModuleContent::Native { .. } => "",
+        }
+    }
+
+}
+
+
+/// Resolves, loads and caches the modules imported by a program
+pub struct ModuleLoader {
+    /// Directories searched when a module is not found relative to the importing file
+    search_paths: Vec<PathBuf>,
+    /// The canonical directories modules can be imported from: the main module's directory and the search paths.
+    /// Imports can't reach other files, since importing doesn't require the read capability
+    allowed_dirs: Vec<PathBuf>,
+    /// The loaded modules, indexed by module id.
+    /// A module is None while it's still being loaded
+    modules: Vec<Option<Module>>,
+    /// Maps the canonical path of each module to its module id
+    cache: HashMap<PathBuf, ModuleID>,
+    /// The chain of modules currently being loaded, used to detect circular imports
+    loading: Vec<PathBuf>,
+    /// How the source modules are compiled
+    options: CompileOptions,
+    /// The compile errors found in all the loaded modules
+    errors: Vec<CompileError>,
+    /// The constants of all the loaded modules
+    constants: ConstantPool,
+}
+
+
+impl ModuleLoader {
+
+    pub fn new(search_paths: Vec<PathBuf>, options: CompileOptions) -> ModuleLoader {
+        let allowed_dirs = search_paths.iter().filter_map(|dir| dir.canonicalize().ok()).collect();
+
+        ModuleLoader {
+            search_paths,
+            allowed_dirs,
+            modules: Vec::new(),
+            cache: HashMap::new(),
+            loading: Vec::new(),
+            options,
+            errors: Vec::new(),
+            constants: ConstantPool::new(),
+        }
+    }
+
+
+    /// Load the main module from its already parsed syntax tree
+    /// The main module always gets MAIN_MODULE_ID
+    pub fn load_main(&mut self, path: &Path, source: &str, syntax_tree: SyntaxTree) -> ModuleID {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Some(dir) = path.parent() {
+            self.allowed_dirs.push(dir.to_path_buf());
+        }
+        self.compile_module(path, source.to_string(), syntax_tree)
+    }
+
+
+    /// Consume the loader and return the loaded modules, indexed by module id, and the program's constant pool.
+    /// Return all the compile errors instead if any module failed to compile
+    pub fn into_modules(self) -> Result<(Vec<Module>, ConstantPool), Vec<CompileError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+
+        let modules = self.modules.into_iter().map(
+            |module| module.expect("All modules should be loaded")
+        ).collect();
+
+        Ok((modules, self.constants))
+    }
+
+
+    /// Search for the module file, first relative to the importing file and then in the search paths.
+    /// Returns the canonical path of the module file.
+    fn resolve_path(&self, name: &str, base_dir: &Path) -> Option<PathBuf> {
+        let mut file = PathBuf::from(name);
+        if file.extension().is_none() {
+            file.set_extension(SOURCE_EXTENSION);
+        }
+
+        std::iter::once(base_dir)
+            .chain(self.search_paths.iter().map(|dir| dir.as_path()))
+            .map(|dir| dir.join(&file))
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| candidate.canonicalize().ok())
+    }
+
+
+    /// Load the module imported at the given line of the importing source file.
+    /// Modules are loaded once, subsequent imports return the cached module id.
+    fn load(&mut self, name: &str, base_dir: &Path, line: usize, source: &str) -> ModuleID {
+        if let Some(init) = stdlib::get_builtin_module(name) {
+            return self.load_builtin(name, init);
+        }
+
+        // Other files are never read, so that their content doesn't end up in the errors
+        if Path::new(name).extension().is_some_and(|extension| extension != SOURCE_EXTENSION) {
+            error::import_not_source(name, line, source, SOURCE_EXTENSION);
+        }
+
+        let path = if let Some(path) = self.resolve_path(name, base_dir) {
+            path
+        } else {
+            let mut searched = vec![base_dir.to_path_buf()];
+            searched.extend(self.search_paths.iter().cloned());
+            error::module_not_found(name, line, source, &searched);
+        };
+
+        if !self.allowed_dirs.iter().any(|dir| path.starts_with(dir)) {
+            error::import_outside_module_paths(name, line, source, &self.allowed_dirs);
+        }
+
+        // Check for cycles before the cache because modules are cached as soon as they start loading
+        if self.loading.contains(&path) {
+            let mut chain = self.loading.clone();
+            chain.push(path);
+            error::circular_import(&chain, line, source);
+        }
+
+        if let Some(module_id) = self.cache.get(&path) {
+            return *module_id;
+        }
+
+        let module_source = files::load_file(&path);
+        let mut tokens = tokenizer::tokenize(&module_source);
+        let syntax_tree = SyntaxTree::from_tokens(&tokens.consume_tokens(), &module_source);
+
+        self.compile_module(path, module_source, syntax_tree)
+    }
+
+
+    fn load_builtin(&mut self, name: &str, init: NativeModuleInit) -> ModuleID {
+        // Built-in modules don't have a file, but they still need a unique cache key
+        let path = PathBuf::from(format!("<builtin>/{}", name));
+
+        if let Some(module_id) = self.cache.get(&path) {
+            return *module_id;
+        }
+
+        let module_id = self.modules.len();
+        self.cache.insert(path.clone(), module_id);
+        self.modules.push(Some(Module {
+            name: name.to_string(),
+            path,
+            content: ModuleContent::Native { init },
+        }));
+
+        module_id
+    }
+
+
+    /// Resolve the imports of the module and compile it into its own top-level code object
+    fn compile_module(&mut self, path: PathBuf, source: String, mut syntax_tree: SyntaxTree) -> ModuleID {
+        // Reserve the module id so that the root code object knows which module it belongs to
+        let module_id = self.modules.len();
+        self.modules.push(None);
+        self.cache.insert(path.clone(), module_id);
+
+        let base_dir = path.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+
+        self.loading.push(path.clone());
+        self.resolve_imports(&mut syntax_tree, &base_dir, &source);
+        self.loading.pop();
+
+        let name = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let root = codegen::compile_module(&name, &mut syntax_tree, &source, module_id, self.options, &mut self.constants, &mut self.errors);
+
+        self.modules[module_id] = Some(Module {
+            name,
+            path,
+            content: ModuleContent::Source { source, root },
+        });
+
+        module_id
+    }
+
+
+    /// Return the names of the constants declared in the global scope of the module.
+    /// The imported modules are compiled before the modules importing them, so that they can't be assigned to from there
+    fn module_constants(&self, module_id: ModuleID) -> Vec<String> {
+        match &self.modules[module_id] {
+            Some(Module { content: ModuleContent::Source { root, .. }, .. }) => root.body_scope().get_local_symbols().into_iter()
+                .filter(|(_, symbol)| !symbol.is_mutable)
+                .map(|(name, _)| name)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+
+    /// Return the names of the members the module exports, which are the globals of source modules
+    fn module_members(&self, module_id: ModuleID) -> Vec<String> {
+        match &self.modules[module_id] {
+            Some(Module { content: ModuleContent::Source { root, .. }, .. }) => root.body_scope().get_local_symbols().into_iter()
+                .map(|(name, _)| name)
+                .collect(),
+            Some(Module { content: ModuleContent::Native { init }, .. }) => init().into_iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+
+    /// Load the modules imported in the syntax tree and store their ids in the import nodes
+    fn resolve_imports(&mut self, syntax_tree: &mut SyntaxTree, base_dir: &Path, source: &str) {
+        for statement in &mut syntax_tree.statements {
+            self.resolve_node_imports(statement, base_dir, source);
+        }
+    }
+
+
+    fn resolve_node_imports(&mut self, node: &mut SyntaxNode, base_dir: &Path, source: &str) {
+        match node {
+
+            SyntaxNode::Import { path, module_id, constants, line, .. } => {
+                *module_id = self.load(path, base_dir, *line, source);
+                *constants = self.module_constants(*module_id);
+            },
+
+            SyntaxNode::ImportFrom { path, names, module_id, constants, line } => {
+                *module_id = self.load(path, base_dir, *line, source);
+                *constants = self.module_constants(*module_id);
+
+                // Report missing members at the import rather than when the module is run
+                let members = self.module_members(*module_id);
+                for name in names.iter().filter(|name| !members.contains(name)) {
+                    self.errors.push(error::missing_import_member(path, name, *line, source));
+                }
+            },
+
+            // Imports can also appear in nested scopes, including the bodies of functions nested in expressions
+            _ => {
+                for child in node.children_mut() {
+                    self.resolve_node_imports(child, base_dir, source);
+                }
+            },
+        }
+    }
+
+}
@@ -0,0 +1,1008 @@
+use std::collections::HashSet;
+
+use super::error;
+use super::syntax_node::{MatchArm, MatchPattern, Params, Pattern, SyntaxNode};
+use super::syntax_tree::SyntaxTree;
+use super::token::Token;
+
+
+/// How tightly an operator binds its operands, from the loosest to the tightest
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Lowest,
+    Or,
+    And,
+    Equality,
+    Comparison,
+    AddSub,
+    MulDivMod,
+    /// Unary operators such as `!x` and `-x`
+    Prefix,
+    /// Calls, subscripts and member accesses
+    Postfix,
+}
+
+
+/// Return the precedence of the token when it's used as a binary operator.
+/// All binary operators are left-associative, so `a - b - c` is `(a - b) - c`
+fn binary_precedence(token: &Token) -> Option<Precedence> {
+    match token {
+        Token::Or { .. } => Some(Precedence::Or),
+        Token::And { .. } => Some(Precedence::And),
+        Token::EqualEqual { .. } | Token::NotEqual { .. } => Some(Precedence::Equality),
+        Token::Less { .. } | Token::Greater { .. } | Token::LessEqual { .. } | Token::GreaterEqual { .. } => Some(Precedence::Comparison),
+        Token::Plus { .. } | Token::Minus { .. } => Some(Precedence::AddSub),
+        Token::Star { .. } | Token::Slash { .. } | Token::Modulo { .. } => Some(Precedence::MulDivMod),
+        _ => None,
+    }
+}
+
+
+/// Return whether the token is an assignment operator.
+/// Assignments are statements, so they are parsed separately from the expressions
+fn is_assignment(token: &Token) -> bool {
+    matches!(token,
+        Token::Equal { .. } |
+        Token::PlusEqual { .. } |
+        Token::MinusEqual { .. } |
+        Token::StarEquals { .. } |
+        Token::SlashEqual { .. } |
+        Token::ModuloEqual { .. }
+    )
+}
+
+
+/// Whether the node can be assigned to: a variable, a subscript, a member, or a non-empty list of assignment targets
+fn is_assignment_target(node: &SyntaxNode) -> bool {
+    match node {
+        SyntaxNode::Identifier { .. } | SyntaxNode::Subscript { .. } | SyntaxNode::Member { .. } => true,
+        SyntaxNode::List { elements, .. } => !elements.is_empty() && elements.iter().all(is_assignment_target),
+        _ => false,
+    }
+}
+
+
+/// Parse the token stream into a syntax tree.
+/// Exits with an error describing the expected and found tokens if the program is not valid
+pub fn parse(tokens: &[Token], source: &str) -> SyntaxTree {
+    let mut parser = Parser { tokens, index: 0, source };
+    let syntax_tree = parser.parse_statements(false);
+
+    if let Some(token) = parser.peek() {
+        parser.unexpected_token("a statement", Some(token));
+    }
+
+    syntax_tree
+}
+
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    /// The index of the next token to parse
+    index: usize,
+    source: &'a str,
+}
+
+
+impl<'a> Parser<'a> {
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index)
+    }
+
+
+    /// Return the token the given number of tokens after the next one
+    fn peek_ahead(&self, offset: usize) -> Option<&'a Token> {
+        self.tokens.get(self.index + offset)
+    }
+
+
+    fn peek_is(&self, is_expected: fn(&Token) -> bool) -> bool {
+        self.peek().is_some_and(is_expected)
+    }
+
+
+    fn advance(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.index);
+        if token.is_some() {
+            self.index += 1;
+        }
+        token
+    }
+
+
+    /// The line of the next token, or of the last token at the end of the file
+    fn current_line(&self) -> usize {
+        self.peek()
+            .or_else(|| self.tokens.last())
+            .map_or(0, Token::get_line)
+    }
+
+
+    fn unexpected_token(&self, expected: &str, found: Option<&Token>) -> ! {
+        error::unexpected_token(expected, found, self.current_line(), self.source)
+    }
+
+
+    /// Consume the token closing the grouping, reporting where the grouping was opened if the file ends first
+    fn expect_closing(&mut self, open: &Token) {
+        let (is_closing, closing): (fn(&Token) -> bool, &str) = match open {
+            Token::OpenParen { .. } => (|token| matches!(token, Token::CloseParen { .. }), "')'"),
+            Token::OpenSquare { .. } => (|token| matches!(token, Token::CloseSquare { .. }), "']'"),
+            Token::OpenBrace { .. } => (|token| matches!(token, Token::CloseBrace { .. }), "'}'"),
+            _ => unreachable!("{} doesn't open a grouping", open),
+        };
+
+        match self.peek() {
+            Some(token) if is_closing(token) => self.index += 1,
+            Some(found) => self.unexpected_token(closing, Some(found)),
+            None => match open {
+                Token::OpenParen { .. } => error::unmatched_parenthesis(open.get_line(), self.source),
+                Token::OpenSquare { .. } => error::unmatched_square_bracket(open.get_line(), self.source),
+                _ => error::unmatched_curly_brace(open.get_line(), self.source),
+            },
+        }
+    }
+
+
+    /// Consume the next token if it's the expected one, or exit with an error describing what was expected
+    fn expect(&mut self, is_expected: fn(&Token) -> bool, expected: &str) -> &'a Token {
+        match self.peek() {
+            Some(token) if is_expected(token) => {
+                self.index += 1;
+                token
+            },
+            found => self.unexpected_token(expected, found),
+        }
+    }
+
+
+    fn expect_identifier(&mut self, expected: &str) -> String {
+        match self.peek() {
+            Some(Token::Identifier { value, .. }) => {
+                self.index += 1;
+                value.to_string()
+            },
+            found => self.unexpected_token(expected, found),
+        }
+    }
+
+
+    fn skip_line_ends(&mut self) {
+        while self.peek_is(|token| matches!(token, Token::EndOfStatement { .. })) {
+            self.index += 1;
+        }
+    }
+
+
+    /// Parse statements until the end of the file or, inside a block, until the closing brace, which is not consumed
+    fn parse_statements(&mut self, in_block: bool) -> SyntaxTree {
+        let mut statements: Vec<SyntaxNode> = Vec::new();
+
+        loop {
+            self.skip_line_ends();
+
+            match self.peek() {
+                None => break,
+                Some(Token::CloseBrace { .. }) if in_block => break,
+                _ => {},
+            }
+
+            statements.push(self.parse_statement());
+
+            // A statement ends at the end of its line or, for one-line blocks, at the closing brace
+            match self.peek() {
+                None | Some(Token::EndOfStatement { .. }) | Some(Token::CloseBrace { .. }) => {},
+                found => self.unexpected_token("the end of the statement", found),
+            }
+        }
+
+        SyntaxTree { statements }
+    }
+
+
+    /// Parse a block of statements between curly braces
+    fn parse_block(&mut self, owner: &str) -> SyntaxTree {
+        let open_brace = self.expect(|token| matches!(token, Token::OpenBrace { .. }), &format!("'{{' to start the body of {}", owner));
+        let body = self.parse_statements(true);
+        self.expect_closing(open_brace);
+        body
+    }
+
+
+    fn parse_statement(&mut self) -> SyntaxNode {
+        let token = self.peek().unwrap();
+        let line = token.get_line();
+
+        match token {
+            // Without a name, the function is an expression
+            Token::Fun { .. } if matches!(self.peek_ahead(1), Some(Token::Identifier { .. })) => self.parse_fun(),
+            Token::If { .. } => self.parse_if(),
+            Token::While { .. } => self.parse_while(),
+            Token::For { .. } => self.parse_for(),
+            Token::Match { .. } => self.parse_match(),
+            Token::Import { .. } => self.parse_import(),
+            Token::From { .. } => self.parse_import_from(),
+
+            Token::Return { .. } => {
+                self.advance();
+                let value = if self.at_statement_end() {
+                    None
+                } else {
+                    Some(Box::new(self.parse_expression(Precedence::Lowest)))
+                };
+                SyntaxNode::Return { value, line }
+            },
+
+            Token::Break { .. } => {
+                self.advance();
+                SyntaxNode::Break { line }
+            },
+
+            Token::Continue { .. } => {
+                self.advance();
+                SyntaxNode::Continue { line }
+            },
+
+            Token::Let { .. } => {
+                self.advance();
+                let pattern = self.parse_pattern("a variable name after 'let'");
+                let declaration = SyntaxNode::Let { pattern, line };
+
+                // A declaration can only be initialized with a plain assignment, since the symbol has no value yet
+                if self.peek_is(|token| matches!(token, Token::Equal { .. })) {
+                    let equal = self.advance().unwrap();
+                    let value = self.parse_expression_list();
+                    SyntaxNode::Assign { left: Box::new(declaration), right: Box::new(value), line: equal.get_line() }
+                } else {
+                    declaration
+                }
+            },
+
+            Token::Const { .. } => {
+                self.advance();
+                let name = self.expect_identifier("a constant name after 'const'");
+                self.expect(|token| matches!(token, Token::Equal { .. }), "'=' and the value of the constant");
+                let value = Box::new(self.parse_expression_list());
+                SyntaxNode::Const { name, value, line }
+            },
+
+            Token::OpenBrace { .. } => {
+                let body = self.parse_block("the scope");
+                SyntaxNode::Scope { body, line }
+            },
+
+            _ => self.parse_expression_statement(),
+        }
+    }
+
+
+    fn at_statement_end(&self) -> bool {
+        matches!(self.peek(), None | Some(Token::EndOfStatement { .. }) | Some(Token::CloseBrace { .. }))
+    }
+
+
+    /// Parse an expression, which may be the target of an assignment.
+    /// Assignments don't produce a value, so they can't be chained or nested in expressions.
+    ///
+    /// Several comma-separated targets, or a list of targets, unpack the assigned list into them
+    fn parse_expression_statement(&mut self) -> SyntaxNode {
+        let target = self.parse_expression(Precedence::Lowest);
+
+        let target = if self.peek_is(|token| matches!(token, Token::Comma { .. })) && self.is_assignment_ahead() {
+            let line = target.get_line();
+            let mut targets = vec![target];
+            while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.advance();
+                targets.push(self.parse_expression(Precedence::Lowest));
+            }
+            SyntaxNode::List { elements: targets, line }
+        } else {
+            target
+        };
+
+        let operator = match self.peek() {
+            Some(operator) if is_assignment(operator) => operator,
+            _ => return target,
+        };
+        self.advance();
+
+        // Only a plain assignment can unpack a list, since the targets have no single current value to update
+        let is_valid = match operator {
+            Token::Equal { .. } => is_assignment_target(&target),
+            _ => matches!(target, SyntaxNode::Identifier { .. } | SyntaxNode::Subscript { .. } | SyntaxNode::Member { .. }),
+        };
+        if !is_valid {
+            error::invalid_assignment_target(target.get_name(), operator.get_line(), self.source);
+        }
+
+        let left = Box::new(target);
+        let right = Box::new(self.parse_expression_list());
+        let line = operator.get_line();
+
+        match operator {
+            Token::Equal { .. } => SyntaxNode::Assign { left, right, line },
+            Token::PlusEqual { .. } => SyntaxNode::AssignAdd { left, right, line },
+            Token::MinusEqual { .. } => SyntaxNode::AssignSub { left, right, line },
+            Token::StarEquals { .. } => SyntaxNode::AssignMul { left, right, line },
+            Token::SlashEqual { .. } => SyntaxNode::AssignDiv { left, right, line },
+            Token::ModuloEqual { .. } => SyntaxNode::AssignMod { left, right, line },
+            _ => unreachable!(),
+        }
+    }
+
+
+    /// Whether an assignment operator comes before the end of the statement, outside of any grouping.
+    /// A comma after an expression then separates assignment targets rather than, for example, match arms
+    fn is_assignment_ahead(&self) -> bool {
+        let mut depth: usize = 0;
+        let mut offset = 0;
+
+        loop {
+            match self.peek_ahead(offset) {
+                Some(Token::OpenParen { .. }) | Some(Token::OpenSquare { .. }) | Some(Token::OpenBrace { .. }) => depth += 1,
+                Some(Token::CloseParen { .. }) | Some(Token::CloseSquare { .. }) | Some(Token::CloseBrace { .. }) => {
+                    if depth == 0 {
+                        return false;
+                    }
+                    depth -= 1;
+                },
+                Some(token) if depth == 0 && is_assignment(token) => return true,
+                Some(Token::EndOfStatement { .. }) | Some(Token::Arrow { .. }) if depth == 0 => return false,
+                Some(_) => {},
+                None => return false,
+            }
+            offset += 1;
+        }
+    }
+
+
+    /// Parse an expression or, if several comma-separated expressions follow, a list of them.
+    /// Used for the value of assignments, so that `a, b = b, a` swaps the values
+    fn parse_expression_list(&mut self) -> SyntaxNode {
+        let first = self.parse_expression(Precedence::Lowest);
+        if !self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+            return first;
+        }
+
+        let line = first.get_line();
+        let mut elements = vec![first];
+        while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+            self.advance();
+            elements.push(self.parse_expression(Precedence::Lowest));
+        }
+
+        SyntaxNode::List { elements, line }
+    }
+
+
+    /// Parse the pattern of a declaration or a loop variable.
+    /// Several comma-separated patterns unpack a list, like a list of patterns in square brackets
+    fn parse_pattern(&mut self, expected: &str) -> Pattern {
+        let line = self.current_line();
+
+        let first = self.parse_pattern_element(expected);
+        let pattern = if self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+            let mut elements = vec![first];
+            while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.advance();
+                elements.push(self.parse_pattern_element(expected));
+            }
+            Pattern::List(elements)
+        } else {
+            first
+        };
+
+        let mut names: HashSet<&str> = HashSet::new();
+        for name in pattern.names() {
+            if !names.insert(name) {
+                error::duplicate_binding(name, line, self.source);
+            }
+        }
+
+        pattern
+    }
+
+
+    /// Parse a name, or a list of patterns in square brackets
+    fn parse_pattern_element(&mut self, expected: &str) -> Pattern {
+        let open_square = match self.peek() {
+            Some(token @ Token::OpenSquare { .. }) => token,
+            _ => return Pattern::Name(self.expect_identifier(expected)),
+        };
+        self.advance();
+
+        let mut elements = vec![self.parse_pattern_element("a variable name")];
+        while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+            self.advance();
+            elements.push(self.parse_pattern_element("a variable name"));
+        }
+        self.expect_closing(open_square);
+
+        Pattern::List(elements)
+    }
+
+
+    /// Parse the expression whose operators all bind at least as tightly as the given precedence
+    fn parse_expression(&mut self, min_precedence: Precedence) -> SyntaxNode {
+        let mut left = self.parse_prefix();
+
+        while let Some(token) = self.peek() {
+            let line = token.get_line();
+
+            // Postfix operators bind the tightest, so they always apply to the expression on their left
+            match token {
+                Token::OpenParen { .. } => {
+                    self.advance();
+                    let arguments = self.parse_arguments(token);
+                    left = SyntaxNode::Call { function: Box::new(left), arguments, line };
+                    continue;
+                },
+                Token::OpenSquare { .. } => {
+                    self.advance();
+                    let index = self.parse_expression(Precedence::Lowest);
+                    self.expect_closing(token);
+                    left = SyntaxNode::Subscript { iterable: Box::new(left), index: Box::new(index), line };
+                    continue;
+                },
+                Token::Dot { .. } => {
+                    self.advance();
+                    let name = self.expect_identifier("a member name after '.'");
+                    left = SyntaxNode::Member { object: Box::new(left), name, line };
+                    continue;
+                },
+                _ => {},
+            }
+
+            let precedence = match binary_precedence(token) {
+                Some(precedence) if precedence >= min_precedence => precedence,
+                _ => break,
+            };
+            self.advance();
+
+            // The right operand only takes the operators that bind tighter, so that the following operators
+            // of the same precedence apply to the result, which makes the operator left-associative
+            let right_precedence = next_precedence(precedence);
+            let right = Box::new(self.parse_expression(right_precedence));
+            let left_box = Box::new(left);
+
+            left = match token {
+                Token::Or { .. } => SyntaxNode::Or { left: left_box, right, line },
+                Token::And { .. } => SyntaxNode::And { left: left_box, right, line },
+                Token::EqualEqual { .. } => SyntaxNode::Equal { left: left_box, right, line },
+                Token::NotEqual { .. } => SyntaxNode::NotEqual { left: left_box, right, line },
+                Token::Less { .. } => SyntaxNode::Less { left: left_box, right, line },
+                Token::Greater { .. } => SyntaxNode::Greater { left: left_box, right, line },
+                Token::LessEqual { .. } => SyntaxNode::LessEqual { left: left_box, right, line },
+                Token::GreaterEqual { .. } => SyntaxNode::GreaterEqual { left: left_box, right, line },
+                Token::Plus { .. } => SyntaxNode::Add { left: left_box, right, line },
+                Token::Minus { .. } => SyntaxNode::Sub { left: left_box, right, line },
+                Token::Star { .. } => SyntaxNode::Mul { left: left_box, right, line },
+                Token::Slash { .. } => SyntaxNode::Div { left: left_box, right, line },
+                Token::Modulo { .. } => SyntaxNode::Mod { left: left_box, right, line },
+                _ => unreachable!(),
+            };
+        }
+
+        left
+    }
+
+
+    /// Parse an operand: a literal, a name, a grouping or a unary operator applied to an operand
+    fn parse_prefix(&mut self) -> SyntaxNode {
+        let token = match self.advance() {
+            Some(token) => token,
+            None => self.unexpected_token("an expression", None),
+        };
+        let line = token.get_line();
+
+        match token {
+            Token::Integer { value, .. } => SyntaxNode::Int { value: *value, line },
+            Token::Float { value, .. } => SyntaxNode::Float { value: *value, line },
+            Token::String { value, .. } => SyntaxNode::String { value: value.to_string(), line },
+            Token::Boolean { value, .. } => SyntaxNode::Boolean { value: *value, line },
+            Token::Identifier { value, .. } => SyntaxNode::Identifier { value: value.to_string(), line },
+            Token::None { .. } => SyntaxNode::None { line },
+
+            Token::OpenParen { .. } if self.is_arrow_function() => self.parse_arrow_function(line),
+
+            Token::OpenParen { .. } => {
+                let child = self.parse_expression(Precedence::Lowest);
+                self.expect_closing(token);
+                SyntaxNode::Parenthesis { child: Box::new(child), line }
+            },
+
+            Token::OpenSquare { .. } => {
+                let elements = self.parse_comma_separated(token, "a list element");
+                SyntaxNode::List { elements, line }
+            },
+
+            Token::Fun { .. } => self.parse_lambda(line),
+
+            // The operand of a prefix operator may itself start with a prefix operator, as in `!!x`,
+            // but binary operators apply to the result
+            Token::Not { .. } => {
+                let operand = self.parse_expression(Precedence::Prefix);
+                SyntaxNode::Not { operand: Box::new(operand), line }
+            },
+
+            // A minus in operand position is a negation, so that `a * -b` is `a * (-b)`
+            Token::Minus { .. } => {
+                let operand = self.parse_expression(Precedence::Prefix);
+                SyntaxNode::Neg { operand: Box::new(operand), line }
+            },
+
+            found => {
+                // Put the token back so that the error points at the right line
+                self.index -= 1;
+                self.unexpected_token("an expression", Some(found))
+            },
+        }
+    }
+
+
+    /// Parse expressions separated by commas, up to and including the token closing the grouping
+    fn parse_comma_separated(&mut self, open: &Token, element: &str) -> Vec<SyntaxNode> {
+        let is_closing: fn(&Token) -> bool = match open {
+            Token::OpenParen { .. } => |token| matches!(token, Token::CloseParen { .. }),
+            _ => |token| matches!(token, Token::CloseSquare { .. }),
+        };
+        let mut elements: Vec<SyntaxNode> = Vec::new();
+
+        while !self.peek_is(is_closing) {
+            if self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.unexpected_token(element, self.peek());
+            }
+
+            elements.push(self.parse_expression(Precedence::Lowest));
+
+            if self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.advance();
+                // A trailing comma must be followed by another element
+                if self.peek_is(is_closing) {
+                    self.unexpected_token(element, self.peek());
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.expect_closing(open);
+        elements
+    }
+
+
+    fn parse_fun(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let name = self.expect_identifier("a function name after 'fun'");
+
+        self.expect(|token| matches!(token, Token::OpenParen { .. }), "'(' to start the parameter list");
+        let params = self.parse_params(line);
+        let body = self.parse_block("the function");
+
+        SyntaxNode::Fun { name, params, body, line }
+    }
+
+
+    /// Parse an anonymous function `fun(a, b) { ... }`, whose `fun` keyword was consumed
+    fn parse_lambda(&mut self, line: usize) -> SyntaxNode {
+        self.expect(|token| matches!(token, Token::OpenParen { .. }), "a function name or '(' after 'fun'");
+        let params = self.parse_params(line);
+        let body = self.parse_block("the function");
+
+        SyntaxNode::Lambda { params, body, line }
+    }
+
+
+    /// Return whether the opening parenthesis that was just consumed starts the parameters of an arrow function,
+    /// that is whether its closing parenthesis is followed by `=>`
+    fn is_arrow_function(&self) -> bool {
+        let mut depth: usize = 1;
+        let mut offset = 0;
+
+        while depth > 0 {
+            match self.peek_ahead(offset) {
+                Some(Token::OpenParen { .. }) => depth += 1,
+                Some(Token::CloseParen { .. }) => depth -= 1,
+                Some(_) => {},
+                None => return false,
+            }
+            offset += 1;
+        }
+
+        matches!(self.peek_ahead(offset), Some(Token::Arrow { .. }))
+    }
+
+
+    /// Parse an arrow function `(a, b) => expression`, whose opening parenthesis was consumed.
+    /// The body may also be a block, which must return the result explicitly
+    fn parse_arrow_function(&mut self, line: usize) -> SyntaxNode {
+        let params = self.parse_params(line);
+        self.expect(|token| matches!(token, Token::Arrow { .. }), "'=>' after the parameters");
+
+        let body = if self.peek_is(|token| matches!(token, Token::OpenBrace { .. })) {
+            self.parse_block("the function")
+        } else {
+            let value = self.parse_expression(Precedence::Lowest);
+            let value_line = value.get_line();
+            SyntaxTree { statements: vec![SyntaxNode::Return { value: Some(Box::new(value)), line: value_line }] }
+        };
+
+        SyntaxNode::Lambda { params, body, line }
+    }
+
+
+    /// Parse the parameters of a function up to and including the closing parenthesis:
+    /// required parameters, then parameters with a default value, then an optional rest parameter
+    fn parse_params(&mut self, line: usize) -> Params {
+        let mut params = Params::default();
+
+        while !self.peek_is(|token| matches!(token, Token::CloseParen { .. })) {
+            if self.peek_is(|token| matches!(token, Token::Ellipsis { .. })) {
+                self.advance();
+                params.names.push(self.expect_identifier("a parameter name after '...'"));
+                params.has_rest = true;
+                // The rest parameter is the last one
+                break;
+            }
+
+            let name = self.expect_identifier("a parameter name");
+            if self.peek_is(|token| matches!(token, Token::Equal { .. })) {
+                self.advance();
+                params.defaults.push(self.parse_expression(Precedence::Lowest));
+            } else if !params.defaults.is_empty() {
+                error::missing_default_value(&name, line, self.source);
+            }
+            params.names.push(name);
+
+            if self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        let expected = if params.has_rest { "')' after the rest parameter, which must be the last one" } else { "',' or ')'" };
+        self.expect(|token| matches!(token, Token::CloseParen { .. }), expected);
+
+        // The argument count of a call is encoded in a single byte
+        if params.names.len() > u8::MAX as usize {
+            error::too_many_parameters(line, self.source, u8::MAX as usize);
+        }
+
+        let mut param_set = HashSet::new();
+        for param in &params.names {
+            if !param_set.insert(param) {
+                error::duplicate_parameter(line, self.source, param, false);
+            }
+        }
+
+        params
+    }
+
+
+    /// Parse the arguments of a call up to and including the closing parenthesis.
+    /// Positional and spread arguments come first, then keyword arguments
+    fn parse_arguments(&mut self, open: &Token) -> Vec<SyntaxNode> {
+        let mut arguments: Vec<SyntaxNode> = Vec::new();
+        let mut keywords: HashSet<&str> = HashSet::new();
+
+        while !self.peek_is(|token| matches!(token, Token::CloseParen { .. })) {
+            let line = self.current_line();
+
+            let argument = match (self.peek(), self.peek_ahead(1)) {
+                (Some(Token::Comma { .. }), _) => self.unexpected_token("an argument", self.peek()),
+
+                (Some(Token::Ellipsis { .. }), _) => {
+                    self.advance();
+                    let value = self.parse_expression(Precedence::Lowest);
+                    SyntaxNode::SpreadArgument { value: Box::new(value), line }
+                },
+
+                (Some(Token::Identifier { value: name, .. }), Some(Token::Colon { .. })) => {
+                    self.index += 2;
+                    if !keywords.insert(name) {
+                        error::duplicate_parameter(line, self.source, name, true);
+                    }
+                    let value = self.parse_expression(Precedence::Lowest);
+                    SyntaxNode::KeywordArgument { name: name.to_string(), value: Box::new(value), line }
+                },
+
+                _ => self.parse_expression(Precedence::Lowest),
+            };
+
+            if !keywords.is_empty() && !matches!(argument, SyntaxNode::KeywordArgument { .. }) {
+                error::positional_after_keyword(line, self.source);
+            }
+            arguments.push(argument);
+
+            if self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                self.advance();
+                // A trailing comma must be followed by another argument
+                if self.peek_is(|token| matches!(token, Token::CloseParen { .. })) {
+                    self.unexpected_token("an argument", self.peek());
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.expect_closing(open);
+        arguments
+    }
+
+
+    fn parse_if(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let condition = Box::new(self.parse_expression(Precedence::Lowest));
+        let body = self.parse_block("the if statement");
+        let else_node = self.parse_else();
+
+        SyntaxNode::If { condition, body, else_node, line }
+    }
+
+
+    /// Parse the elif and else branches following an if or elif body, which may start on the next line
+    fn parse_else(&mut self) -> Option<Box<SyntaxNode>> {
+        let after_body = self.index;
+        self.skip_line_ends();
+
+        let token = match self.peek() {
+            Some(token @ (Token::Elif { .. } | Token::Else { .. })) => token,
+            _ => {
+                // The statement ends here, so leave the line end to terminate it
+                self.index = after_body;
+                return None;
+            },
+        };
+        let line = token.get_line();
+        self.advance();
+
+        let node = match token {
+            Token::Elif { .. } => {
+                let condition = Box::new(self.parse_expression(Precedence::Lowest));
+                let body = self.parse_block("the elif branch");
+                let else_node = self.parse_else();
+                SyntaxNode::Elif { condition, body, else_node, line }
+            },
+            _ => {
+                let body = self.parse_block("the else branch");
+                SyntaxNode::Else { body, line }
+            },
+        };
+
+        Some(Box::new(node))
+    }
+
+
+    fn parse_while(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let condition = Box::new(self.parse_expression(Precedence::Lowest));
+        let body = self.parse_block("the while loop");
+
+        SyntaxNode::While { condition, body, line }
+    }
+
+
+    fn parse_for(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let pattern = self.parse_pattern("a loop variable name after 'for'");
+        self.expect(|token| matches!(token, Token::In { .. }), "'in'");
+        let iterable = Box::new(self.parse_expression(Precedence::Lowest));
+        let body = self.parse_block("the for loop");
+
+        SyntaxNode::For { pattern, iterable, body, line }
+    }
+
+
+    fn parse_match(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let value = Box::new(self.parse_expression(Precedence::Lowest));
+        let open_brace = self.expect(|token| matches!(token, Token::OpenBrace { .. }), "'{' to start the arms of the match");
+
+        let mut arms: Vec<MatchArm> = Vec::new();
+        loop {
+            self.skip_line_ends();
+            if matches!(self.peek(), None | Some(Token::CloseBrace { .. })) {
+                break;
+            }
+
+            arms.push(self.parse_match_arm());
+
+            // Arms are separated by commas or line ends
+            match self.peek() {
+                Some(Token::Comma { .. }) => {
+                    self.advance();
+                },
+                None | Some(Token::EndOfStatement { .. }) | Some(Token::CloseBrace { .. }) => {},
+                found => self.unexpected_token("',' or the end of the line after the match arm", found),
+            }
+        }
+        self.expect_closing(open_brace);
+
+        if !arms.iter().any(MatchArm::is_catch_all) {
+            error::match_without_wildcard(line, self.source);
+        }
+
+        SyntaxNode::Match { value, arms, line }
+    }
+
+
+    /// Parse a match arm `pattern if guard => body`, whose body is a block or a single statement
+    fn parse_match_arm(&mut self) -> MatchArm {
+        let line = self.current_line();
+        let pattern = self.parse_match_pattern();
+
+        let mut names: HashSet<&str> = HashSet::new();
+        for name in pattern.bindings() {
+            if !names.insert(name) {
+                error::duplicate_binding(name, line, self.source);
+            }
+        }
+
+        let guard = if self.peek_is(|token| matches!(token, Token::If { .. })) {
+            self.advance();
+            Some(self.parse_expression(Precedence::Lowest))
+        } else {
+            None
+        };
+
+        self.expect(|token| matches!(token, Token::Arrow { .. }), "'=>' after the pattern of the match arm");
+
+        let body = if self.peek_is(|token| matches!(token, Token::OpenBrace { .. })) {
+            self.parse_block("the match arm")
+        } else {
+            SyntaxTree { statements: vec![self.parse_statement()] }
+        };
+
+        MatchArm { pattern, guard, body, line }
+    }
+
+
+    /// Parse a pattern of a match arm, which may be several alternative patterns separated by '|'
+    fn parse_match_pattern(&mut self) -> MatchPattern {
+        let line = self.current_line();
+
+        let first = self.parse_match_alternative();
+        if !self.peek_is(|token| matches!(token, Token::Pipe { .. })) {
+            return first;
+        }
+
+        let mut alternatives = vec![first];
+        while self.peek_is(|token| matches!(token, Token::Pipe { .. })) {
+            self.advance();
+            alternatives.push(self.parse_match_alternative());
+        }
+
+        let pattern = MatchPattern::Alternatives(alternatives);
+        // Which names would be bound would depend on the alternative that matched
+        if let Some(name) = pattern.bindings().first() {
+            error::binding_in_alternatives(name, line, self.source);
+        }
+
+        pattern
+    }
+
+
+    /// Parse a wildcard, a binding, a literal or a list of patterns in square brackets
+    fn parse_match_alternative(&mut self) -> MatchPattern {
+        match self.peek() {
+            Some(Token::Identifier { value, .. }) => {
+                self.advance();
+                if value == "_" {
+                    MatchPattern::Wildcard
+                } else {
+                    MatchPattern::Binding(value.to_string())
+                }
+            },
+
+            Some(open_square @ Token::OpenSquare { .. }) => {
+                self.advance();
+
+                let mut elements: Vec<MatchPattern> = Vec::new();
+                if !self.peek_is(|token| matches!(token, Token::CloseSquare { .. })) {
+                    elements.push(self.parse_match_pattern());
+                    while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+                        self.advance();
+                        elements.push(self.parse_match_pattern());
+                    }
+                }
+                self.expect_closing(open_square);
+
+                MatchPattern::List(elements)
+            },
+
+            Some(Token::Minus { .. }) => {
+                // Only numbers can be negated in a pattern
+                if !matches!(self.peek_ahead(1), Some(Token::Integer { .. }) | Some(Token::Float { .. })) {
+                    self.advance();
+                    self.unexpected_token("a number after '-' in the pattern", self.peek());
+                }
+                MatchPattern::Literal(Box::new(self.parse_prefix()))
+            },
+
+            Some(Token::Integer { .. }) |
+            Some(Token::Float { .. }) |
+            Some(Token::String { .. }) |
+            Some(Token::Boolean { .. }) |
+            Some(Token::None { .. }) => MatchPattern::Literal(Box::new(self.parse_prefix())),
+
+            found => self.unexpected_token("a pattern", found),
+        }
+    }
+
+
+    /// Parse the module of an import statement, which can be either a string path or a bare identifier.
+    /// Return the module path and the name the module is bound to by default
+    fn parse_module_path(&mut self) -> (String, String) {
+        match self.peek() {
+            Some(Token::String { value, .. }) => {
+                self.advance();
+                let name = std::path::Path::new(value).file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                (value.to_string(), name)
+            },
+            Some(Token::Identifier { value, .. }) => {
+                self.advance();
+                (value.to_string(), value.to_string())
+            },
+            found => self.unexpected_token("a module name or a string path", found),
+        }
+    }
+
+
+    /// import <module> [as <name>]
+    fn parse_import(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let (path, default_alias) = self.parse_module_path();
+
+        let alias = if self.peek_is(|token| matches!(token, Token::As { .. })) {
+            self.advance();
+            self.expect_identifier("a name after 'as'")
+        } else {
+            default_alias
+        };
+
+        SyntaxNode::Import { path, alias, module_id: 0, constants: Vec::new(), line }
+    }
+
+
+    /// from <module> import <name> [, <name>]*
+    fn parse_import_from(&mut self) -> SyntaxNode {
+        let line = self.advance().unwrap().get_line();
+        let (path, _) = self.parse_module_path();
+        self.expect(|token| matches!(token, Token::Import { .. }), "'import' after the module path");
+
+        let mut names: Vec<String> = vec![self.expect_identifier("a name to import")];
+        while self.peek_is(|token| matches!(token, Token::Comma { .. })) {
+            self.advance();
+            names.push(self.expect_identifier("a name to import"));
+        }
+
+        let mut name_set = HashSet::new();
+        for name in &names {
+            if !name_set.insert(name) {
+                error::duplicate_import(line, self.source, name);
+            }
+        }
+
+        SyntaxNode::ImportFrom { path, names, module_id: 0, constants: Vec::new(), line }
+    }
+
+}
+
+
+/// Return the precedence just above the given one
+fn next_precedence(precedence: Precedence) -> Precedence {
+    match precedence {
+        Precedence::Lowest => Precedence::Or,
+        Precedence::Or => Precedence::And,
+        Precedence::And => Precedence::Equality,
+        Precedence::Equality => Precedence::Comparison,
+        Precedence::Comparison => Precedence::AddSub,
+        Precedence::AddSub => Precedence::MulDivMod,
+        Precedence::MulDivMod => Precedence::Prefix,
+        Precedence::Prefix | Precedence::Postfix => Precedence::Postfix,
+    }
+}
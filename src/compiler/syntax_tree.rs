@@ -1,5 +1,6 @@
+use super::parser;
+use super::syntax_node::SyntaxNode;
 use super::token::Token;
-use super::syntax_node::{SyntaxNode, self};
 
 
 /// Represents a list of statements.
@@ -12,14 +13,7 @@ pub struct SyntaxTree {
 impl SyntaxTree {
 
     pub fn from_tokens(tokens: &[Token], source: &str) -> SyntaxTree {
-        let mut raw_statements = syntax_node::tokens_to_syntax_node_statements(tokens, source);
-
-        let statements = raw_statements.iter_mut().map(
-            |statement| syntax_node::parse_statement(statement, source)
-        ).collect();
-
-        SyntaxTree { statements }
+        parser::parse(tokens, source)
     }
 
 }
-
@@ -3,63 +3,74 @@
 #[derive(Debug)]
 pub enum Token {
 
-    EndOfStatement { priority: usize, line: usize },
+    EndOfStatement { line: usize },
 
     Numeric { value: String, line: usize },
-    Integer { value: i64, priority: usize, line: usize },
-    Float { value: f64, priority: usize, line: usize },
-    String { value: String, priority: usize, line: usize },
-    Boolean { value: bool, priority: usize, line: usize },
-    Identifier { value: String, priority: usize, line: usize },
-
-    Plus { priority: usize, line: usize },
-    Minus { priority: usize, line: usize },
-    Star { priority: usize, line: usize },
-    Slash { priority: usize, line: usize },
-    Modulo { priority: usize, line: usize },
-    Equal { priority: usize, line: usize },
-    Not { priority: usize, line: usize },
-    Less { priority: usize, line: usize },
-    Greater { priority: usize, line: usize },
-    Ampersand { priority: usize, line: usize },
-    Pipe { priority: usize, line: usize },
-    Comma { priority: usize, line: usize },
-
-    OpenParen { priority: usize, line: usize },
-    CloseParen { priority: usize, line: usize },
-    OpenBrace { priority: usize, line: usize },
-    CloseBrace { priority: usize, line: usize },
-    OpenSquare { priority: usize, line: usize },
-    CloseSquare { priority: usize, line: usize },
+    Integer { value: i64, line: usize },
+    Float { value: f64, line: usize },
+    String { value: String, line: usize },
+    Boolean { value: bool, line: usize },
+    Identifier { value: String, line: usize },
+
+    Plus { line: usize },
+    Minus { line: usize },
+    Star { line: usize },
+    Slash { line: usize },
+    Modulo { line: usize },
+    Equal { line: usize },
+    Not { line: usize },
+    Less { line: usize },
+    Greater { line: usize },
+    Ampersand { line: usize },
+    Pipe { line: usize },
+    Comma { line: usize },
+    Dot { line: usize },
+    /// Only exists while tokenizing, since it must be followed by a third '.'
+    DotDot { line: usize },
+    Ellipsis { line: usize },
+    Colon { line: usize },
+
+    OpenParen { line: usize },
+    CloseParen { line: usize },
+    OpenBrace { line: usize },
+    CloseBrace { line: usize },
+    OpenSquare { line: usize },
+    CloseSquare { line: usize },
 
     // Compound tokens
 
-    PlusEqual { priority: usize, line: usize },
-    MinusEqual { priority: usize, line: usize },
-    StarEquals { priority: usize, line: usize },
-    SlashEqual { priority: usize, line: usize },
-    ModuloEqual { priority: usize, line: usize },
-    EqualEqual { priority: usize, line: usize },
-    NotEqual { priority: usize, line: usize },
-    LessEqual { priority: usize, line: usize },
-    GreaterEqual { priority: usize, line: usize },
-    And { priority: usize, line: usize },
-    Or { priority: usize, line: usize },
+    PlusEqual { line: usize },
+    MinusEqual { line: usize },
+    StarEquals { line: usize },
+    SlashEqual { line: usize },
+    ModuloEqual { line: usize },
+    EqualEqual { line: usize },
+    NotEqual { line: usize },
+    Arrow { line: usize },
+    LessEqual { line: usize },
+    GreaterEqual { line: usize },
+    And { line: usize },
+    Or { line: usize },
 
     // Keywords
 
-    Fun { priority: usize, line: usize },
-    Return { priority: usize, line: usize },
-    If { priority: usize, line: usize },
-    Elif { priority: usize, line: usize },
-    Else { priority: usize, line: usize },
-    While { priority: usize, line: usize },
-    For { priority: usize, line: usize },
-    In { priority: usize, line: usize },
-    Break { priority: usize, line: usize },
-    Continue { priority: usize, line: usize },
-    None { priority: usize, line: usize },
-    Let { priority: usize, line: usize },
+    Fun { line: usize },
+    Return { line: usize },
+    If { line: usize },
+    Elif { line: usize },
+    Else { line: usize },
+    While { line: usize },
+    For { line: usize },
+    In { line: usize },
+    Break { line: usize },
+    Continue { line: usize },
+    None { line: usize },
+    Let { line: usize },
+    Const { line: usize },
+    Import { line: usize },
+    From { line: usize },
+    As { line: usize },
+    Match { line: usize },
 
 }
 
@@ -89,6 +100,10 @@ impl Token {
             Token::Ampersand { line, .. } => *line,
             Token::Pipe { line, .. } => *line,
             Token::Comma { line, .. } => *line,
+            Token::Dot { line, .. } => *line,
+            Token::DotDot { line, .. } => *line,
+            Token::Ellipsis { line, .. } => *line,
+            Token::Colon { line, .. } => *line,
 
             Token::OpenParen { line, .. } => *line,
             Token::CloseParen { line, .. } => *line,
@@ -104,6 +119,7 @@ impl Token {
             Token::ModuloEqual { line, .. } => *line,
             Token::EqualEqual { line, .. } => *line,
             Token::NotEqual { line, .. } => *line,
+            Token::Arrow { line, .. } => *line,
             Token::LessEqual { line, .. } => *line,
             Token::GreaterEqual { line, .. } => *line,
             Token::And { line, .. } => *line,
@@ -121,9 +137,86 @@ impl Token {
             Token::Continue { line, .. } => *line,
             Token::None { line, .. } => *line,
             Token::Let { line, .. } => *line,
+            Token::Const { line, .. } => *line,
+            Token::Import { line, .. } => *line,
+            Token::From { line, .. } => *line,
+            Token::As { line, .. } => *line,
+            Token::Match { line, .. } => *line,
         }
     }
 
+
+    /// Return a description of the token as it appears in the source code, for error messages
+    pub fn describe(&self) -> String {
+        let symbol = match self {
+            Token::EndOfStatement { .. } => return "the end of the line".to_string(),
+
+            Token::Numeric { value, .. } => return format!("number {}", value),
+            Token::Integer { value, .. } => return format!("integer {}", value),
+            Token::Float { value, .. } => return format!("float {}", value),
+            Token::String { value, .. } => return format!("string {:?}", value),
+            Token::Boolean { value, .. } => return format!("'{}'", value),
+            Token::Identifier { value, .. } => return format!("identifier '{}'", value),
+
+            Token::Plus { .. } => "+",
+            Token::Minus { .. } => "-",
+            Token::Star { .. } => "*",
+            Token::Slash { .. } => "/",
+            Token::Modulo { .. } => "%",
+            Token::Equal { .. } => "=",
+            Token::Not { .. } => "!",
+            Token::Less { .. } => "<",
+            Token::Greater { .. } => ">",
+            Token::Ampersand { .. } => "&",
+            Token::Pipe { .. } => "|",
+            Token::Comma { .. } => ",",
+            Token::Dot { .. } => ".",
+            Token::DotDot { .. } => "..",
+            Token::Ellipsis { .. } => "...",
+            Token::Colon { .. } => ":",
+
+            Token::OpenParen { .. } => "(",
+            Token::CloseParen { .. } => ")",
+            Token::OpenBrace { .. } => "{",
+            Token::CloseBrace { .. } => "}",
+            Token::OpenSquare { .. } => "[",
+            Token::CloseSquare { .. } => "]",
+
+            Token::PlusEqual { .. } => "+=",
+            Token::MinusEqual { .. } => "-=",
+            Token::StarEquals { .. } => "*=",
+            Token::SlashEqual { .. } => "/=",
+            Token::ModuloEqual { .. } => "%=",
+            Token::EqualEqual { .. } => "==",
+            Token::NotEqual { .. } => "!=",
+            Token::Arrow { .. } => "=>",
+            Token::LessEqual { .. } => "<=",
+            Token::GreaterEqual { .. } => ">=",
+            Token::And { .. } => "&&",
+            Token::Or { .. } => "||",
+
+            Token::Fun { .. } => "fun",
+            Token::Return { .. } => "return",
+            Token::If { .. } => "if",
+            Token::Elif { .. } => "elif",
+            Token::Else { .. } => "else",
+            Token::While { .. } => "while",
+            Token::For { .. } => "for",
+            Token::In { .. } => "in",
+            Token::Break { .. } => "break",
+            Token::Continue { .. } => "continue",
+            Token::None { .. } => "None",
+            Token::Let { .. } => "let",
+            Token::Const { .. } => "const",
+            Token::Import { .. } => "import",
+            Token::From { .. } => "from",
+            Token::As { .. } => "as",
+            Token::Match { .. } => "match",
+        };
+
+        format!("'{}'", symbol)
+    }
+
 }
 
 
@@ -152,6 +245,10 @@ impl std::fmt::Display for Token {
             Token::Ampersand { .. } => write!(f, "Ampersand"),
             Token::Pipe { .. } => write!(f, "Pipe"),
             Token::Comma { .. } => write!(f, "Comma"),
+            Token::Dot { .. } => write!(f, "Dot"),
+            Token::DotDot { .. } => write!(f, "DotDot"),
+            Token::Ellipsis { .. } => write!(f, "Ellipsis"),
+            Token::Colon { .. } => write!(f, "Colon"),
 
             Token::OpenParen { .. } => write!(f, "OpenParen"),
             Token::CloseParen { .. } => write!(f, "CloseParen"),
@@ -167,6 +264,7 @@ impl std::fmt::Display for Token {
             Token::ModuloEqual { .. } => write!(f, "ModuloEqual"),
             Token::EqualEqual { .. } => write!(f, "EqualEqual"),
             Token::NotEqual { .. } => write!(f, "NotEqual"),
+            Token::Arrow { .. } => write!(f, "Arrow"),
             Token::LessEqual { .. } => write!(f, "LessEqual"),
             Token::GreaterEqual { .. } => write!(f, "GreaterEqual"),
             Token::And { .. } => write!(f, "And"),
@@ -184,133 +282,43 @@ impl std::fmt::Display for Token {
             Token::Continue { .. } => write!(f, "Continue"),
             Token::None { .. } => write!(f, "None"),
             Token::Let { .. } => write!(f, "Let"),
+            Token::Const { .. } => write!(f, "Const"),
+            Token::Import { .. } => write!(f, "Import"),
+            Token::From { .. } => write!(f, "From"),
+            Token::As { .. } => write!(f, "As"),
+            Token::Match { .. } => write!(f, "Match"),
         }
     }
 
 }
 
 
-pub fn string_to_keyword(string: &str, priority: usize, line: usize) -> Option<Token> {
+pub fn string_to_keyword(string: &str, line: usize) -> Option<Token> {
     match string {
-        "fun" => Some(Token::Fun { priority, line }),
-        "return" => Some(Token::Return { priority, line }),
-        "if" => Some(Token::If { priority, line }),
-        "elif" => Some(Token::Elif { priority, line }),
-        "else" => Some(Token::Else { priority, line }),
-        "while" => Some(Token::While { priority, line }),
-        "for" => Some(Token::For { priority, line }),
-        "in" => Some(Token::In { priority, line }),
-        "break" => Some(Token::Break { priority, line }),
-        "continue" => Some(Token::Continue { priority, line }),
-        "true" => Some(Token::Boolean { value: true, priority, line }),
-        "false" => Some(Token::Boolean { value: false, priority, line }),
-        "None" => Some(Token::None { priority, line }),
-        "let" => Some(Token::Let { priority, line }),
+        "fun" => Some(Token::Fun { line }),
+        "return" => Some(Token::Return { line }),
+        "if" => Some(Token::If { line }),
+        "elif" => Some(Token::Elif { line }),
+        "else" => Some(Token::Else { line }),
+        "while" => Some(Token::While { line }),
+        "for" => Some(Token::For { line }),
+        "in" => Some(Token::In { line }),
+        "break" => Some(Token::Break { line }),
+        "continue" => Some(Token::Continue { line }),
+        "true" => Some(Token::Boolean { value: true, line }),
+        "false" => Some(Token::Boolean { value: false, line }),
+        "None" => Some(Token::None { line }),
+        "let" => Some(Token::Let { line }),
+        "const" => Some(Token::Const { line }),
+        "import" => Some(Token::Import { line }),
+        "from" => Some(Token::From { line }),
+        "as" => Some(Token::As { line }),
+        "match" => Some(Token::Match { line }),
         _ => None,
     }
 }
 
 
-pub enum Priority {
-    Value = 0,
-    Keyword,
-    Elif,
-    Else,
-    Assignment,
-    Or,
-    And,
-    Equality,
-    Comparison,
-    AddSub,
-    MulDivMod,
-    Not,
-    Grouping,
-}
-
-
-fn add_variant_priority(token_variant: &mut Token) {
-
-    // Branches are ordered by increasing priority following the C operator precedence
-
-    match token_variant {
-
-        // Invalid tokens
-        Token::Numeric { .. } => unimplemented!("Numeric token should not be added to the token list"),
-
-        Token::Integer { priority, .. } => *priority = Priority::Value as usize,
-        Token::Float { priority, .. } => *priority = Priority::Value as usize,
-        Token::String { priority, .. } => *priority = Priority::Value as usize,
-        Token::Boolean { priority, .. } => *priority = Priority::Value as usize,
-        Token::Identifier { priority, .. } => *priority = Priority::Value as usize,
-        Token::None { priority, .. } => *priority = Priority::Value as usize,
-        
-        // Non-operation tokens
-        Token::Comma { priority, .. } => *priority = 0,
-        Token::Ampersand { priority, .. } => *priority = 0,
-        Token::Pipe { priority, .. } => *priority = 0,
-        Token::EndOfStatement { priority, .. } => *priority = 0,
-
-        // Keyword operators
-        Token::Fun { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::Return { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::If { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::Elif { priority, .. } => *priority += Priority::Elif as usize,
-        Token::Else { priority, .. } => *priority += Priority::Else as usize,
-        Token::While { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::For { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::In { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::Break { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::Continue { priority, .. } => *priority += Priority::Keyword as usize,
-        Token::Let { priority, .. } => *priority += Priority::Keyword as usize,
-
-        // Assignment
-        Token::Equal { priority, .. } => *priority += Priority::Assignment as usize,
-        Token::PlusEqual { priority, .. } => *priority += Priority::Assignment as usize,
-        Token::MinusEqual { priority, .. } => *priority += Priority::Assignment as usize,
-        Token::StarEquals { priority, .. } => *priority += Priority::Assignment as usize,
-        Token::SlashEqual { priority, .. } => *priority += Priority::Assignment as usize,
-        Token::ModuloEqual { priority, .. } => *priority += Priority::Assignment as usize,
-
-        // Logical or
-        Token::Or { priority, .. } => *priority += Priority::Or as usize,
-
-        // Logical and
-        Token::And { priority, .. } => *priority += Priority::And as usize,
-
-        // Equality
-        Token::EqualEqual { priority, .. } => *priority += Priority::Equality as usize,
-        Token::NotEqual { priority, .. } => *priority += Priority::Equality as usize,
-
-        // Comparison
-        Token::Less { priority, .. } => *priority += Priority::Comparison as usize,
-        Token::Greater { priority, .. } => *priority += Priority::Comparison as usize,
-        Token::LessEqual { priority, .. } => *priority += Priority::Comparison as usize,
-        Token::GreaterEqual { priority, .. } => *priority += Priority::Comparison as usize,
-
-        // Addition and subtraction
-        Token::Plus { priority, .. } => *priority += Priority::AddSub as usize,
-        Token::Minus { priority, .. } => *priority += Priority::AddSub as usize,
-
-        // Multiplication, division, and remainder
-        Token::Star { priority, .. } => *priority += Priority::MulDivMod as usize,
-        Token::Slash { priority, .. } => *priority += Priority::MulDivMod as usize,
-        Token::Modulo { priority, .. } => *priority += Priority::MulDivMod as usize,
-
-        // Logical not
-        Token::Not { priority, .. } => *priority += Priority::Not as usize,
-
-        // Grouping
-        Token::OpenParen { priority, .. } => *priority += Priority::Grouping as usize,
-        Token::CloseParen { priority, .. } => *priority += Priority::Grouping as usize,
-        Token::OpenBrace { priority, .. } => *priority += Priority::Grouping as usize,
-        Token::CloseBrace { priority, .. } => *priority += Priority::Grouping as usize,
-        Token::OpenSquare { priority, .. } => *priority += Priority::Grouping as usize,
-        Token::CloseSquare { priority, .. } => *priority += Priority::Grouping as usize,
-        
-    }
-}
-
-
 pub struct TokenList {
     tokens: Vec<Token>,
 }
@@ -324,8 +332,7 @@ impl TokenList {
         }
     }
 
-    pub fn push(&mut self, mut token: Token) {
-        add_variant_priority(&mut token);
+    pub fn push(&mut self, token: Token) {
         self.tokens.push(token);
     }
 
@@ -337,5 +344,13 @@ impl TokenList {
         self.tokens.last()
     }
 
+    pub fn pop(&mut self) -> Option<Token> {
+        self.tokens.pop()
+    }
+
+    pub fn as_slice(&self) -> &[Token] {
+        &self.tokens
+    }
+
 }
 
@@ -1,4 +1,4 @@
-use super::token::{Token, TokenList, Priority, string_to_keyword};
+use super::token::{Token, TokenList, string_to_keyword};
 use super::error;
 
 
@@ -10,8 +10,41 @@ fn is_start_of_name_char(c: char) -> bool {
     c.is_alphabetic() || c == '_'
 }
 
+/// Numeric literals are collected greedily and validated once complete, so that malformed literals
+/// such as `1.2.3` or `0b12` are reported as a whole
 fn is_numeric_char(c: char) -> bool {
-    c >= '0' && c <= '9' || c == '.'
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+fn is_start_of_numeric_char(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
+
+/// Whether the token can end an operand, so that a minus after it is a subtraction rather than a negation
+fn ends_operand(token: &Token) -> bool {
+    matches!(token,
+        Token::Integer { .. } | Token::Float { .. } | Token::String { .. } | Token::Boolean { .. } |
+        Token::Identifier { .. } | Token::None { .. } | Token::CloseParen { .. } | Token::CloseSquare { .. }
+    )
+}
+
+
+/// Whether the last token is a minus that negates the next operand
+fn is_negation(tokens: &TokenList) -> bool {
+    match tokens.as_slice() {
+        [.., before, Token::Minus { .. }] => !ends_operand(before),
+        [Token::Minus { .. }] => true,
+        _ => false,
+    }
+}
+
+
+/// Leave the innermost grouping if it was opened by the given character
+fn close_grouping(groupings: &mut Vec<char>, open: char) {
+    if groupings.last() == Some(&open) {
+        groupings.pop();
+    }
 }
 
 
@@ -21,9 +54,9 @@ pub fn tokenize(source: &str) -> TokenList {
     let mut line: usize = 0;
 
     let mut current_token: Option<Token> = None;
-    let mut current_priority: usize = 0;
     let mut string_escape: bool = false;
-    let mut grouping_depth: usize = 0;
+    // The opening characters of the groupings the current character is in, from the outermost
+    let mut groupings: Vec<char> = Vec::new();
     let mut is_comment: bool = false;
 
     for ch in source.chars() {
@@ -41,16 +74,24 @@ pub fn tokenize(source: &str) -> TokenList {
             match token {
 
                 Token::Numeric { value, .. } => {
-                    if is_numeric_char(ch) {
+                    // The sign of an exponent is part of the number, but 'e' is a digit in hexadecimal literals
+                    let is_exponent_sign = (ch == '+' || ch == '-')
+                        && value.ends_with(['e', 'E'])
+                        && !value.starts_with("0x") && !value.starts_with("0X");
+
+                    if is_numeric_char(ch) || is_exponent_sign {
                         value.push(ch);
                         continue;
                     }
 
-                    // Differentiate between integers and floats
-                    if value.contains('.') {
-                        tokens.push(Token::Float { value: value.parse().unwrap(), priority: current_priority, line });
-                    } else {
-                        tokens.push(Token::Integer { value: value.parse().unwrap(), priority: current_priority, line });
+                    match parse_number(value, line, is_negation(&tokens)) {
+                        // The smallest integer only fits once negated, so the minus is folded into the literal
+                        Ok(number @ Token::Integer { value: i64::MIN, .. }) => {
+                            tokens.pop();
+                            tokens.push(number);
+                        },
+                        Ok(number) => tokens.push(number),
+                        Err(hint) => error::invalid_numeric_literal(value, line, source, &hint),
                     }
 
                     current_token = None;
@@ -67,12 +108,17 @@ pub fn tokenize(source: &str) -> TokenList {
                             'r' => '\r',
                             '"' => '"',
                             '\\' => '\\',
-                            _ => error::invalid_escape_sequence(ch, line, &source, "Valid escape sequences are: '\\n', '\\t', '\\r', '\\\"' and '\\\\'"),
+                            _ => error::invalid_escape_sequence(ch, line, source, "Valid escape sequences are: '\\n', '\\t', '\\r', '\\\"' and '\\\\'"),
                         });
                         string_escape = false;
                         continue;
                     }
 
+                    if ch == '\\' {
+                        string_escape = true;
+                        continue;
+                    }
+
                     if ch == '"' {
                         tokens.push(current_token.take().unwrap());
                         // current_token is None after take()
@@ -90,7 +136,7 @@ pub fn tokenize(source: &str) -> TokenList {
                     }
 
                     // Check if the name is a keyword
-                    if let Some(keyword) = string_to_keyword(value, current_priority, line) {
+                    if let Some(keyword) = string_to_keyword(value, line) {
                         tokens.push(keyword);
                         current_token = None;
                     } else {
@@ -103,120 +149,135 @@ pub fn tokenize(source: &str) -> TokenList {
 
                 Token::Plus { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::PlusEqual { priority: current_priority, line });
+                        tokens.push(Token::PlusEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Minus { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::MinusEqual { priority: current_priority, line });
+                        tokens.push(Token::MinusEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Star { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::StarEquals { priority: current_priority, line });
+                        tokens.push(Token::StarEquals { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Slash { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::SlashEqual { priority: current_priority, line });
+                        tokens.push(Token::SlashEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Modulo { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::ModuloEqual { priority: current_priority, line });
+                        tokens.push(Token::ModuloEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Equal { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::EqualEqual { priority: current_priority, line });
+                        tokens.push(Token::EqualEqual { line });
+                        current_token = None;
+                        continue;
+                    }
+
+                    if ch == '>' {
+                        tokens.push(Token::Arrow { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Not { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::NotEqual { priority: current_priority, line });
+                        tokens.push(Token::NotEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Less { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::LessEqual { priority: current_priority, line });
+                        tokens.push(Token::LessEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Greater { .. } => {
                     if ch == '=' {
-                        tokens.push(Token::GreaterEqual { priority: current_priority, line });
+                        tokens.push(Token::GreaterEqual { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
                 },
 
                 Token::Ampersand { .. } => {
                     if ch == '&' {
-                        tokens.push(Token::And { priority: current_priority, line });
+                        tokens.push(Token::And { line });
                         current_token = None;
                         continue;
                     }
 
-                    error::invalid_character(ch, line, &source, "Expected '&' to be followed by another '&'. Bitwise and is not supported.");
+                    error::invalid_character(ch, line, source, "Expected '&' to be followed by another '&'. Bitwise and is not supported.");
                     // tokens.push(current_token.take().unwrap());
                     // // current_token is None after take()
                     // continue;
@@ -224,97 +285,136 @@ pub fn tokenize(source: &str) -> TokenList {
 
                 Token::Pipe { .. } => {
                     if ch == '|' {
-                        tokens.push(Token::Or { priority: current_priority, line });
+                        tokens.push(Token::Or { line });
                         current_token = None;
                         continue;
                     }
 
                     tokens.push(current_token.take().unwrap());
                     // current_token is None after take()
-                    continue;
+
+                    // The current character is not part of the operator, so it must be processed again
+                },
+
+                Token::Dot { .. } => {
+                    if ch == '.' {
+                        current_token = Some(Token::DotDot { line });
+                        continue;
+                    }
+
+                    tokens.push(current_token.take().unwrap());
+                    // current_token is None after take()
+
+                    // The current character is not part of the token, so it must be processed again
+                },
+
+                Token::DotDot { .. } => {
+                    if ch == '.' {
+                        tokens.push(Token::Ellipsis { line });
+                        current_token = None;
+                        continue;
+                    }
+
+                    error::invalid_character(ch, line, source, "Expected '..' to be followed by another '.' to spread a list.");
+                },
+
+                // Single-character tokens are already complete
+                Token::Comma { .. } |
+                Token::Colon { .. } |
+                Token::OpenParen { .. } |
+                Token::CloseParen { .. } |
+                Token::OpenSquare { .. } |
+                Token::CloseSquare { .. } |
+                Token::OpenBrace { .. } |
+                Token::CloseBrace { .. } => {
+                    tokens.push(current_token.take().unwrap());
+                    // current_token is None after take()
+
+                    // The current character is not part of the token, so it must be processed again
                 },
                 
-                _ => unimplemented!("Invalid token: {:?}", token),
+                _ => error::invalid_character(ch, line, source, &format!("The character cannot follow {}.", token.describe())),
 
             }
 
         }
 
         if is_start_of_name_char(ch) {
-            current_token = Some(Token::Identifier { value: String::new(), priority: current_priority, line });
+            current_token = Some(Token::Identifier { value: ch.to_string(), line });
             continue;
         }
 
-        if is_numeric_char(ch) {
+        // A leading dot is a member access, not the start of a float
+        if is_start_of_numeric_char(ch) {
             current_token = Some(Token::Numeric { value: ch.to_string(), line });
             continue;
         }
 
         match ch {
-            '+' => current_token = Some(Token::Plus { priority: current_priority, line }),
-            '-' => current_token = Some(Token::Minus { priority: current_priority, line }),
-            '*' => current_token = Some(Token::Star { priority: current_priority, line }),
-            '/' => current_token = Some(Token::Slash { priority: current_priority, line }),
-            '%' => current_token = Some(Token::Modulo { priority: current_priority, line }),
-            '=' => current_token = Some(Token::Equal { priority: current_priority, line }),
-            '!' => current_token = Some(Token::Not { priority: current_priority, line }),
-            '<' => current_token = Some(Token::Less { priority: current_priority, line }),
-            '>' => current_token = Some(Token::Greater { priority: current_priority, line }),
-            '&' => current_token = Some(Token::Ampersand { priority: current_priority, line }),
-            '|' => current_token = Some(Token::Pipe { priority: current_priority, line }),
-            ',' => current_token = Some(Token::Comma { priority: current_priority, line }),
-            '"' => current_token = Some(Token::String { value: String::new(), priority: current_priority, line }),
+            '+' => current_token = Some(Token::Plus { line }),
+            '-' => current_token = Some(Token::Minus { line }),
+            '*' => current_token = Some(Token::Star { line }),
+            '/' => current_token = Some(Token::Slash { line }),
+            '%' => current_token = Some(Token::Modulo { line }),
+            '=' => current_token = Some(Token::Equal { line }),
+            '!' => current_token = Some(Token::Not { line }),
+            '<' => current_token = Some(Token::Less { line }),
+            '>' => current_token = Some(Token::Greater { line }),
+            '&' => current_token = Some(Token::Ampersand { line }),
+            '|' => current_token = Some(Token::Pipe { line }),
+            ',' => current_token = Some(Token::Comma { line }),
+            '.' => current_token = Some(Token::Dot { line }),
+            ':' => current_token = Some(Token::Colon { line }),
+            '"' => current_token = Some(Token::String { value: String::new(), line }),
 
             '(' => {
-                current_token = Some(Token::OpenParen { priority: current_priority, line });
-                current_priority += Priority::Grouping as usize;
-                grouping_depth += 1;
+                current_token = Some(Token::OpenParen { line });
+                groupings.push(ch);
             },
             ')' => {
-                current_priority -= Priority::Grouping as usize;
-                grouping_depth -= 1;
-                current_token = Some(Token::CloseParen { priority: current_priority, line });
+                // Unmatched closing tokens are reported by the parser
+                close_grouping(&mut groupings, '(');
+                current_token = Some(Token::CloseParen { line });
             },
             '[' => {
-                current_token = Some(Token::OpenSquare { priority: current_priority, line });
-                current_priority += Priority::Grouping as usize;
-                grouping_depth += 1;
+                current_token = Some(Token::OpenSquare { line });
+                groupings.push(ch);
             },
             ']' => {
-                current_priority -= Priority::Grouping as usize;
-                grouping_depth -= 1;
-                current_token = Some(Token::CloseSquare { priority: current_priority, line });
+                close_grouping(&mut groupings, '[');
+                current_token = Some(Token::CloseSquare { line });
             },
             '{' => {
-                current_priority += Priority::Grouping as usize;
-                current_token = Some(Token::OpenBrace { priority: current_priority, line });
+                current_token = Some(Token::OpenBrace { line });
+                groupings.push(ch);
             },
             '}' => {
-                current_priority -= Priority::Grouping as usize;
-                current_token = Some(Token::CloseBrace { priority: current_priority, line });
+                close_grouping(&mut groupings, '{');
+                current_token = Some(Token::CloseBrace { line });
             },
 
             '#' => is_comment = true,
 
             '\n' => {
-                line += 1;
                 if let Some(token) = current_token {
                     tokens.push(token);
                     current_token = None;
                 }
-                // The statement isn't finished if the newline is found inside a grouping token
+                // The statement isn't finished if the newline is found inside parentheses or square brackets,
+                // but the body of a function passed as an argument is made of statements again
                 // Also, don't push a new EndOFStatement token if the last token is already an EndOfStatement
-                if grouping_depth == 0 &&
+                if matches!(groupings.last(), None | Some('{')) &&
                     !matches!(tokens.last(), Some(Token::EndOfStatement { .. })) {
-                    tokens.push(Token::EndOfStatement { priority: current_priority, line });
+                    tokens.push(Token::EndOfStatement { line });
                 }
+                line += 1;
             },
 
             // Ignored characters
             ' ' | '\t' | '\r' => continue,
             
             // Unhandled character
-            _ => error::invalid_character(ch, line, &source, "The character is not valid in this context."),
+            _ => error::invalid_character(ch, line, source, "The character is not valid in this context."),
         }
 
         // No code should be able to reach this point
@@ -323,3 +423,95 @@ pub fn tokenize(source: &str) -> TokenList {
     tokens
 }
 
+
+/// Convert a complete numeric literal into an Integer or Float token.
+///
+/// Integers can be written in decimal, or in hexadecimal, binary or octal with the `0x`, `0b` and `0o` prefixes.
+/// Floats are decimal, with a fractional part, an exponent such as `1e9` or `2.5E-3`, or both.
+/// Digits can be grouped with `_` separators, as in `1_000_000`.
+/// If the literal is negated, its magnitude can be one more than the largest integer, and the token is the negative value.
+/// Return a hint describing the problem if the literal is malformed or doesn't fit in its type
+fn parse_number(literal: &str, line: usize, is_negated: bool) -> Result<Token, String> {
+    let (radix, digits) = match literal.get(.. 2) {
+        Some("0x") | Some("0X") => (16, &literal[2 ..]),
+        Some("0b") | Some("0B") => (2, &literal[2 ..]),
+        Some("0o") | Some("0O") => (8, &literal[2 ..]),
+        _ => (10, literal),
+    };
+
+    // Separators are only allowed between two digits
+    let chars: Vec<char> = digits.chars().collect();
+    for (index, ch) in chars.iter().enumerate() {
+        if *ch == '_' {
+            let is_between_digits = index > 0 && index + 1 < chars.len()
+                && chars[index - 1].is_digit(radix) && chars[index + 1].is_digit(radix);
+            if !is_between_digits {
+                return Err("The '_' separator can only be used between two digits.".to_string());
+            }
+        }
+    }
+    let digits: String = chars.into_iter().filter(|ch| *ch != '_').collect();
+
+    if radix != 10 {
+        if digits.is_empty() {
+            return Err(format!("Expected digits after the '{}' prefix.", &literal[.. 2]));
+        }
+        if let Some(invalid) = digits.chars().find(|ch| !ch.is_digit(radix)) {
+            return Err(format!("'{}' is not a valid digit in base {}.", invalid, radix));
+        }
+        return integer_token(u64::from_str_radix(&digits, radix).ok(), line, is_negated);
+    }
+
+    // Validate the shape of the literal, since Rust's parser accepts forms such as "inf" or "1."
+    let mut rest = digits.as_str();
+    let integer_digits = rest.len() - rest.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+    rest = &rest[integer_digits ..];
+    let mut is_float = false;
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let fraction_digits = fraction.len() - fraction.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+        if fraction_digits == 0 {
+            return Err("Expected digits after the decimal point.".to_string());
+        }
+        rest = &fraction[fraction_digits ..];
+        is_float = true;
+    }
+
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let exponent_digits = exponent.len() - exponent.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+        if exponent_digits == 0 {
+            return Err("Expected digits in the exponent.".to_string());
+        }
+        rest = &exponent[exponent_digits ..];
+        is_float = true;
+    }
+
+    match rest.chars().next() {
+        Some('.') => return Err("A number can only have one decimal point.".to_string()),
+        Some(unexpected) => return Err(format!("Unexpected character '{}' in the number.", unexpected)),
+        None => {},
+    }
+
+    if is_float {
+        let value: f64 = digits.parse().unwrap();
+        if value.is_infinite() {
+            return Err(format!("The float is too large. The largest float is {:e}.", f64::MAX));
+        }
+        Ok(Token::Float { value, line })
+    } else {
+        integer_token(digits.parse().ok(), line, is_negated)
+    }
+}
+
+
+/// Create the token of an integer literal from its magnitude, which is None if it doesn't even fit in 64 bits
+fn integer_token(magnitude: Option<u64>, line: usize, is_negated: bool) -> Result<Token, String> {
+    match magnitude {
+        Some(magnitude) if is_negated && magnitude == i64::MIN.unsigned_abs() => Ok(Token::Integer { value: i64::MIN, line }),
+        Some(magnitude) => i64::try_from(magnitude)
+            .map(|value| Token::Integer { value, line })
+            .map_err(|_| format!("The integer is too large. The largest integer is {}.", i64::MAX)),
+        None => Err(format!("The integer is too large. The largest integer is {}.", i64::MAX)),
+    }
+}
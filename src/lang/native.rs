@@ -0,0 +1,99 @@
+use super::object::{Object, OpResult, TypeCode, Value};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// The signature of functions implemented in Rust and callable from scripts.
+/// Arguments are passed in call order and are already dereferenced.
+pub type NativeFn = fn(&mut Vm, &[Object]) -> OpResult;
+
+
+/// A function implemented natively.
+#[derive(Debug, Clone, Copy)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub function: NativeFn,
+}
+
+
+impl NativeFunction {
+
+    /// Create a new function object wrapping the native function
+    pub fn new_object(name: &'static str, function: NativeFn) -> Object {
+        Object::new(TypeCode::Function, Value::Native(NativeFunction { name, function }))
+    }
+
+}
+
+
+/// Raise a TypeError if the number of arguments passed to the native function is not the expected one.
+pub fn expect_arg_count(function: &str, args: &[Object], expected: usize) -> Result<(), RuntimeError> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("{}() takes {} argument{} but {} were given", function, expected, if expected == 1 { "" } else { "s" }, args.len())
+        ))
+    }
+}
+
+
+/// Raise a TypeError if fewer than the minimum number of arguments were passed to the native function.
+pub fn expect_min_arg_count(function: &str, args: &[Object], min: usize) -> Result<(), RuntimeError> {
+    if args.len() >= min {
+        Ok(())
+    } else {
+        Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("{}() takes at least {} argument{} but {} were given", function, min, if min == 1 { "" } else { "s" }, args.len())
+        ))
+    }
+}
+
+
+/// Raise a TypeError if the number of arguments is not between min and max, inclusive.
+pub fn expect_arg_range(function: &str, args: &[Object], min: usize, max: usize) -> Result<(), RuntimeError> {
+    if (min..=max).contains(&args.len()) {
+        Ok(())
+    } else {
+        Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("{}() takes from {} to {} arguments but {} were given", function, min, max, args.len())
+        ))
+    }
+}
+
+
+/// Create a TypeError for an argument that is not of any of the expected types.
+pub fn wrong_arg_type(function: &str, arg: &Object, expected: &[TypeCode]) -> RuntimeError {
+    let names: Vec<&str> = expected.iter().map(|type_code| type_code.name()).collect();
+    let expected = match names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, others)) => format!("{} or {}", others.join(", "), last),
+        None => "nothing".to_string(),
+    };
+
+    RuntimeError::with_message(
+        ErrorCode::TypeError,
+        format!("{}() expected {}, got {}", function, expected, arg.type_code.name())
+    )
+}
+
+
+/// Return the string value of the argument, or raise a TypeError if it's not a string.
+pub fn expect_string<'a>(function: &str, arg: &'a Object) -> Result<&'a str, RuntimeError> {
+    match arg {
+        Object { type_code: TypeCode::String, value: Value::String(value), .. } => Ok(value),
+        _ => Err(wrong_arg_type(function, arg, &[TypeCode::String])),
+    }
+}
+
+
+/// Return the integer value of the argument, or raise a TypeError if it's not an integer.
+pub fn expect_int(function: &str, arg: &Object) -> Result<i64, RuntimeError> {
+    match arg {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => Ok(*value),
+        _ => Err(wrong_arg_type(function, arg, &[TypeCode::Int])),
+    }
+}
@@ -1,20 +1,28 @@
-use crate::compiler::code_node::CodeNode;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::compiler::code_object::CodeObject;
+use super::native::NativeFunction;
 use crate::runtime::error_codes::{ErrorCode, RuntimeError};
-use crate::utils::byte_code::{ByteCode, self};
+use crate::runtime::memory::Address;
 
 
-
-pub enum TypeSize {
-    Number = 8,
-    Boolean = 1,
-    None = 0
-}
+pub type OpResult = Result<Object, RuntimeError>;
 
 
-pub type OpResult = Result<Object, RuntimeError>;
+/// Create an OverflowError for an integer operation whose result doesn't fit in an Int
+pub fn overflow_error(operation: &str, lhs: i64, rhs: i64) -> RuntimeError {
+    RuntimeError::with_message(
+        ErrorCode::OverflowError,
+        format!("Cannot {} {} and {}: integer overflow", operation, lhs, rhs)
+    )
+}
 
 
-const TYPE_CODE_COUNT: usize = 8;
+const TYPE_CODE_COUNT: usize = 10;
 
 
 #[derive(Debug, Clone, Copy)]
@@ -27,10 +35,12 @@ pub enum TypeCode {
     None,
     Function,
     Ref,
+    Module,
+    Iterator,
 }
 
 
-const TYPE_CODE_NAMES: [&'static str; TYPE_CODE_COUNT] = [
+const TYPE_CODE_NAMES: [&str; TYPE_CODE_COUNT] = [
     "Int",
     "Float",
     "String",
@@ -39,6 +49,8 @@ const TYPE_CODE_NAMES: [&'static str; TYPE_CODE_COUNT] = [
     "None",
     "Function",
     "Ref",
+    "Module",
+    "Iterator",
 ];
 
 
@@ -55,7 +67,7 @@ impl From<u8> for TypeCode {
 
     fn from(code: u8) -> Self {
         if code < TYPE_CODE_COUNT as u8 {
-            unsafe { std::mem::transmute(code) }
+            unsafe { std::mem::transmute::<u8, TypeCode>(code) }
         } else {
             panic!("Invalid type code: {}", code);
         }
@@ -68,12 +80,63 @@ impl From<u8> for TypeCode {
 pub enum Value {
     Int(i64),
     Float(f64),
-    String(String),
+    String(Rc<str>),
     Bool(bool),
     List(Vec<Object>),
     None,
-    Function(*mut CodeNode),
+    Function(Function),
+    Native(NativeFunction),
     Ref(*mut Object),
+    Module(Namespace),
+    Iterator(ObjectIterator),
+}
+
+
+/// A function declared in the source code, with the variables it captured from the enclosing functions
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub code_object: *const CodeObject,
+    /// The captured variables, in the order of the code object's captures.
+    /// They stay on the heap, which never frees objects, so the function can outlive the scopes that declared them
+    pub captures: Rc<[*mut Object]>,
+}
+
+
+/// The state of a for loop over an iterable object
+#[derive(Debug, Clone)]
+pub enum ObjectIterator {
+    /// The elements of a list, or the characters of a string
+    Elements(std::vec::IntoIter<Object>),
+    /// The integers from next up to, but excluding, end
+    Range { next: i64, end: i64 },
+}
+
+
+impl Iterator for ObjectIterator {
+    type Item = Object;
+
+    fn next(&mut self) -> Option<Object> {
+        match self {
+            ObjectIterator::Elements(elements) => elements.next(),
+            ObjectIterator::Range { next, end } => {
+                if next < end {
+                    let value = *next;
+                    *next += 1;
+                    Some(Object::new(TypeCode::Int, Value::Int(value)))
+                } else {
+                    None
+                }
+            },
+        }
+    }
+}
+
+
+/// The members exported by a module, mapped to their heap addresses
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    pub name: String,
+    pub members: HashMap<String, Address>,
 }
 
 
@@ -86,6 +149,39 @@ pub struct Object {
 }
 
 
+/// Format the object the way scripts print it.
+/// Strings are quoted inside lists, so that their elements can be told apart
+impl std::fmt::Display for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.value {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{:?}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::List(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match &element.value {
+                        Value::String(value) => write!(f, "{:?}", value)?,
+                        _ => write!(f, "{}", element)?,
+                    }
+                }
+                write!(f, "]")
+            },
+            Value::None => write!(f, "None"),
+            Value::Function(function) => write!(f, "<function {}>", unsafe { &(*function.code_object).name }),
+            Value::Native(native) => write!(f, "<native function {}>", native.name),
+            Value::Ref(object_ptr) => write!(f, "{}", unsafe { &**object_ptr }),
+            Value::Module(namespace) => write!(f, "<module {}>", namespace.name),
+            Value::Iterator(_) => write!(f, "<iterator>"),
+        }
+    }
+}
+
+
 impl Object {
 
     /// Returns a None object
@@ -145,55 +241,136 @@ impl Object {
 
 
     pub fn dec_ref_count(&mut self) {
-        self.ref_count -= 1;
+        self.ref_count = self.ref_count.saturating_sub(1);
     }
 
 
+    /// Release the object referenced by this object, if any
     pub fn destroy(&mut self) {
-        todo!("Decrease ref count in references")
+        if let Value::Ref(object_ptr) = self.value {
+            unsafe { (*object_ptr).dec_ref_count() };
+        }
     }
 
 
-    pub fn is_dead(&self) -> bool {
-        self.ref_count == 0
+    /// Return the type and value of the object, for debugging output.
+    /// References are followed, so that the referenced value is shown
+    pub fn describe(&self) -> String {
+        match &self.value {
+            Value::Int(value) => format!("Int {}", value),
+            Value::Float(value) => format!("Float {:?}", value),
+            Value::String(value) => format!("String {:?}", value),
+            Value::Bool(value) => format!("Bool {}", value),
+            Value::List(elements) => format!("List [{}]", elements.iter().map(Object::describe).collect::<Vec<String>>().join(", ")),
+            Value::None => "None".to_string(),
+            Value::Function(function) => format!("Function {}", unsafe { &(*function.code_object).name }),
+            Value::Native(native) => format!("Native {}", native.name),
+            Value::Ref(object_ptr) => format!("Ref -> {}", unsafe { (**object_ptr).describe() }),
+            Value::Module(namespace) => format!("Module {}", namespace.name),
+            Value::Iterator(_) => "Iterator".to_string(),
+        }
     }
 
 
-    pub fn from_byte_code_const(type_code: TypeCode, code: &ByteCode, index: usize) -> (Object, usize) {
-        match type_code {
-            TypeCode::Int => {
-                let (number, to_add) = byte_code::get_raw_int(index, code);
-                (Object::new(TypeCode::Int, Value::Int(number)), to_add)
+    /// Return an iterator over the elements of the object
+    pub fn get_iter(obj: &Object) -> OpResult {
+        let iterator = match obj {
+            Object { type_code: TypeCode::List, value: Value::List(elements), .. } => {
+                ObjectIterator::Elements(elements.clone().into_iter())
             },
-            TypeCode::Float => {
-                let (number, to_add) = byte_code::get_raw_float(index, code);
-                (Object::new(TypeCode::Float, Value::Float(number as f64)), to_add)
+            Object { type_code: TypeCode::String, value: Value::String(string), .. } => {
+                let chars: Vec<Object> = string.chars().map(
+                    |ch| Object::new(TypeCode::String, Value::String(ch.to_string().into()))
+                ).collect();
+                ObjectIterator::Elements(chars.into_iter())
             },
-            TypeCode::String => {
-                let (string, to_add) = byte_code::get_raw_string(index, code);
-                (Object::new(TypeCode::String, Value::String(string)), to_add)
+            Object { type_code: TypeCode::Int, value: Value::Int(end), .. } => {
+                ObjectIterator::Range { next: 0, end: *end }
             },
-            TypeCode::Bool => {
-                let (boolean, to_add) = byte_code::get_raw_boolean(index, code);
-                (Object::new(TypeCode::Bool, Value::Bool(boolean)), to_add)
+            _ => return Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("{} is not iterable", obj.type_code.name())
+            )),
+        };
+
+        Ok(Object::new(TypeCode::Iterator, Value::Iterator(iterator)))
+    }
+
+
+    /// Return a copy of the element of the iterable at the given index.
+    /// Elements are never returned by reference, since the list could be reassigned or grow while the reference is in use
+    pub fn subscript(iterable: &Object, index: &Object) -> OpResult {
+        match iterable {
+            Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => {
+                Object::subscript(unsafe { &**object_ptr }, index)
             },
-            TypeCode::None => {
-                (Object::new(TypeCode::None, Value::None), TypeSize::None as usize)
+            Object { type_code: TypeCode::List, value: Value::List(elements), .. } => {
+                let position = Object::to_index(index, elements.len())?;
+                Ok(elements[position].clone())
             },
-            
-            _ => unimplemented!("Cannot create object from byte code for type {}", type_code.name())
+            Object { type_code: TypeCode::String, value: Value::String(string), .. } => {
+                let position = Object::to_index(index, string.chars().count())?;
+                let ch = string.chars().nth(position).unwrap();
+                Ok(Object::new(TypeCode::String, Value::String(ch.to_string().into())))
+            },
+            _ => Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("{} is not subscriptable", iterable.type_code.name())
+            )),
         }
     }
 
 
+    /// Return the element of the list at the given index, to assign it
+    pub fn subscript_mut<'a>(&'a mut self, index: &Object) -> Result<&'a mut Object, RuntimeError> {
+        match self {
+            Object { type_code: TypeCode::List, value: Value::List(elements), .. } => {
+                let position = Object::to_index(index, elements.len())?;
+                Ok(&mut elements[position])
+            },
+            _ => Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("Cannot assign to an element of {}, only List elements can be assigned", self.type_code.name())
+            )),
+        }
+    }
+
+
+    /// Convert the index object to a position in a sequence of the given length.
+    /// Negative indices count from the end of the sequence
+    fn to_index(index: &Object, length: usize) -> Result<usize, RuntimeError> {
+        let index = match index {
+            Object { type_code: TypeCode::Int, value: Value::Int(index), .. } => *index,
+            Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => {
+                return Object::to_index(unsafe { &**object_ptr }, length);
+            },
+            _ => return Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("Index must be an Int, got {}", index.type_code.name())
+            )),
+        };
+
+        let position = if index < 0 {
+            length.checked_sub(index.unsigned_abs() as usize)
+        } else {
+            Some(index as usize).filter(|position| *position < length)
+        };
+
+        position.ok_or_else(|| RuntimeError::with_message(
+            ErrorCode::IndexError,
+            format!("Index {} is out of range for length {}", index, length)
+        ))
+    }
+
+
     pub fn add(lhs: &Object, rhs: &Object) -> OpResult {
         match (lhs, rhs) {
 
             (Object { type_code: TypeCode::Int, value: Value::Int(lhs), .. }, Object { type_code: TypeCode::Int, value: Value::Int(rhs), .. }) => {
-                Ok(Object::new(
-                    TypeCode::Int,
-                    Value::Int(lhs + rhs)
-                ))
+                match lhs.checked_add(*rhs) {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(overflow_error("add", *lhs, *rhs)),
+                }
             },
 
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
@@ -217,7 +394,7 @@ impl Object {
             (Object { type_code: TypeCode::String, value: Value::String(lhs), .. }, Object { type_code: TypeCode::String, value: Value::String(rhs), .. }) => {
                 Ok(Object::new(
                     TypeCode::String,
-                    Value::String(lhs.to_owned() + rhs)
+                    Value::String(format!("{}{}", lhs, rhs).into())
                 ))
             },
             
@@ -244,10 +421,10 @@ impl Object {
         match (lhs, rhs) {
 
             (Object { type_code: TypeCode::Int, value: Value::Int(lhs), .. }, Object { type_code: TypeCode::Int, value: Value::Int(rhs), .. }) => {
-                Ok(Object::new(
-                    TypeCode::Int,
-                    Value::Int(lhs - rhs)
-                ))
+                match lhs.checked_sub(*rhs) {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(overflow_error("subtract", *lhs, *rhs)),
+                }
             },
 
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
@@ -279,14 +456,42 @@ impl Object {
     }
 
 
+    pub fn neg(obj: &Object) -> OpResult {
+        match obj {
+
+            Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => {
+                match value.checked_neg() {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(RuntimeError::with_message(
+                        ErrorCode::OverflowError,
+                        format!("Cannot negate {}: integer overflow", value)
+                    )),
+                }
+            },
+
+            Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => {
+                Ok(Object::new(
+                    TypeCode::Float,
+                    Value::Float(-value)
+                ))
+            },
+
+            _ => Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("Cannot negate {}", obj.type_code.name())
+            )),
+        }
+    }
+
+
     pub fn mul(lhs: &Object, rhs: &Object) -> OpResult {
         match (lhs, rhs) {
             
             (Object { type_code: TypeCode::Int, value: Value::Int(lhs), .. }, Object { type_code: TypeCode::Int, value: Value::Int(rhs), .. }) => {
-                Ok(Object::new(
-                    TypeCode::Int,
-                    Value::Int(lhs * rhs)
-                ))
+                match lhs.checked_mul(*rhs) {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(overflow_error("multiply", *lhs, *rhs)),
+                }
             },
 
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
@@ -328,10 +533,11 @@ impl Object {
                     ));
                 }
                 
-                Ok(Object::new(
-                    TypeCode::Int,
-                    Value::Int(lhs / rhs)
-                ))
+                // i64::MIN / -1 is the only overflowing division
+                match lhs.checked_div(*rhs) {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(overflow_error("divide", *lhs, *rhs)),
+                }
             },
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
                 if *rhs == 0.0 {
@@ -391,10 +597,10 @@ impl Object {
                     ));
                 }
                 
-                Ok(Object::new(
-                    TypeCode::Int,
-                    Value::Int(lhs % rhs)
-                ))
+                match lhs.checked_rem(*rhs) {
+                    Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                    None => Err(overflow_error("take the remainder of", *lhs, *rhs)),
+                }
             },
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
                 if *rhs == 0.0 {
@@ -444,6 +650,8 @@ impl Object {
     }   
 
     
+    /// Compare the objects by value, except functions which are compared by identity.
+    /// Objects of different types are never equal, except Ints and Floats with the same value
     pub fn eq(lhs: &Object, rhs: &Object) -> bool {
         match (lhs, rhs) {
 
@@ -455,12 +663,13 @@ impl Object {
                 lhs == rhs
             },
 
+            // Converting the Int to a Float could round it, which would make equality not transitive
             (Object { type_code: TypeCode::Float, value: Value::Float(lhs), ..}, Object { type_code: TypeCode::Int, value: Value::Int(rhs), .. }) => {
-                lhs == &(*rhs as f64)
+                exact_int(*lhs) == Some(*rhs)
             },
 
             (Object { type_code: TypeCode::Int, value: Value::Int(lhs), .. }, Object { type_code: TypeCode::Float, value: Value::Float(rhs), .. }) => {
-                &(*lhs as f64) == rhs
+                Some(*lhs) == exact_int(*rhs)
             },
 
             (Object { type_code: TypeCode::String, value: Value::String(lhs), .. }, Object { type_code: TypeCode::String, value: Value::String(rhs), .. }) => {
@@ -475,6 +684,20 @@ impl Object {
                 true
             },
 
+            // Lists are equal if their elements are equal, in the same order
+            (Object { type_code: TypeCode::List, value: Value::List(lhs), .. }, Object { type_code: TypeCode::List, value: Value::List(rhs), .. }) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter()).all(|(lhs, rhs)| Object::eq(lhs, rhs))
+            },
+
+            // Functions are only equal to the functions created by the same declaration with the same captured variables
+            (Object { type_code: TypeCode::Function, value: Value::Function(lhs), .. }, Object { type_code: TypeCode::Function, value: Value::Function(rhs), .. }) => {
+                std::ptr::eq(lhs.code_object, rhs.code_object) && lhs.captures == rhs.captures
+            },
+
+            (Object { type_code: TypeCode::Function, value: Value::Native(lhs), .. }, Object { type_code: TypeCode::Function, value: Value::Native(rhs), .. }) => {
+                std::ptr::fn_addr_eq(lhs.function, rhs.function)
+            },
+
             _ => false,
         }
     }
@@ -492,53 +715,34 @@ impl Object {
     }
 
 
-    pub fn and(a: &Object, b: &Object) -> OpResult {
-        match a.to_bool() {
-            Ok(a) => {
-                if a {
-                    match b.to_bool() {
-                        Ok(b) => Ok(Object::new(TypeCode::Bool, Value::Bool(a && b))),
-                        Err(e) => Err(e),
-                    }
-                } else {
-                    Ok(Object::new(TypeCode::Bool, Value::Bool(false)))
-                }
-            },
-            Err(e) => Err(e),
-        }
-    }
-
-
-    pub fn or(a: &Object, b: &Object) -> OpResult {
-        match a.to_bool() {
-            Ok(a) => {
-                if !a {
-                    match b.to_bool() {
-                        Ok(b) => Ok(Object::new(TypeCode::Bool, Value::Bool(a || b))),
-                        Err(e) => Err(e),
-                    }
-                } else {
-                    Ok(Object::new(TypeCode::Bool, Value::Bool(true)))
-                }
-            },
-            Err(e) => Err(e),
-        }
-    }
-
-
-    pub fn greater(a: &Object, b: &Object) -> OpResult {
+    /// Order the objects, numbers by value and Strings and Lists lexicographically.
+    /// Return None if the objects are unordered, which happens when a Float is NaN.
+    /// Raise a TypeError if they can't be compared, for example a String and a number
+    pub fn compare(a: &Object, b: &Object) -> Result<Option<Ordering>, RuntimeError> {
         match (a, b) {
             (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a > b)))
+                Ok(Some(a.cmp(b)))
             },
             (Object { type_code: TypeCode::Float, value: Value::Float(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a > b)))
+                Ok(a.partial_cmp(b))
             },
             (Object { type_code: TypeCode::Float, value: Value::Float(a), ..}, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a > *b as f64)))
+                Ok(compare_int_float(*b, *a).map(Ordering::reverse))
             },
             (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a as f64 > *b)))
+                Ok(compare_int_float(*a, *b))
+            },
+            (Object { type_code: TypeCode::String, value: Value::String(a), .. }, Object { type_code: TypeCode::String, value: Value::String(b), .. }) => {
+                Ok(Some(a.cmp(b)))
+            },
+            (Object { type_code: TypeCode::List, value: Value::List(a), .. }, Object { type_code: TypeCode::List, value: Value::List(b), .. }) => {
+                // The first different elements decide, otherwise the shorter list comes first
+                for (a, b) in a.iter().zip(b.iter()) {
+                    if !Object::eq(a, b) {
+                        return Object::compare(a, b);
+                    }
+                }
+                Ok(Some(a.len().cmp(&b.len())))
             },
             _ => Err(RuntimeError::with_message(
                 ErrorCode::TypeError,
@@ -548,71 +752,126 @@ impl Object {
     }
 
 
+    /// Compare the objects and return whether their ordering is accepted. Unordered objects are never accepted
+    fn compare_with(a: &Object, b: &Object, accept: fn(Ordering) -> bool) -> OpResult {
+        let accepted = Object::compare(a, b)?.is_some_and(accept);
+        Ok(Object::new(TypeCode::Bool, Value::Bool(accepted)))
+    }
+
+
+    pub fn greater(a: &Object, b: &Object) -> OpResult {
+        Object::compare_with(a, b, Ordering::is_gt)
+    }
+
+
     pub fn greater_eq(a: &Object, b: &Object) -> OpResult {
-        match (a, b) {
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a >= b)))
-            },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a >= b)))
-            },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), ..}, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a >= *b as f64)))
-            },
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a as f64 >= *b)))
-            },
-            _ => Err(RuntimeError::with_message(
-                ErrorCode::TypeError,
-                format!("Cannot compare {} and {}", a.type_code.name(), b.type_code.name())
-            )),
-        }
+        Object::compare_with(a, b, Ordering::is_ge)
     }
 
 
     pub fn less(a: &Object, b: &Object) -> OpResult {
-        match (a, b) {
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a < b)))
-            },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a < b)))
-            },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), ..}, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a < *b as f64)))
-            },
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool((*a as f64) < *b)))
-            },
-            _ => Err(RuntimeError::with_message(
-                ErrorCode::TypeError,
-                format!("Cannot compare {} and {}", a.type_code.name(), b.type_code.name())
-            )),
-        }
+        Object::compare_with(a, b, Ordering::is_lt)
     }
 
 
     pub fn less_eq(a: &Object, b: &Object) -> OpResult {
-        match (a, b) {
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a <= b)))
-            },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(a <= b)))
+        Object::compare_with(a, b, Ordering::is_le)
+    }
+
+
+    /// Return the hash of the object, which is the same for all the objects equal to it.
+    /// Raise a TypeError for the objects that can't be hashed, such as modules
+    pub fn hash(obj: &Object) -> Result<u64, RuntimeError> {
+        let mut hasher = DefaultHasher::new();
+        Object::hash_into(obj, &mut hasher)?;
+        Ok(hasher.finish())
+    }
+
+
+    fn hash_into(obj: &Object, hasher: &mut DefaultHasher) -> Result<(), RuntimeError> {
+        // The type code is hashed first, so that equal values of different types don't collide
+        match &obj.value {
+            // Ints are equal to the Floats with the same value, so those Floats are hashed as Ints.
+            // This includes -0.0, which is equal to 0.0 but has different bits
+            Value::Int(value) => Object::hash_int(*value, hasher),
+            Value::Float(value) => match exact_int(*value) {
+                Some(value) => Object::hash_int(value, hasher),
+                None => {
+                    (TypeCode::Float as u8).hash(hasher);
+                    value.to_bits().hash(hasher);
+                },
+            },
+            Value::String(value) => {
+                (TypeCode::String as u8).hash(hasher);
+                value.hash(hasher);
+            },
+            Value::Bool(value) => {
+                (TypeCode::Bool as u8).hash(hasher);
+                value.hash(hasher);
+            },
+            Value::List(elements) => {
+                (TypeCode::List as u8).hash(hasher);
+                elements.len().hash(hasher);
+                for element in elements {
+                    Object::hash_into(element, hasher)?;
+                }
             },
-            (Object { type_code: TypeCode::Float, value: Value::Float(a), ..}, Object { type_code: TypeCode::Int, value: Value::Int(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a <= *b as f64)))
+            Value::None => (TypeCode::None as u8).hash(hasher),
+            Value::Function(function) => {
+                (TypeCode::Function as u8).hash(hasher);
+                (function.code_object as usize).hash(hasher);
             },
-            (Object { type_code: TypeCode::Int, value: Value::Int(a), .. }, Object { type_code: TypeCode::Float, value: Value::Float(b), .. }) => {
-                Ok(Object::new(TypeCode::Bool, Value::Bool(*a as f64 <= *b)))
+            Value::Native(native) => {
+                (TypeCode::Function as u8).hash(hasher);
+                (native.function as usize).hash(hasher);
             },
-            _ => Err(RuntimeError::with_message(
+            Value::Ref(_) | Value::Module(_) | Value::Iterator(_) => return Err(RuntimeError::with_message(
                 ErrorCode::TypeError,
-                format!("Cannot compare {} and {}", a.type_code.name(), b.type_code.name())
+                format!("Cannot hash {}", obj.type_code.name())
             )),
         }
+        Ok(())
+    }
+
+
+    fn hash_int(value: i64, hasher: &mut DefaultHasher) {
+        (TypeCode::Int as u8).hash(hasher);
+        value.hash(hasher);
+    }
+
+
+}
+
+
+/// The smallest Float above i64::MAX, which is 2^63. i64::MIN is exactly -2^63
+const INT_RANGE_END: f64 = 9223372036854775808.0;
+
+
+/// Return the Int equal to the Float, if it's integral and in the range of an Int
+fn exact_int(value: f64) -> Option<i64> {
+    if value.fract() == 0.0 && (-INT_RANGE_END .. INT_RANGE_END).contains(&value) {
+        Some(value as i64)
+    } else {
+        None
     }
+}
 
 
+/// Order an Int and a Float exactly, without rounding the Int to a Float.
+/// Return None if the Float is NaN
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        None
+    } else if float >= INT_RANGE_END {
+        Some(Ordering::Less)
+    } else if float < -INT_RANGE_END {
+        Some(Ordering::Greater)
+    } else {
+        // The integral part of the Float is an Int, and its fractional part decides when they're equal
+        match int.cmp(&(float.trunc() as i64)) {
+            Ordering::Equal => 0.0.partial_cmp(&float.fract()),
+            ordering => Some(ordering),
+        }
+    }
 }
 
@@ -2,18 +2,29 @@ mod utils;
 mod compiler;
 mod lang;
 mod runtime;
+mod stdlib;
 
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use compiler::bytecode_file::BYTECODE_EXTENSION;
+use compiler::codegen::CompileOptions;
+use compiler::jit::Jit;
 
 
 #[derive(Parser)]
-#[clap(author, about, version)]
+#[clap(author, about, version, trailing_var_arg = true, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
 
-    /// The input file to execute
-    #[clap(value_parser)]
-    pub input_file: PathBuf,
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    /// The input file to execute, either a source file or a compiled .wsc file
+    #[clap(value_parser, required = true)]
+    pub input_file: Option<PathBuf>,
 
     /// Verbose mode
     #[clap(short, long, action)]
@@ -23,23 +34,227 @@ struct Cli {
     #[clap(short, long, action)]
     pub quiet: bool,
 
+    /// Additional directory to search for imported modules, which can only come from the script's directory and these. Can be repeated
+    #[clap(short = 'I', long = "module-path", value_parser)]
+    pub module_path: Vec<PathBuf>,
+
+    /// Compile each function the first time it's called instead of compiling the whole program ahead of time
+    #[clap(long, action)]
+    pub lazy: bool,
+
+    /// Warn when a declaration shadows a variable of the same or an enclosing scope
+    #[clap(long = "warn-shadowing", action)]
+    pub warn_shadowing: bool,
+
+    /// Allow the script to read files. Restrict to the given comma-separated paths if any
+    #[clap(long = "allow-read", value_parser, num_args = 0.., value_delimiter = ',', require_equals = true)]
+    pub allow_read: Option<Vec<PathBuf>>,
+
+    /// Allow the script to write files. Restrict to the given comma-separated paths if any
+    #[clap(long = "allow-write", value_parser, num_args = 0.., value_delimiter = ',', require_equals = true)]
+    pub allow_write: Option<Vec<PathBuf>>,
+
+    /// Allow the script to read environment variables
+    #[clap(long = "allow-env", action)]
+    pub allow_env: bool,
+
+    /// Print the disassembled byte code of the program before running it
+    #[clap(long = "dump-bytecode", action)]
+    pub dump_bytecode: bool,
+
+    /// Print the tokens of the input file with their lines, then exit without running it
+    #[clap(long = "dump-tokens", action)]
+    pub dump_tokens: bool,
+
+    /// Print the syntax tree of the input file with the line of each node, then exit without running it
+    #[clap(long = "dump-ast", action)]
+    pub dump_ast: bool,
+
+    /// The output format of --dump-tokens and --dump-ast
+    #[clap(long = "dump-format", value_enum, default_value_t = DumpFormat::Text)]
+    pub dump_format: DumpFormat,
+
+    /// Print every executed instruction with its operands, the object stack and the scope depth
+    #[clap(long, action)]
+    pub trace: bool,
+
+    /// Only trace the instructions compiled from the given source lines, such as 10-20
+    #[clap(long = "trace-lines", value_parser = runtime::tracer::parse_line_range, requires = "trace")]
+    pub trace_lines: Option<RangeInclusive<usize>>,
+
+    /// Only trace the given function, or the top-level code of the given module. Can be repeated
+    #[clap(long = "trace-function", value_parser, requires = "trace")]
+    pub trace_function: Vec<String>,
+
+    /// Write the trace to the given file instead of the standard output
+    #[clap(long = "trace-output", value_parser, requires = "trace")]
+    pub trace_output: Option<PathBuf>,
+
+    /// The maximum depth of nested function calls, above which a StackOverflow error is raised
+    #[clap(long = "max-call-depth", value_parser, default_value_t = runtime::vm::DEFAULT_MAX_CALL_DEPTH)]
+    pub max_call_depth: usize,
+
+    /// Arguments passed to the script
+    #[clap(value_parser, allow_hyphen_values = true)]
+    pub script_args: Vec<String>,
+
 }
 
 
-fn main() {
-    
-    let args = Cli::parse();
+#[derive(Subcommand)]
+enum Command {
+
+    /// Compile a source file into a .wsc file that can be run without compiling it again
+    Compile {
+
+        /// The source file to compile
+        #[clap(value_parser)]
+        input_file: PathBuf,
+
+        /// The compiled file to write. Defaults to the input file with the .wsc extension
+        #[clap(short, long, value_parser)]
+        output: Option<PathBuf>,
+
+        /// Additional directory to search for imported modules, which can only come from the script's directory and these. Can be repeated
+        #[clap(short = 'I', long = "module-path", value_parser)]
+        module_path: Vec<PathBuf>,
+
+        /// Warn when a declaration shadows a variable of the same or an enclosing scope
+        #[clap(long = "warn-shadowing", action)]
+        warn_shadowing: bool,
 
-    let source = utils::files::load_file(&args.input_file);
+    },
+
+    /// Print the disassembled byte code of a source file or a compiled .wsc file
+    Disasm {
+
+        /// The source file or compiled file to disassemble
+        #[clap(value_parser)]
+        input_file: PathBuf,
+
+        /// Additional directory to search for imported modules, which can only come from the script's directory and these. Can be repeated
+        #[clap(short = 'I', long = "module-path", value_parser)]
+        module_path: Vec<PathBuf>,
+
+    },
+
+}
+
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    Text,
+    Json,
+}
+
+
+/// Print the tokens and/or the syntax tree of the source file, as requested by the dump flags
+fn dump_source_file(input_file: &Path, args: &Cli) {
+    let source = utils::files::load_file(&input_file.to_path_buf());
+
+    let tokens = compiler::tokenizer::tokenize(&source).consume_tokens();
+
+    if args.dump_tokens {
+        match args.dump_format {
+            DumpFormat::Text => print!("{}", compiler::dump::tokens_to_text(&tokens)),
+            DumpFormat::Json => print!("{}", compiler::dump::tokens_to_json(&tokens)),
+        }
+    }
+
+    if args.dump_ast {
+        let syntax_tree = compiler::syntax_tree::SyntaxTree::from_tokens(&tokens, &source);
+        match args.dump_format {
+            DumpFormat::Text => print!("{}", compiler::dump::syntax_tree_to_text(&syntax_tree)),
+            DumpFormat::Json => print!("{}", compiler::dump::syntax_tree_to_json(&syntax_tree)),
+        }
+    }
+}
+
+
+/// Load, parse and compile the source file and all the modules it imports
+fn compile_source_file(input_file: &Path, module_path: Vec<PathBuf>, options: CompileOptions) -> Jit {
+    let source = utils::files::load_file(&input_file.to_path_buf());
 
     let mut tokens = compiler::tokenizer::tokenize(&source);
 
     let syntax_tree = compiler::syntax_tree::SyntaxTree::from_tokens(&tokens.consume_tokens(), &source);
+
+    Jit::from_syntax_tree(syntax_tree, &source, input_file, module_path, options)
+}
+
+
+/// Load a compiled .wsc file, or compile a source file
+fn load_program(input_file: &Path, module_path: Vec<PathBuf>, options: CompileOptions) -> Jit {
+    if input_file.extension().is_some_and(|extension| extension == BYTECODE_EXTENSION) {
+        Jit::from_bytecode_file(input_file)
+    } else {
+        compile_source_file(input_file, module_path, options)
+    }
+}
+
+
+fn main() {
     
-    let mut jit = compiler::jit::Jit::from_syntax_tree(syntax_tree, &source);
+    let args = Cli::parse();
+
+    match args.command {
+        Some(Command::Compile { input_file, output, module_path, warn_shadowing }) => {
+            // Compiled files can't contain uncompiled functions, so the program is never compiled lazily
+            let jit = compile_source_file(&input_file, module_path, CompileOptions { lazy: false, warn_shadowing });
+            let output = output.unwrap_or_else(|| input_file.with_extension(BYTECODE_EXTENSION));
+            jit.save_bytecode_file(&output);
+            return;
+        },
+        Some(Command::Disasm { input_file, module_path }) => {
+            let jit = load_program(&input_file, module_path, CompileOptions::default());
+            print!("{}", compiler::disassembler::disassemble(&jit));
+            return;
+        },
+        None => {},
+    }
+
+    let input_file = args.input_file.clone().expect("The input file is required without a subcommand");
+
+    if args.dump_tokens || args.dump_ast {
+        dump_source_file(&input_file, &args);
+        return;
+    }
+
+    let options = CompileOptions { lazy: args.lazy, warn_shadowing: args.warn_shadowing };
+    let mut jit = load_program(&input_file, args.module_path, options);
+
+    if args.dump_bytecode {
+        print!("{}", compiler::disassembler::disassemble(&jit));
+    }
 
-    let mut vm = runtime::vm::Vm::new(args.verbose);
-    let status = vm.execute(&mut jit, &source, args.verbose);
+    let capabilities = runtime::capabilities::Capabilities {
+        read: runtime::capabilities::PathPermission::from_flag(args.allow_read),
+        write: runtime::capabilities::PathPermission::from_flag(args.allow_write),
+        env: args.allow_env,
+    };
+
+    let tracer = if args.trace {
+        let filter = runtime::tracer::TraceFilter {
+            lines: args.trace_lines,
+            functions: args.trace_function,
+        };
+        let output: Box<dyn Write> = match &args.trace_output {
+            Some(path) => Box::new(LineWriter::new(
+                File::create(path).unwrap_or_else(|error| panic!("Could not create file: {}: {:?}", path.display(), error))
+            )),
+            None => Box::new(std::io::stdout()),
+        };
+        Some(runtime::tracer::Tracer::new(filter, output))
+    } else {
+        None
+    };
+
+    let mut vm = runtime::vm::Vm::new(args.verbose, capabilities, args.script_args, tracer, args.max_call_depth);
+    let status = vm.execute(&mut jit);
+
+    if !matches!(status.code, runtime::error_codes::ErrorCode::Ok) {
+        status.raise();
+    }
 
     if !args.quiet {
         println!("Program finished with exit code {} ({})", status.code, status.code.name());
@@ -0,0 +1,125 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::error_codes::{ErrorCode, RuntimeError};
+
+
+/// The set of paths a script is allowed to access
+#[derive(Debug, Clone)]
+pub enum PathPermission {
+    /// No path can be accessed
+    Denied,
+    /// Any path can be accessed
+    All,
+    /// Only paths inside the given directories (or the given files themselves) can be accessed.
+    /// The paths are stored in absolute form
+    Only(Vec<PathBuf>),
+}
+
+
+impl PathPermission {
+
+    /// Create a path permission from a command line flag.
+    /// A missing flag denies everything, a flag without values allows everything.
+    pub fn from_flag(flag: Option<Vec<PathBuf>>) -> PathPermission {
+        match flag {
+            None => PathPermission::Denied,
+            Some(paths) if paths.is_empty() => PathPermission::All,
+            Some(paths) => PathPermission::Only(
+                paths.iter().map(|path| absolute_path(path)).collect()
+            ),
+        }
+    }
+
+
+    pub fn allows(&self, path: &Path) -> bool {
+        match self {
+            PathPermission::Denied => false,
+            PathPermission::All => true,
+            PathPermission::Only(allowed) => {
+                let path = absolute_path(path);
+                allowed.iter().any(|allowed| path.starts_with(allowed))
+            },
+        }
+    }
+
+}
+
+
+/// The I/O operations a script is allowed to perform.
+/// Everything is denied by default because scripts may be untrusted.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub read: PathPermission,
+    pub write: PathPermission,
+    pub env: bool,
+}
+
+
+impl Capabilities {
+
+    /// Raise a PermissionDenied error if the path cannot be read
+    pub fn check_read(&self, function: &str, path: &Path) -> Result<(), RuntimeError> {
+        if self.read.allows(path) {
+            Ok(())
+        } else {
+            Err(permission_denied(function, &format!("read access to \"{}\"", path.display()), "--allow-read"))
+        }
+    }
+
+
+    /// Raise a PermissionDenied error if the path cannot be written
+    pub fn check_write(&self, function: &str, path: &Path) -> Result<(), RuntimeError> {
+        if self.write.allows(path) {
+            Ok(())
+        } else {
+            Err(permission_denied(function, &format!("write access to \"{}\"", path.display()), "--allow-write"))
+        }
+    }
+
+
+    /// Raise a PermissionDenied error if the environment cannot be read
+    pub fn check_env(&self, function: &str) -> Result<(), RuntimeError> {
+        if self.env {
+            Ok(())
+        } else {
+            Err(permission_denied(function, "access to the environment", "--allow-env"))
+        }
+    }
+
+}
+
+
+fn permission_denied(function: &str, access: &str, flag: &str) -> RuntimeError {
+    RuntimeError::with_message(
+        ErrorCode::PermissionDenied,
+        format!("{}() requires {}, run again with {} to allow it", function, access, flag)
+    )
+}
+
+
+/// Make the path absolute and resolve symbolic links and "..", so that a path cannot escape the allowed directories.
+/// Components that don't exist yet cannot be links, so they are resolved lexically.
+fn absolute_path(path: &Path) -> PathBuf {
+    let mut resolved = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .unwrap_or_default();
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {},
+            Component::ParentDir => {
+                resolved.pop();
+            },
+            Component::Normal(name) => {
+                resolved.push(name);
+                // Resolve links as soon as possible, so that ".." pops the real parent directory
+                if let Ok(real) = resolved.canonicalize() {
+                    resolved = real;
+                }
+            },
+        }
+    }
+
+    resolved
+}
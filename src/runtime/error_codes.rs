@@ -25,14 +25,6 @@ impl RuntimeError {
     }
 
 
-    pub fn new(code: ErrorCode, message: Option<String>) -> Self {
-        Self {
-            code,
-            message,
-        }
-    }
-
-    
     pub fn raise(&self) -> ! {
         if let Some(message) = &self.message {
             eprintln!("{}: {}", self.code.name(), message);
@@ -53,6 +45,14 @@ pub enum ErrorCode {
     InvalidMemoryAccess,
     UndeclaredSymbol,
     ReturnOutsideFunction,
+    AttributeError,
+    OverflowError,
+    ValueError,
+    PermissionDenied,
+    IOError,
+    IndexError,
+    /// Too many nested function calls
+    StackOverflow,
 }
 
 
@@ -73,6 +73,13 @@ impl ErrorCode {
             ErrorCode::InvalidMemoryAccess => "InvalidMemoryAccess",
             ErrorCode::UndeclaredSymbol => "UndeclaredSymbol",
             ErrorCode::ReturnOutsideFunction => "ReturnOutsideFunction",
+            ErrorCode::AttributeError => "AttributeError",
+            ErrorCode::OverflowError => "OverflowError",
+            ErrorCode::ValueError => "ValueError",
+            ErrorCode::PermissionDenied => "PermissionDenied",
+            ErrorCode::IOError => "IOError",
+            ErrorCode::IndexError => "IndexError",
+            ErrorCode::StackOverflow => "StackOverflow",
         }
     }
 
@@ -25,23 +25,44 @@ impl ScopeStack {
     }
 
 
-    pub fn get_heap_address_from_global_id(&self, index: usize) -> Address {
-        // The index should always be valid
-        self.heap_index[index]  
+    /// Return the heap address of the global with the given id, if it was already allocated.
+    /// The global scope is the first one, so a global that isn't allocated yet can't be confused with a local of a later scope
+    pub fn get_heap_address_from_global_id(&self, global_id: usize) -> Option<Address> {
+        let globals_end = self.heap_index_offsets.get(1).copied().unwrap_or(self.heap_index.len());
+
+        if global_id < globals_end {
+            Some(self.heap_index[global_id])
+        } else {
+            None
+        }
     }
 
 
-    pub fn get_heap_address_from_local_id(&self, local_id: usize) -> Address {
-        // The index should always be valid
-        self.heap_index[self.stack_offsets.last().unwrap() + local_id]
+    /// Return the heap address of the local with the given id in the current scope, if it was already allocated
+    pub fn get_heap_address_from_local_id(&self, local_id: usize) -> Option<Address> {
+        let scope_start = self.heap_index_offsets.last().unwrap();
+        self.heap_index.get(scope_start + local_id).copied()
     }
 
 
-    pub fn get_heap_address_from_offsets(&self, local_id: usize, scope_offset: usize) -> Address {
-        // The index should always be valid
-        let current_scope_offset = self.stack_offsets.last().unwrap();
-        let target_scope_offset = self.stack_offsets[self.stack_offsets.len() - 1 - scope_offset];
-        self.heap_index[target_scope_offset - current_scope_offset + local_id]
+    /// Return the heap address of the local with the given id in the scope scope_offset levels above the current one,
+    /// if it was already allocated
+    pub fn get_heap_address_from_offsets(&self, local_id: usize, scope_offset: usize) -> Option<Address> {
+        let scope_index = self.heap_index_offsets.len().checked_sub(1 + scope_offset)?;
+        let scope_start = self.heap_index_offsets[scope_index];
+        let scope_end = self.heap_index_offsets.get(scope_index + 1).copied().unwrap_or(self.heap_index.len());
+
+        if scope_start + local_id < scope_end {
+            Some(self.heap_index[scope_start + local_id])
+        } else {
+            None
+        }
+    }
+
+
+    /// Return the objects on the object stack, from the bottom to the top
+    pub fn objects(&self) -> &[Object] {
+        &self.stack
     }
 
 
@@ -92,8 +113,24 @@ impl ScopeStack {
     }
 
 
-    pub fn get_last_stack_index(&self) -> usize {
-        self.stack.len() - 1
+    /// Return the number of scopes currently on the stack
+    pub fn scope_depth(&self) -> usize {
+        self.heap_index_offsets.len()
+    }
+
+
+    /// Pop scopes until only the given number of scopes is left
+    pub fn pop_scopes_to(&mut self, depth: usize) {
+        while self.heap_index_offsets.len() > depth {
+            self.pop_scope();
+        }
+    }
+
+
+    /// Consume the scope stack and return the heap addresses of the objects it indexes.
+    /// Called after a module's top-level code has run, when only its global scope is left.
+    pub fn into_heap_index(self) -> Vec<Address> {
+        self.heap_index
     }
 
 }
@@ -102,8 +139,9 @@ impl ScopeStack {
 pub struct Heap {
     
     // TODO: free address table
-    // TODO: add pages if necessary to avoid resizing
-    objects: Vec<Object>,
+    /// Objects are boxed so that references to them stay valid when the heap grows
+    #[allow(clippy::vec_box)] // Refs are raw pointers to the boxed objects, which moving them would invalidate
+    objects: Vec<Box<Object>>,
 
 }
 
@@ -126,7 +164,7 @@ impl Heap {
     pub fn get_ref(&mut self, address: Address) -> OpResult {
         if let Some(obj) = self.objects.get_mut(address) {
             obj.inc_ref_count();
-            Ok(Object::new_ref(obj as *mut Object))
+            Ok(Object::new_ref(obj.as_mut() as *mut Object))
         } else {
             Err(RuntimeError::with_message(
                 ErrorCode::InvalidMemoryAccess,
@@ -141,7 +179,15 @@ impl Heap {
     pub fn allocate(&mut self) -> Address {
         // TODO: Garbage collection and free address table
         let address = self.objects.len();
-        self.objects.push(Object::none());
+        self.objects.push(Box::new(Object::none()));
+        address
+    }
+
+
+    /// Allocate space on the heap for the given object.
+    pub fn allocate_object(&mut self, obj: Object) -> Address {
+        let address = self.objects.len();
+        self.objects.push(Box::new(obj));
         address
     }
 
@@ -1,7 +1,9 @@
+pub mod capabilities;
 pub mod error_codes;
 pub mod memory;
 pub mod op_code;
+pub mod tracer;
+pub mod verifier;
 pub mod vm;
-mod execution_queue;
 
 
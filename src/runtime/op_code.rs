@@ -1,30 +1,44 @@
+use crate::utils::byte_code::{ID_SIZE, PTR_SIZE, JUMP_OFFSET_SIZE};
 
 
 // Max is 256
-const OP_CODE_COUNT: usize = 30;
+pub const OP_CODE_COUNT: usize = 46;
 
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum OpCode {
 
     /// Do nothing.
+    // Never emitted by the compiler, but byte code may still contain it
+    #[allow(dead_code)]
     Nop,
     /// Push to the object stack a new local reference to an object in the heap.
     LoadLocalRef,
     /// Push to the object stack a new global reference to an object in the heap.
+    /// 
+    /// Globals are identified by the id of the module that declares them and their global id.
     LoadGlobalRef,
     /// Push to the object stack a new reference to an object in the heap with a certain offset from the current scope start.
     LoadOffsetRef,
-    /// Push a new object to the object stack constructed from constant byte code.
+    /// Push a copy of the constant with the given index in the program's constant pool to the object stack.
     LoadConst,
-    /// TODO
+    /// Leave the current scope.
+    /// 
+    /// Free the scope's heap index entries and remove its objects from the object stack.
     PopScope,
-    /// Call a callable object. Performs runtime checks to ensure that the object is callable.
+    /// Consume the n argument objects and the callable object below them, where n is the 1-byte argument count.
+    /// 
+    /// Push the return value of the call to the object stack.
     /// 
     /// If the object is not callable, a TypeError is raised.
     CallFunction,
-    /// Push a new function object to the object stack.
+    /// Consume the references to the variables the function captures and push a new function object to the object stack.
+    /// The function is the one with the given index among the functions declared in the current code object.
+    /// The captured variables are shared with the enclosing scopes, and the first one is the deepest on the stack.
     MakeFunction,
+    /// Consume the TOS reference and the value below it.
+    /// 
+    /// Store a copy of the value into the referenced object.
     StoreTop,
     /// Consume the two top-most objects on the object stack.
     ///
@@ -68,7 +82,15 @@ pub enum OpCode {
     /// 
     /// Push the resulting object to the object stack.  
     Not,
+    /// Consume the TOS object and push an iterator over it.
+    /// 
+    /// Lists are iterated by element, strings by character and integers from 0 up to, but excluding, the integer.
     GetIter,
+    /// Consume the index and the iterable object below it.
+    /// 
+    /// Push a copy of the list element, or the character string, at that index.
+    /// 
+    /// Raise an IndexError if the index is out of range.
     Subscript,
     /// Consume the TOS and return from the function call.
     /// 
@@ -76,24 +98,13 @@ pub enum OpCode {
     ReturnValue,
     /// Return from a function call without a value.
     Return,
+    /// Enter a new scope.
     PushScope,
     /// Consume the two top-most objects on the object stack.
     /// 
     /// Push a new boolean object to the object stack.
     /// 
     /// Raise an error if the operation cannot be performed.
-    And,
-    /// Consume the two top-most objects on the object stack.
-    /// 
-    /// Push a new boolean object to the object stack.
-    /// 
-    /// Raise an error if the operation cannot be performed.
-    Or,
-    /// Consume the two top-most objects on the object stack.
-    /// 
-    /// Push a new boolean object to the object stack.
-    /// 
-    /// Raise an error if the operation cannot be performed.
     Greater,
     /// Consume the two top-most objects on the object stack.
     /// 
@@ -125,11 +136,91 @@ pub enum OpCode {
     /// 
     /// Push the list object to the top of the object stack.
     MakeList,
+    /// Push the namespace object of the module with the given id to the object stack.
+    /// 
+    /// The module's top-level code is executed the first time the module is imported.
+    ImportModule,
+    /// Consume the TOS object and push a reference to its member whose name is the String constant with the given index.
+    /// 
+    /// Raise an AttributeError if the object has no such member.
+    GetMember,
+    /// Consume the TOS object and discard it.
+    Pop,
+    /// Move the program counter by the signed 4-byte offset.
+    Jump,
+    /// Consume the TOS object and move the program counter by the signed 4-byte offset if the object is false.
+    JumpIfFalse,
+    /// If the TOS object is false, leave it on the object stack and move the program counter by the signed 4-byte offset.
+    /// Otherwise, consume it.
+    /// 
+    /// Used by `&&`, which evaluates to its first false operand, or to its last operand if all are true,
+    /// so the right operand is only evaluated if the left one is true.
+    JumpIfFalseOrPop,
+    /// If the TOS object is true, leave it on the object stack and move the program counter by the signed 4-byte offset.
+    /// Otherwise, consume it.
+    /// 
+    /// Used by `||`, which evaluates to its first true operand, or to its last operand if all are false,
+    /// so the right operand is only evaluated if the left one is false.
+    JumpIfTrueOrPop,
+    /// Advance the TOS iterator and push its next element.
+    /// 
+    /// If the iterator is exhausted, leave it on the object stack and move the program counter by the signed 4-byte offset.
+    ForIter,
+    /// Consume the TOS object and push its arithmetic negation.
+    /// 
+    /// Raise a TypeError if the object is not a number, or an OverflowError if the negated integer doesn't fit in an Int.
+    Negate,
+    /// Consume the keyword arguments, the list of positional arguments and the callable object below them.
+    /// Each keyword argument is a name string followed by its value, and their count is the pointer-sized operand.
+    /// 
+    /// Push the return value of the call to the object stack.
+    /// 
+    /// Used for calls with spread or keyword arguments, or with too many arguments to count in a byte.
+    /// Raise a TypeError if the object is not callable or the arguments don't match its parameters.
+    CallFunctionEx,
+    /// Consume the TOS list and append its elements to the list below it.
+    /// 
+    /// Raise a TypeError if the TOS object is not a list.
+    ExtendList,
+    /// Push true if the caller didn't pass the parameter with the 1-byte index, false otherwise.
+    /// 
+    /// Used to initialize the parameters with a default value that weren't passed.
+    MissingArgument,
+    /// Consume the n argument objects and the callable object below them, where n is the 1-byte argument count.
+    /// 
+    /// Return the return value of the call from the current function.
+    /// The called function reuses the frame and the scopes of the current function, so tail recursion runs in constant depth.
+    /// 
+    /// If the object is not callable, a TypeError is raised.
+    TailCall,
+    /// Consume the TOS list and push its n elements in reverse order, so that the first element is on top, where n is the pointer-sized operand.
+    /// 
+    /// Raise a ValueError if the list doesn't have exactly n elements, or a TypeError if the object is not a list.
+    UnpackList,
+    /// Consume the TOS and push true if it's a list of n elements, false otherwise, where n is the pointer-sized operand.
+    /// 
+    /// Used to test the list patterns of match statements.
+    MatchList,
+    /// Consume the n indices, the reference to a list below them and the value below it, where n is the pointer-sized operand.
+    /// 
+    /// Store a copy of the value into the element found by indexing the list with each index in turn.
+    /// The element is only looked up when storing, so that evaluating the indices can't invalidate it.
+    /// 
+    /// Raise a TypeError if an indexed object is not a list, or an IndexError if an index is out of range.
+    StoreSubscript,
+    /// Consume the keyword arguments, the list of positional arguments and the callable object below them, like CallFunctionEx.
+    /// 
+    /// Return the return value of the call from the current function, reusing its frame like TailCall.
+    /// 
+    /// Raise a TypeError if the object is not callable or the arguments don't match its parameters.
+    TailCallEx,
+    /// Push a reference to the captured variable with the pointer-sized index, among the captures of the current function.
+    LoadCapture,
 
 }
 
 
-const OP_CODE_NAMES: [&'static str; OP_CODE_COUNT] = [
+const OP_CODE_NAMES: [&str; OP_CODE_COUNT] = [
     "Nop",
     "LoadLocalRef",
     "LoadGlobalRef",
@@ -152,14 +243,30 @@ const OP_CODE_NAMES: [&'static str; OP_CODE_COUNT] = [
     "ReturnValue",
     "Return",
     "PushScope",
-    "And",
-    "Or",
     "Greater",
     "GreaterEqual",
     "Less",
     "LessEqual",
     "Allocate",
     "MakeList",
+    "ImportModule",
+    "GetMember",
+    "Pop",
+    "Jump",
+    "JumpIfFalse",
+    "JumpIfFalseOrPop",
+    "JumpIfTrueOrPop",
+    "ForIter",
+    "Negate",
+    "CallFunctionEx",
+    "ExtendList",
+    "MissingArgument",
+    "TailCall",
+    "UnpackList",
+    "MatchList",
+    "StoreSubscript",
+    "TailCallEx",
+    "LoadCapture",
 ];
 
 
@@ -170,14 +277,54 @@ impl std::fmt::Display for OpCode {
 }
 
 
-impl std::convert::From<u8> for OpCode {
+impl OpCode {
 
-    fn from(value: u8) -> Self {
+    /// Return the op code with the given byte value, if there is one
+    pub fn from_byte(value: u8) -> Option<OpCode> {
         if value < OP_CODE_COUNT as u8 {
-            unsafe { std::mem::transmute(value) }
+            Some(unsafe { std::mem::transmute::<u8, OpCode>(value) })
         } else {
-            panic!("Invalid op code: {}", value);
+            None
         }
     }
+
+
+    /// Return the number of bytes of the instruction's operands, which follow the op code byte
+    pub fn operand_size(&self) -> usize {
+        match self {
+            OpCode::LoadLocalRef => ID_SIZE,
+            OpCode::LoadGlobalRef |
+            OpCode::LoadOffsetRef => 2 * ID_SIZE,
+            OpCode::LoadConst |
+            OpCode::MakeFunction |
+            OpCode::MakeList |
+            OpCode::UnpackList |
+            OpCode::MatchList |
+            OpCode::StoreSubscript |
+            OpCode::CallFunctionEx |
+            OpCode::TailCallEx |
+            OpCode::LoadCapture |
+            OpCode::ImportModule |
+            OpCode::GetMember => PTR_SIZE,
+            OpCode::CallFunction |
+            OpCode::TailCall |
+            OpCode::MissingArgument => 1,
+            OpCode::Jump |
+            OpCode::JumpIfFalse |
+            OpCode::JumpIfFalseOrPop |
+            OpCode::JumpIfTrueOrPop |
+            OpCode::ForIter => JUMP_OFFSET_SIZE,
+            _ => 0,
+        }
+    }
+
+}
+
+
+impl std::convert::From<u8> for OpCode {
+
+    fn from(value: u8) -> Self {
+        OpCode::from_byte(value).unwrap_or_else(|| panic!("Invalid op code: {}", value))
+    }
 }
 
@@ -0,0 +1,89 @@
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+use super::memory::ScopeStack;
+use super::op_code::OpCode;
+use crate::compiler::code_object::CodeObject;
+use crate::compiler::disassembler;
+use crate::compiler::jit::Jit;
+use crate::lang::object::Object;
+
+
+/// Selects the instructions the tracer prints
+#[derive(Default)]
+pub struct TraceFilter {
+    /// Only trace the instructions compiled from these source lines
+    pub lines: Option<RangeInclusive<usize>>,
+    /// Only trace the code of the functions with these names.
+    /// The top-level code of a module is selected by the module's name
+    pub functions: Vec<String>,
+}
+
+
+impl TraceFilter {
+
+    fn accepts(&self, code_object: &CodeObject, line: usize) -> bool {
+        self.lines.as_ref().is_none_or(|lines| lines.contains(&line))
+            && (self.functions.is_empty() || self.functions.iter().any(|name| **name == *code_object.name))
+    }
+
+}
+
+
+/// Prints every instruction executed by the VM, with its decoded operands, the object stack and the scope depth
+pub struct Tracer {
+    filter: TraceFilter,
+    /// Should be line buffered, so that the trace is complete even if the program exits on an error
+    output: Box<dyn Write>,
+}
+
+
+impl Tracer {
+
+    pub fn new(filter: TraceFilter, output: Box<dyn Write>) -> Tracer {
+        Tracer {
+            filter,
+            output,
+        }
+    }
+
+
+    /// Trace the instruction at the given index, before it's executed
+    pub fn trace(&mut self, code_object: &CodeObject, pc: usize, stack: &ScopeStack, jit: &Jit) {
+        let line = code_object.get_line(pc);
+        if !self.filter.accepts(code_object, line) {
+            return;
+        }
+
+        let op_code = OpCode::from(code_object.code()[pc]);
+        let operands = disassembler::describe_operands(op_code, pc + 1, code_object, jit, code_object.module_id);
+        let objects = stack.objects().iter().map(Object::describe).collect::<Vec<String>>().join(", ");
+
+        writeln!(
+            self.output,
+            "{}:{} {} {:>5}  {:<16} {:<24} scopes {} stack [{}]",
+            jit.get_module(code_object.module_id).name, line, code_object.name, pc, op_code.to_string(), operands, stack.scope_depth(), objects
+        ).expect("Could not write the trace");
+    }
+
+}
+
+
+/// Parse a range of source lines such as "10-20", or a single line such as "12"
+pub fn parse_line_range(range: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse_line = |line: &str| line.trim().parse::<usize>().map_err(|_| format!("invalid line number '{}'", line));
+
+    match range.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse_line(start)?, parse_line(end)?);
+            if start > end {
+                return Err(format!("the line range {} is empty", range));
+            }
+            Ok(start ..= end)
+        },
+        None => {
+            let line = parse_line(range)?;
+            Ok(line ..= line)
+        },
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::op_code::OpCode;
+use crate::compiler::code_object::CodeObject;
+use crate::compiler::constant_pool::ConstantPool;
+use crate::lang::object::{Object, TypeCode, Value};
+use crate::utils::byte_code::{self, ByteCode};
+
+
+/// A violation of the byte code invariants the VM relies on
+pub struct VerifyError {
+    /// The name of the code object the error was found in
+    pub code_name: String,
+    /// The index of the offending instruction
+    pub pc: usize,
+    pub message: String,
+}
+
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "VerifyError in {} at instruction {}: {}", self.code_name, self.pc, self.message)
+    }
+}
+
+
+/// The state of the object stack before an instruction, relative to the start of the code object
+#[derive(Clone, PartialEq)]
+struct StackState {
+    depth: usize,
+    /// The depth of the object stack when each scope entered by this code was pushed.
+    /// PopScope truncates the object stack back to the innermost one
+    scope_marks: Vec<usize>,
+    /// The positions on the object stack of the iterators created by GetIter, which ForIter expects
+    iterators: Vec<usize>,
+}
+
+
+impl StackState {
+
+    /// The lowest depth the instruction may pop to, below which the objects belong to an enclosing scope or caller
+    fn floor(&self) -> usize {
+        self.scope_marks.last().copied().unwrap_or(0)
+    }
+
+
+    /// Set the depth of the object stack, forgetting the iterators that were removed from it
+    fn truncate(&mut self, depth: usize) {
+        self.depth = depth;
+        self.iterators.retain(|position| *position < depth);
+    }
+
+}
+
+
+/// Verify the code object and, recursively, its compiled functions, before they are executed.
+///
+/// Checks that every op code is valid, that operands fit in the code, that jumps land on an instruction,
+/// that constant, function and module indices exist and that the object stack never underflows
+/// and has the same depth whichever path reaches an instruction.
+/// Functions that haven't been compiled yet are verified when they are compiled
+pub fn verify(code_object: &CodeObject, constants: &ConstantPool, module_count: usize) -> Result<(), VerifyError> {
+    if !code_object.is_compiled() {
+        return Ok(());
+    }
+
+    Verifier { code_object, constants, module_count }.verify_code()?;
+
+    for function in code_object.functions() {
+        verify(function, constants, module_count)?;
+    }
+
+    Ok(())
+}
+
+
+struct Verifier<'a> {
+    code_object: &'a CodeObject,
+    constants: &'a ConstantPool,
+    module_count: usize,
+}
+
+
+impl Verifier<'_> {
+
+    fn error(&self, pc: usize, message: String) -> VerifyError {
+        VerifyError {
+            code_name: self.code_object.name.to_string(),
+            pc,
+            message,
+        }
+    }
+
+
+    fn verify_code(&self) -> Result<(), VerifyError> {
+        let instructions = self.decode_instructions()?;
+        self.verify_operands(&instructions)?;
+        self.verify_stack(&instructions)
+    }
+
+
+    /// Split the code into instructions, mapping the index of each instruction to its op code
+    fn decode_instructions(&self) -> Result<BTreeMap<usize, OpCode>, VerifyError> {
+        let code: &ByteCode = self.code_object.code();
+        let mut instructions: BTreeMap<usize, OpCode> = BTreeMap::new();
+        let mut pc: usize = 0;
+
+        while pc < code.len() {
+            let op_code = OpCode::from_byte(code[pc])
+                .ok_or_else(|| self.error(pc, format!("invalid op code {}", code[pc])))?;
+
+            let next = pc + 1 + op_code.operand_size();
+            if next > code.len() {
+                return Err(self.error(pc, format!("{} expects {} bytes of operands, but the code ends after {}", op_code, op_code.operand_size(), code.len() - pc - 1)));
+            }
+
+            instructions.insert(pc, op_code);
+            pc = next;
+        }
+
+        Ok(instructions)
+    }
+
+
+    /// Check that the operands referring to other parts of the program are in range
+    fn verify_operands(&self, instructions: &BTreeMap<usize, OpCode>) -> Result<(), VerifyError> {
+        let code = self.code_object.code();
+
+        for (&pc, &op_code) in instructions {
+            let operand = pc + 1;
+
+            match op_code {
+                OpCode::LoadConst => {
+                    let (index, _) = byte_code::get_raw_usize(operand, code);
+                    if index >= self.constants.constants().len() {
+                        return Err(self.error(pc, format!("constant index {} is out of range for a pool of {} constants", index, self.constants.constants().len())));
+                    }
+                },
+
+                OpCode::GetMember => {
+                    let (index, _) = byte_code::get_raw_usize(operand, code);
+                    match self.constants.constants().get(index) {
+                        Some(Object { type_code: TypeCode::String, value: Value::String(_), .. }) => {},
+                        Some(_) => return Err(self.error(pc, format!("member name constant {} is not a String", index))),
+                        None => return Err(self.error(pc, format!("constant index {} is out of range for a pool of {} constants", index, self.constants.constants().len()))),
+                    }
+                },
+
+                OpCode::MakeFunction => {
+                    let (index, _) = byte_code::get_raw_usize(operand, code);
+                    if index >= self.code_object.functions().len() {
+                        return Err(self.error(pc, format!("function index {} is out of range for {} declared functions", index, self.code_object.functions().len())));
+                    }
+                },
+
+                OpCode::LoadCapture => {
+                    let (index, _) = byte_code::get_raw_usize(operand, code);
+                    if index >= self.code_object.captures.len() {
+                        return Err(self.error(pc, format!("capture index {} is out of range for {} captured variables", index, self.code_object.captures.len())));
+                    }
+                },
+
+                OpCode::ImportModule => {
+                    let (module_id, _) = byte_code::get_raw_usize(operand, code);
+                    if module_id >= self.module_count {
+                        return Err(self.error(pc, format!("module id {} is out of range for {} modules", module_id, self.module_count)));
+                    }
+                },
+
+                OpCode::MissingArgument => {
+                    let param_index = code[operand] as usize;
+                    if !self.code_object.is_function || param_index >= self.code_object.named_params() {
+                        return Err(self.error(pc, format!("parameter index {} is out of range for {} parameters", param_index, self.code_object.named_params())));
+                    }
+                },
+
+                OpCode::LoadGlobalRef => {
+                    let (module_id, _) = byte_code::get_raw_id(operand, code);
+                    if module_id >= self.module_count {
+                        return Err(self.error(pc, format!("module id {} is out of range for {} modules", module_id, self.module_count)));
+                    }
+                },
+
+                OpCode::Jump | OpCode::JumpIfFalse | OpCode::JumpIfFalseOrPop | OpCode::JumpIfTrueOrPop | OpCode::ForIter => {
+                    let target = self.jump_target(pc, op_code)
+                        .ok_or_else(|| self.error(pc, "jump target is out of the code".to_string()))?;
+
+                    // Jumping to the end of the code is the same as reaching it
+                    if target != code.len() && !instructions.contains_key(&target) {
+                        return Err(self.error(pc, format!("jump target {} is not the start of an instruction", target)));
+                    }
+                },
+
+                _ => {},
+            }
+        }
+
+        Ok(())
+    }
+
+
+    fn jump_target(&self, pc: usize, op_code: OpCode) -> Option<usize> {
+        let after = pc + 1 + op_code.operand_size();
+        let (offset, _) = byte_code::get_raw_jump(pc + 1, self.code_object.code());
+
+        let target = after as i64 + offset as i64;
+        if (0 ..= self.code_object.code().len() as i64).contains(&target) {
+            Some(target as usize)
+        } else {
+            None
+        }
+    }
+
+
+    /// Follow every path through the code, tracking the object stack depth and the entered scopes
+    fn verify_stack(&self, instructions: &BTreeMap<usize, OpCode>) -> Result<(), VerifyError> {
+        let code = self.code_object.code();
+        let mut states: HashMap<usize, StackState> = HashMap::new();
+        let mut pending: Vec<(usize, StackState)> = vec![(0, StackState { depth: 0, scope_marks: Vec::new(), iterators: Vec::new() })];
+
+        while let Some((pc, state)) = pending.pop() {
+
+            if pc == code.len() {
+                // Reaching the end doesn't clean up the stack, and the global scope of top-level code is kept after it ends
+                if state.depth != 0 || !state.scope_marks.is_empty() {
+                    return Err(self.error(pc, format!("the code ends with {} objects and {} scopes left on the stack", state.depth, state.scope_marks.len())));
+                }
+                continue;
+            }
+
+            if let Some(known) = states.get(&pc) {
+                if *known != state {
+                    return Err(self.error(pc, format!("the instruction is reached with different stack depths ({} and {})", known.depth, state.depth)));
+                }
+                continue;
+            }
+            states.insert(pc, state.clone());
+
+            let op_code = instructions[&pc];
+            let next = pc + 1 + op_code.operand_size();
+            let mut state = state;
+
+            let (pops, pushes) = match op_code {
+                OpCode::PushScope => {
+                    state.scope_marks.push(state.depth);
+                    (0, 0)
+                },
+                OpCode::PopScope => {
+                    match state.scope_marks.pop() {
+                        Some(mark) => state.truncate(mark),
+                        None => return Err(self.error(pc, "PopScope without a matching PushScope".to_string())),
+                    }
+                    (0, 0)
+                },
+                OpCode::CallFunction => (code[pc + 1] as usize + 1, 1),
+                OpCode::TailCall => (code[pc + 1] as usize + 1, 0),
+                // The callable, the list of positional arguments and a name and a value for each keyword argument
+                OpCode::CallFunctionEx | OpCode::TailCallEx => match byte_code::get_raw_usize(pc + 1, code).0.checked_mul(2).and_then(|pops| pops.checked_add(2)) {
+                    Some(pops) => (pops, if op_code == OpCode::CallFunctionEx { 1 } else { 0 }),
+                    None => return Err(self.error(pc, format!("{} has too many keyword arguments", op_code))),
+                },
+                OpCode::MakeList => (byte_code::get_raw_usize(pc + 1, code).0, 1),
+                // The operands are checked before the stack
+                OpCode::MakeFunction => (self.code_object.functions()[byte_code::get_raw_usize(pc + 1, code).0].captures.len(), 1),
+                OpCode::UnpackList => (1, byte_code::get_raw_usize(pc + 1, code).0),
+                // The value, the reference to the list and the indices
+                OpCode::StoreSubscript => match byte_code::get_raw_usize(pc + 1, code).0.checked_add(2) {
+                    Some(pops) => (pops, 0),
+                    None => return Err(self.error(pc, "StoreSubscript has too many indices".to_string())),
+                },
+                _ => stack_effect(op_code),
+            };
+
+            if state.depth - state.floor() < pops {
+                return Err(self.error(pc, format!("{} pops {} objects, but only {} are on the stack", op_code, pops, state.depth - state.floor())));
+            }
+
+            // The iterator of a for loop is pushed before the loop's scope is entered, so it may be below the scope's objects
+            if op_code == OpCode::ForIter && (state.depth == 0 || !state.iterators.contains(&(state.depth - 1))) {
+                return Err(self.error(pc, "ForIter expects an iterator created by GetIter on top of the stack".to_string()));
+            }
+
+            state.truncate(state.depth - pops);
+            state.depth = match state.depth.checked_add(pushes) {
+                Some(depth) => depth,
+                None => return Err(self.error(pc, format!("{} pushes too many objects", op_code))),
+            };
+
+            if op_code == OpCode::GetIter {
+                state.iterators.push(state.depth - 1);
+            }
+
+            match op_code {
+                OpCode::Return | OpCode::ReturnValue | OpCode::TailCall | OpCode::TailCallEx => {
+                    // The call leaves all the scopes entered by the function, so the path ends here
+                },
+                OpCode::Jump => {
+                    pending.push((self.jump_target(pc, op_code).unwrap(), state));
+                },
+                OpCode::JumpIfFalse => {
+                    pending.push((self.jump_target(pc, op_code).unwrap(), state.clone()));
+                    pending.push((next, state));
+                },
+                OpCode::JumpIfFalseOrPop | OpCode::JumpIfTrueOrPop => {
+                    // The object is only consumed if the jump isn't taken
+                    let mut jumped = state.clone();
+                    jumped.depth += 1;
+                    pending.push((self.jump_target(pc, op_code).unwrap(), jumped));
+                    pending.push((next, state));
+                },
+                OpCode::ForIter => {
+                    // The iterator stays on the stack, and the next element is pushed only if there is one
+                    pending.push((self.jump_target(pc, op_code).unwrap(), state.clone()));
+                    state.depth += 1;
+                    pending.push((next, state));
+                },
+                _ => {
+                    pending.push((next, state));
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+
+/// Return the number of objects the instruction pops from and pushes to the object stack.
+/// Instructions whose effect depends on their operands or on the scopes are handled by the caller
+fn stack_effect(op_code: OpCode) -> (usize, usize) {
+    match op_code {
+        OpCode::Nop |
+        OpCode::Allocate |
+        OpCode::Return |
+        OpCode::Jump => (0, 0),
+
+        // ForIter leaves the iterator where it was, which is checked by the caller
+        OpCode::ForIter => (0, 0),
+
+        OpCode::LoadLocalRef |
+        OpCode::LoadGlobalRef |
+        OpCode::LoadOffsetRef |
+        OpCode::LoadConst |
+        OpCode::LoadCapture |
+        OpCode::MissingArgument |
+        OpCode::ImportModule => (0, 1),
+
+        OpCode::Not |
+        OpCode::Negate |
+        OpCode::MatchList |
+        OpCode::GetIter |
+        OpCode::GetMember => (1, 1),
+
+        OpCode::Pop |
+        OpCode::JumpIfFalse |
+        OpCode::JumpIfFalseOrPop |
+        OpCode::JumpIfTrueOrPop |
+        OpCode::ReturnValue => (1, 0),
+
+        OpCode::StoreTop => (2, 0),
+
+        OpCode::ExtendList => (2, 1),
+
+        OpCode::Add |
+        OpCode::Sub |
+        OpCode::Mul |
+        OpCode::Div |
+        OpCode::Mod |
+        OpCode::Equal |
+        OpCode::NotEqual |
+        OpCode::Subscript |
+        OpCode::Greater |
+        OpCode::GreaterEqual |
+        OpCode::Less |
+        OpCode::LessEqual => (2, 1),
+
+        OpCode::PushScope |
+        OpCode::PopScope |
+        OpCode::CallFunction |
+        OpCode::CallFunctionEx |
+        OpCode::TailCall |
+        OpCode::TailCallEx |
+        OpCode::MakeFunction |
+        OpCode::MakeList |
+        OpCode::UnpackList |
+        OpCode::StoreSubscript => unreachable!("The stack effect of {} depends on its operands or the scopes", op_code),
+    }
+}
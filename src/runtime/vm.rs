@@ -1,28 +1,55 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use crate::utils::string::get_lines;
 use super::op_code::OpCode;
 use super::error_codes::{RuntimeError, ErrorCode};
-use crate::lang::object::{Object, TypeCode, Value};
+use crate::lang::object::{Object, OpResult, TypeCode, Value, Namespace, Function};
 use crate::compiler::jit::Jit;
+use crate::compiler::module::{ModuleID, ModuleContent};
+use crate::compiler::code_object::CodeObject;
+use crate::compiler::{codegen, error};
 use super::memory::{Heap, ScopeStack, Address};
-use crate::utils::byte_code::{ByteCode, self};
-use crate::compiler::code_node::{NodeContent, CodeNode};
-use super::execution_queue;
-
-
-struct FunctionCall {
-    /// The object stack index where the return value is stored.
-    pub return_index: usize,
-    /// The function that was called.
-    pub function: *const CodeNode,
+use super::capabilities::Capabilities;
+use super::tracer::Tracer;
+use crate::utils::byte_code::{ByteCode, JumpOffset, self};
+
+
+/// The default maximum number of frames on the call stack
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+/// The maximum number of functions called by native functions that can run at the same time.
+/// Each of them runs in a nested loop on the Rust stack, which is much smaller than the call stack
+const MAX_NATIVE_CALL_DEPTH: usize = 256;
+
+/// The positional arguments and the named keyword arguments of a call
+type Arguments = (Vec<Object>, Vec<(Rc<str>, Object)>);
+
+
+/// The execution state of a function call, or of the top-level code of a module
+struct Frame {
+    /// The code being executed.
+    pub code_object: *const CodeObject,
+    /// The index of the next instruction, saved while the frame is calling another function.
+    pub pc: usize,
+    /// The number of scopes on the scope stack before the call.
+    pub scope_depth: usize,
+    /// The indices of the parameters with a default value that the caller didn't pass.
+    pub missing_args: Vec<usize>,
+    /// The variables captured by the called function object.
+    pub captures: Rc<[*mut Object]>,
 }
 
 
-impl FunctionCall {
+impl Frame {
 
-    pub fn new(return_index: usize, function: *const CodeNode) -> Self {
+    pub fn new(code_object: *const CodeObject, scope_depth: usize, missing_args: Vec<usize>, captures: Rc<[*mut Object]>) -> Self {
         Self { 
-            return_index, 
-            function,
+            code_object,
+            pc: 0,
+            scope_depth, 
+            missing_args,
+            captures,
         }
     }
 
@@ -31,172 +58,141 @@ impl FunctionCall {
 
 pub struct Vm {
     stack: ScopeStack,
-    call_stack: Vec<FunctionCall>,
+    call_stack: Vec<Frame>,
+    /// The maximum number of frames on the call stack, above which a StackOverflow is raised
+    max_call_depth: usize,
+    /// The number of functions called by native functions that are running
+    native_call_depth: usize,
     heap: Heap,
     verbose: bool,
+    /// The program being executed
+    jit: *mut Jit,
+    /// The heap addresses of the globals of each module whose top-level code has finished running
+    module_globals: HashMap<ModuleID, Vec<Address>>,
+    /// The namespace of each imported module
+    namespaces: HashMap<ModuleID, Namespace>,
+    /// The I/O operations the program is allowed to perform
+    capabilities: Capabilities,
+    /// The command line arguments passed to the program after the script path
+    script_args: Vec<String>,
+    /// Prints the executed instructions, if tracing is enabled
+    tracer: Option<Tracer>,
 }
 
 
 impl Vm {
 
-    pub fn new(verbose: bool) -> Vm {
+    pub fn new(verbose: bool, capabilities: Capabilities, script_args: Vec<String>, tracer: Option<Tracer>, max_call_depth: usize) -> Vm {
         Vm {
             stack: ScopeStack::new(),
             heap: Heap::new(),
             call_stack: Vec::new(),
+            max_call_depth,
+            native_call_depth: 0,
             verbose,
+            jit: std::ptr::null_mut(),
+            module_globals: HashMap::new(),
+            namespaces: HashMap::new(),
+            capabilities,
+            script_args,
+            tracer,
         }
     }
 
 
-    pub fn execute(&mut self, jit: &mut Jit, source: &str, verbose: bool) -> RuntimeError {
-        // Push the global scope
-        self.stack.push_scope();
-
-        if verbose {
-            // self.run_verbose(jit, source);
-        } else {
-            self.run(jit, source);
-        }
-
-        // If no error was thrown, return no error
-        RuntimeError::no_error()
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
     }
 
 
-    fn run(&mut self, jit: &mut Jit, source: &str) {
-        let pop_scope_node = CodeNode::pop_scope_node();
-
-        let mut queue = execution_queue::new_queue();
-
-        execution_queue::extend_queue(&queue, &jit.root.nodes);
-
-        while queue.len() > 0 {
-
-            // Don't remove the node from the queue because it may have children
-            // to be executed first
-            let node = queue.last().unwrap();
-
-            // TODO: load all the nodes at once and then start executing
-
-            match &node.children {
-
-                NodeContent::None => {
-                    // Do nothing, there are no children to compile or execute
-                    // Execute the current node in the queue
-                },
-                
-                NodeContent::ListLike { children } => {
-                    // Push the children to execute first onto the queue
-                    execution_queue::extend_queue(&queue, children);
-                    continue;
-                },
-                
-                NodeContent::Scope { body } => {
-                    // Push the pop scope instruction
-                    execution_queue::push_queue(&queue, &pop_scope_node);
-
-                    // Push the scope contents
-                    execution_queue::extend_queue(&queue, &body.nodes);
-
-                    // Don't continue in order to execute the push scope instruction
-                },
-                
-                NodeContent::LoopLike { condition, body } => {
-                    todo!()
-                },
-                
-                NodeContent::IfLike { condition, body, else_node } => {
-                    execution_queue::push_queue(&queue, condition);
-
-                    todo!()
-
-                    // Continue in order to execute the condition first
-                    continue;
-                },
-                
-                NodeContent::Function { .. } => {
-                    // Do nothing, functions are compiled upon calling
-                },
-    
-                NodeContent::Optional { child } => {
-                    if let Some(child) = child {
-                        execution_queue::push_queue(&queue, child);
-                    }
-                    continue;
-                }
-            
-            }
-
-            // Execute the current node
-            self.execute_node(node, source);
-
-
-            // Finally, remove the last node from the queue after it has been executed
-            queue.pop();
-        }
+    pub fn script_args(&self) -> &[String] {
+        &self.script_args
     }
 
 
-    // TODO: reimplement this
-    // fn run_verbose(&mut self, jit: &Jit, source: &str) {
-    //     let mut index: usize = 0;
+    /// Run the main module and return the error that stopped it, or no error.
+    /// Errors unwind the calls instead of exiting, so that the caller decides how to report them
+    pub fn execute(&mut self, jit: &mut Jit) -> RuntimeError {
+        self.jit = jit as *mut Jit;
 
-    //     while let Some(block) = jit.statements.get(index) {
+        // Push the global scope
+        self.stack.push_scope();
 
-    //         println!("{}", get_lines(source, block.syntax_node.get_line(), 0));
+        let result = match &jit.main_module().content {
+            ModuleContent::Source { root, .. } => self.run(root),
+            ModuleContent::Native { .. } => unreachable!("The main module is always compiled from source"),
+        };
 
-    //         // Recursively execute the current code block and its children
-    //         self.execute_block(block, source, jit);
+        // If no error was thrown, return no error
+        result.err().unwrap_or_else(RuntimeError::no_error)
+    }
 
-    //         index += 1;
-    //     }
-    // }
 
+    /// Execute the top-level code of a module until its end is reached
+    fn run(&mut self, code_object: &CodeObject) -> Result<(), RuntimeError> {
+        self.call_stack.push(Frame::new(code_object, self.stack.scope_depth(), Vec::new(), Rc::from([])));
+        self.run_frames(self.call_stack.len() - 1).map(|_| ())
+    }
 
-    fn execute_node(&mut self, node: &CodeNode, source: &str) {
-        let code: &ByteCode = node.get_code(source);
-        let mut pc: usize = 0;
 
-        while pc < code.len() {
+    /// Execute the frame at the given height of the call stack until it returns or its end is reached.
+    /// Return the return value, which is None if there's no return value.
+    /// An error leaves the frame and the frames it called, and is returned
+    ///
+    /// The functions it calls are executed in the same loop, on their own frame, so that deep recursion doesn't overflow the Rust stack
+    fn run_frames(&mut self, base: usize) -> Result<Object, RuntimeError> {
+        let frame = &self.call_stack[base];
+        let mut code_object: &CodeObject = unsafe { &*frame.code_object };
+        let mut code: &ByteCode = code_object.code();
+        let mut pc: usize = frame.pc;
+
+        loop {
+
+            if pc >= code.len() {
+                // Only top-level code reaches its end, since functions end with a return.
+                // Its global scope is kept, as it holds the module's globals
+                self.call_stack.pop();
+                return Ok(Object::none());
+            }
 
+            // Errors are reported at the start of the instruction
+            let instruction_pc = pc;
             let instruction: OpCode = OpCode::from(code[pc]);
             pc += 1;
 
-            match instruction {
+            if let Some(tracer) = &mut self.tracer {
+                tracer.trace(code_object, instruction_pc, &self.stack, unsafe {&*self.jit});
+            }
+
+            let result: Result<(), RuntimeError> = match instruction {
 
                 OpCode::Nop => {
                     // Do nothing
+                    Ok(())
                 },
 
                 OpCode::LoadLocalRef => {
                     let (local_id, to_add) = byte_code::get_raw_id(pc, code);
                     pc += to_add;
 
-                    let address: Address = self.stack.get_heap_address_from_local_id(local_id);
-                    match self.heap.get_ref(address) {
-                        Ok(object_ref) => {
-                            self.stack.push(object_ref);
-                        },
-                        Err(error) => {
-                            self.throw_error(error, node, source);
-                        }
-                    }
+                    let address = self.stack.get_heap_address_from_local_id(local_id);
+                    self.push_ref(address)
                 },
 
                 OpCode::LoadGlobalRef => {
+                    let (module_id, to_add) = byte_code::get_raw_id(pc, code);
+                    pc += to_add;
+
                     let (global_id, to_add) = byte_code::get_raw_id(pc, code);
                     pc += to_add;
 
-                    let address: Address = self.stack.get_heap_address_from_global_id(global_id);
-                    match self.heap.get_ref(address) {
-                        Ok(object_ref) => {
-                            self.stack.push(object_ref);
-                        },
-                        Err(error) => {
-                            self.throw_error(error, node, source);
-                        }
-                    }
+                    let address = if let Some(globals) = self.module_globals.get(&module_id) {
+                        globals.get(global_id).copied()
+                    } else {
+                        // The module's top-level code is still running, so its global scope is on the current stack
+                        self.stack.get_heap_address_from_global_id(global_id)
+                    };
+                    self.push_ref(address)
                 },
 
                 OpCode::LoadOffsetRef => {
@@ -206,30 +202,28 @@ impl Vm {
                     let (scope_offset, to_add) = byte_code::get_raw_id(pc, code);
                     pc += to_add;
 
-                    let address: Address = self.stack.get_heap_address_from_offsets(local_id, scope_offset);
-                    match self.heap.get_ref(address) {
-                        Ok(object_ref) => {
-                            self.stack.push(object_ref);
-                        },
-                        Err(error) => {
-                            self.throw_error(error, node, source);
-                        }
-                    }
+                    let address = self.stack.get_heap_address_from_offsets(local_id, scope_offset);
+                    self.push_ref(address)
                 },
 
                 OpCode::LoadConst => {
-                    let type_code = TypeCode::from(code[pc]);
-                    pc += 1;
-
-                    let (obj, to_add) = Object::from_byte_code_const(type_code, code, pc);
+                    let (index, to_add) = byte_code::get_raw_usize(pc, code);
                     pc += to_add;
 
-                    self.stack.push(obj);
+                    // Constants are shared, so cloning them doesn't allocate
+                    let constant = unsafe {&*self.jit}.constants.get(index).clone();
+                    self.stack.push(constant);
+                    Ok(())
                 },
 
                 OpCode::PopScope => {
-                    // TODO: make it reachable
-                    unreachable!("PopScope should not be executed");
+                    self.stack.pop_scope();
+                    Ok(())
+                },
+
+                OpCode::PushScope => {
+                    self.stack.push_scope();
+                    Ok(())
                 },
 
                 OpCode::CallFunction => {
@@ -237,304 +231,685 @@ impl Vm {
                     let arg_count = code[pc] as usize;
                     pc += 1;
 
-                    // Load the arguments
+                    // Load the arguments, which are popped in reverse order
                     let mut arguments: Vec<Object> = Vec::with_capacity(arg_count);
                     for _ in 0..arg_count {
                         arguments.push(self.stack.pop_require());
                     }
+                    arguments.reverse();
 
                     // Load the callable object
                     let callable = self.stack.pop_require();
-                    let callable = self.deref_if_ref(&callable);
 
-                    // Get the function to call and check if the object is callable
-                    let code_node_ptr: *mut CodeNode = match callable {
-                        Object { type_code: TypeCode::Function, value: Value::Function(code_node), .. } => {
-                            *code_node
+                    // The caller resumes after the call once the function returns
+                    self.call_stack.last_mut().unwrap().pc = pc;
+                    self.enter_call(&callable, arguments, Vec::new()).map(|return_value| match return_value {
+                        Some(return_value) => self.stack.push(return_value),
+                        None => {
+                            code_object = unsafe { &*self.call_stack.last().unwrap().code_object };
+                            code = code_object.code();
+                            pc = 0;
                         },
-                        _ => {
-                            self.throw_error(RuntimeError::with_message(
-                                ErrorCode::TypeError,
-                                "Object is not callable".to_owned()
-                            ), node, source);
-                        }
-                    };
-                    
-                    // Push the function call to the runtime call stack
-                    self.call_stack.push(FunctionCall::new(
-                        self.stack.get_last_stack_index(),
-                        code_node_ptr
-                    ));
-
-                    let code_node: &mut CodeNode = unsafe {
-                        &mut *code_node_ptr
-                    };
-
-                    // Call the function
-                    self.execute_node(code_node, source);
+                    })
                 },
                 
                 OpCode::MakeFunction => {
-                    // Load the code node containing the information about the function
-                    let (node_ptr, to_add) = byte_code::get_raw_ptr::<CodeNode>(pc, code);
+                    // Load the code object of the function declared in this code
+                    let (function_index, to_add) = byte_code::get_raw_usize(pc, code);
                     pc += to_add;
 
-                    let func_obj = Object::new(TypeCode::Function, Value::Function(node_ptr as *mut CodeNode));
-                    self.stack.push(func_obj);
-                },
-                
-                OpCode::StoreTop => {
-                    let r_obj = self.stack.pop_require();
-                    let mut l_ref = self.stack.pop_require();
+                    let function = code_object.functions()[function_index].as_ref();
 
-                    if let Err(error) = self.assign_ref(&mut l_ref, r_obj) {
-                        self.throw_error(error, node, source);
+                    // The captured variables are popped in reverse order
+                    let mut captures: Vec<*mut Object> = Vec::with_capacity(function.captures.len());
+                    for _ in 0..function.captures.len() {
+                        match self.stack.pop_require() {
+                            Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => captures.push(object_ptr),
+                            _ => unreachable!("Captured variables are always loaded by reference"),
+                        }
                     }
+                    captures.reverse();
+
+                    self.stack.push(Object::new(TypeCode::Function, Value::Function(Function {
+                        code_object: function,
+                        captures: captures.into(),
+                    })));
+                    Ok(())
                 },
-                
-                OpCode::Add => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::LoadCapture => {
+                    let (index, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    match Object::add(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    let object_ptr = self.call_stack.last().unwrap().captures[index];
+                    unsafe { (*object_ptr).inc_ref_count() };
+                    self.stack.push(Object::new_ref(object_ptr));
+                    Ok(())
                 },
                 
-                OpCode::Sub => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
-
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::StoreTop => {
+                    let target = self.stack.pop_require();
+                    let value = self.stack.pop_require();
 
-                    match Object::sub(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    // Objects are stored by value
+                    let value = self.deref_if_ref(&value).clone();
+                    self.assign_ref(&target, value)
                 },
-                
-                OpCode::Mul => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::StoreSubscript => {
+                    let (count, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    match Object::mul(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
+                    // The indices are popped in reverse order
+                    let mut indices: Vec<Object> = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        indices.push(self.stack.pop_require());
                     }
+                    indices.reverse();
+
+                    let target = self.stack.pop_require();
+                    let value = self.stack.pop_require();
+                    let value = self.deref_if_ref(&value).clone();
+                    self.assign_element(&target, &indices, value)
                 },
                 
-                OpCode::Div => {
-                    let b = self.stack.pop_require();
+                OpCode::Add => self.binary_op(Object::add),
+                OpCode::Sub => self.binary_op(Object::sub),
+                OpCode::Mul => self.binary_op(Object::mul),
+                OpCode::Div => self.binary_op(Object::div),
+                OpCode::Mod => self.binary_op(Object::rem),
+                OpCode::Greater => self.binary_op(Object::greater),
+                OpCode::GreaterEqual => self.binary_op(Object::greater_eq),
+                OpCode::Less => self.binary_op(Object::less),
+                OpCode::LessEqual => self.binary_op(Object::less_eq),
+
+                OpCode::Equal => self.binary_op(
+                    |a, b| Ok(Object::new(TypeCode::Bool, Value::Bool(Object::eq(a, b))))
+                ),
+
+                OpCode::NotEqual => self.binary_op(
+                    |a, b| Ok(Object::new(TypeCode::Bool, Value::Bool(Object::ne(a, b))))
+                ),
+                
+                OpCode::Not => {
                     let a = self.stack.pop_require();
+                    let result = Object::not(self.deref_if_ref(&a));
+                    result.map(|obj| self.stack.push(obj))
+                },
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::Negate => {
+                    let a = self.stack.pop_require();
+                    let result = Object::neg(self.deref_if_ref(&a));
+                    result.map(|obj| self.stack.push(obj))
+                },
+                
+                OpCode::GetIter => {
+                    let iterable = self.stack.pop_require();
+                    let result = Object::get_iter(self.deref_if_ref(&iterable));
+                    result.map(|iterator| self.stack.push(iterator))
+                },
+                
+                OpCode::Subscript => {
+                    let index = self.stack.pop_require();
+                    let iterable = self.stack.pop_require();
+                    Object::subscript(&iterable, &index).map(|element| self.stack.push(element))
+                },
+                
+                OpCode::ReturnValue | OpCode::Return => {
+                    if !code_object.is_function {
+                        Err(RuntimeError::with_message(
+                            ErrorCode::ReturnOutsideFunction,
+                            "Cannot return outside of a function".to_owned(),
+                        ))
+                    } else {
+                        let return_value = if let OpCode::ReturnValue = instruction {
+                            let return_value = self.stack.pop_require();
+                            self.deref_if_ref(&return_value).clone()
+                        } else {
+                            Object::none()
+                        };
+
+                        if let Some(return_value) = self.leave_frame(base, return_value) {
+                            return Ok(return_value);
+                        }
 
-                    match Object::div(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
+                        // Resume the calling function
+                        let caller = self.call_stack.last().unwrap();
+                        code_object = unsafe { &*caller.code_object };
+                        code = code_object.code();
+                        pc = caller.pc;
+                        Ok(())
                     }
                 },
-                
-                OpCode::Mod => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::Allocate => {
+                    let address: Address = self.heap.allocate();
+                    self.stack.push_heap_address(address);
+                    Ok(())
+                },
+
+                OpCode::MakeList => {
+                    let (count, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    match Object::rem(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
+                    // The elements are popped in reverse order
+                    let mut elements: Vec<Object> = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        let element = self.stack.pop_require();
+                        elements.push(self.deref_if_ref(&element).clone());
                     }
+                    elements.reverse();
+
+                    self.stack.push(Object::new(TypeCode::List, Value::List(elements)));
+                    Ok(())
                 },
-                
-                OpCode::Equal => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::CallFunctionEx => {
+                    let (keyword_count, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    self.stack.push(
-                        Object::new(TypeCode::Bool, Value::Bool(Object::eq(a, b)))
-                    );
+                    self.pop_arguments_ex(keyword_count).and_then(|(arguments, keywords)| {
+                        let callable = self.stack.pop_require();
+
+                        self.call_stack.last_mut().unwrap().pc = pc;
+                        self.enter_call(&callable, arguments, keywords)
+                    }).map(|return_value| match return_value {
+                        Some(return_value) => self.stack.push(return_value),
+                        None => {
+                            code_object = unsafe { &*self.call_stack.last().unwrap().code_object };
+                            code = code_object.code();
+                            pc = 0;
+                        },
+                    })
                 },
 
-                OpCode::NotEqual => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
+                OpCode::UnpackList => {
+                    let (count, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                    let list = self.stack.pop_require();
+                    let elements = match self.deref_if_ref(&list) {
+                        Object { type_code: TypeCode::List, value: Value::List(elements), .. } if elements.len() == count => Ok(elements.clone()),
+                        Object { type_code: TypeCode::List, value: Value::List(elements), .. } => Err(RuntimeError::with_message(
+                            ErrorCode::ValueError,
+                            format!("Expected {} value{} to unpack, got {}", count, if count == 1 { "" } else { "s" }, elements.len())
+                        )),
+                        object => Err(RuntimeError::with_message(
+                            ErrorCode::TypeError,
+                            format!("Cannot unpack {}, only a List can be unpacked", object.type_code.name())
+                        )),
+                    };
+
+                    // The first element ends up on top, so that it's stored into the first target
+                    elements.map(|elements| for element in elements.into_iter().rev() {
+                        self.stack.push(element);
+                    })
+                },
 
-                    self.stack.push(
-                        Object::new(TypeCode::Bool, Value::Bool(Object::ne(a, b)))
+                OpCode::MatchList => {
+                    let (count, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
+
+                    let object = self.stack.pop_require();
+                    let is_match = matches!(
+                        self.deref_if_ref(&object),
+                        Object { type_code: TypeCode::List, value: Value::List(elements), .. } if elements.len() == count
                     );
+                    self.stack.push(Object::new(TypeCode::Bool, Value::Bool(is_match)));
+                    Ok(())
                 },
-                
-                OpCode::Not => {
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
+                OpCode::ExtendList => {
+                    let spread = self.stack.pop_require();
+                    let mut list = self.stack.pop_require();
 
-                    match Object::not(a) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),                        
+                    match (&mut list.value, self.deref_if_ref(&spread)) {
+                        (Value::List(elements), Object { type_code: TypeCode::List, value: Value::List(spread_elements), .. }) => {
+                            elements.extend(spread_elements.iter().cloned());
+                            self.stack.push(list);
+                            Ok(())
+                        },
+                        (_, spread) => Err(RuntimeError::with_message(
+                            ErrorCode::TypeError,
+                            format!("Cannot spread {}, only a List can be spread", spread.type_code.name())
+                        )),
                     }
                 },
-                
-                OpCode::GetIter => todo!(),
-                
-                OpCode::Subscript => todo!(),
-                
-                OpCode::ReturnValue => {
 
-                },
+                OpCode::TailCall | OpCode::TailCallEx => {
+                    let arguments = if instruction == OpCode::TailCall {
+                        let arg_count = code[pc] as usize;
+                        pc += 1;
 
-                OpCode::Return => {
-                    let last_call = if let Some(last_call) = self.call_stack.pop() {
-                        last_call
+                        let mut arguments: Vec<Object> = Vec::with_capacity(arg_count);
+                        for _ in 0..arg_count {
+                            arguments.push(self.stack.pop_require());
+                        }
+                        arguments.reverse();
+                        Ok((arguments, Vec::new()))
                     } else {
-                        self.throw_error(RuntimeError::with_message(
-                            ErrorCode::ReturnOutsideFunction,
-                            "Cannot return outside of a function".to_owned(),
-                        ), node, source);
+                        let (keyword_count, to_add) = byte_code::get_raw_usize(pc, code);
+                        pc += to_add;
+                        self.pop_arguments_ex(keyword_count)
                     };
 
-                    while self.stack.get_last_stack_index() > last_call.return_index {
-                        self.stack.pop_scope();
+                    let callable = self.stack.pop_require();
+
+                    if !code_object.is_function {
+                        Err(RuntimeError::with_message(
+                            ErrorCode::ReturnOutsideFunction,
+                            "Cannot return outside of a function".to_owned(),
+                        ))
+                    } else {
+                        match arguments.and_then(|(arguments, keywords)| self.enter_tail_call(&callable, arguments, keywords)) {
+                            // A native function was called, so its return value is returned like by ReturnValue
+                            Ok(Some(return_value)) => {
+                                if let Some(return_value) = self.leave_frame(base, return_value) {
+                                    return Ok(return_value);
+                                }
+
+                                let caller = self.call_stack.last().unwrap();
+                                code_object = unsafe { &*caller.code_object };
+                                code = code_object.code();
+                                pc = caller.pc;
+                                Ok(())
+                            },
+                            Ok(None) => {
+                                code_object = unsafe { &*self.call_stack.last().unwrap().code_object };
+                                code = code_object.code();
+                                pc = 0;
+                                Ok(())
+                            },
+                            Err(error) => Err(error),
+                        }
                     }
                 },
 
-                OpCode::PushScope => {
-                    self.stack.push_scope();
-                },
+                OpCode::MissingArgument => {
+                    let param_index = code[pc] as usize;
+                    pc += 1;
 
-                OpCode::And => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
+                    let is_missing = self.call_stack.last().is_some_and(|call| call.missing_args.contains(&param_index));
+                    self.stack.push(Object::new(TypeCode::Bool, Value::Bool(is_missing)));
+                    Ok(())
+                },
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::ImportModule => {
+                    let (module_id, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    match Object::and(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    self.import_module(module_id).map(|namespace| {
+                        self.stack.push(Object::new(TypeCode::Module, Value::Module(namespace)));
+                    })
                 },
-                
-                OpCode::Or => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::GetMember => {
+                    let (index, to_add) = byte_code::get_raw_usize(pc, code);
+                    pc += to_add;
 
-                    match Object::or(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    let name = match unsafe {&*self.jit}.constants.get(index) {
+                        Object { type_code: TypeCode::String, value: Value::String(name), .. } => name,
+                        _ => unreachable!("Member names are always String constants"),
+                    };
+
+                    let obj = self.stack.pop_require();
+                    self.get_member(&obj, name)
                 },
-                
-                OpCode::Greater => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::Pop => {
+                    self.stack.pop_require();
+                    Ok(())
+                },
 
-                    match Object::greater(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                OpCode::Jump => {
+                    let (offset, to_add) = byte_code::get_raw_jump(pc, code);
+                    pc = jump_target(pc + to_add, offset);
+                    Ok(())
                 },
-                
-                OpCode::GreaterEqual => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::JumpIfFalse => {
+                    let (offset, to_add) = byte_code::get_raw_jump(pc, code);
+                    pc += to_add;
 
-                    match Object::greater_eq(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    let condition = self.stack.pop_require();
+                    self.deref_if_ref(&condition).to_bool().map(|condition| {
+                        if !condition {
+                            pc = jump_target(pc, offset);
+                        }
+                    })
                 },
-                
-                OpCode::Less => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::JumpIfFalseOrPop | OpCode::JumpIfTrueOrPop => {
+                    let (offset, to_add) = byte_code::get_raw_jump(pc, code);
+                    pc += to_add;
 
-                    match Object::less(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
-                    }
+                    let condition = self.stack.pop_require();
+                    let jump_when = instruction == OpCode::JumpIfTrueOrPop;
+                    self.deref_if_ref(&condition).to_bool().map(|value| {
+                        if value == jump_when {
+                            pc = jump_target(pc, offset);
+                            self.stack.push(condition);
+                        }
+                    })
                 },
-                
-                OpCode::LessEqual => {
-                    let b = self.stack.pop_require();
-                    let a = self.stack.pop_require();
 
-                    let a = self.deref_if_ref(&a);
-                    let b = self.deref_if_ref(&b);
+                OpCode::ForIter => {
+                    let (offset, to_add) = byte_code::get_raw_jump(pc, code);
+                    pc += to_add;
 
-                    match Object::less_eq(a, b) {
-                        Ok(obj) => self.stack.push(obj),
-                        Err(error) => self.throw_error(error, node, source),
+                    let mut iterator = self.stack.pop_require();
+                    let element = match &mut iterator.value {
+                        Value::Iterator(iterator) => iterator.next(),
+                        _ => unreachable!("ForIter always follows GetIter"),
+                    };
+                    self.stack.push(iterator);
+
+                    match element {
+                        Some(element) => self.stack.push(element),
+                        None => pc = jump_target(pc, offset),
                     }
+                    Ok(())
                 },
 
-                OpCode::Allocate => {
-                    let address: Address = self.heap.allocate();
-                    self.stack.push_heap_address(address);
-                },
+            };
 
-                OpCode::MakeList => {
-                    let (count, to_add) = byte_code::get_raw_usize(pc, code);
-                    pc += to_add;
+            if let Err(error) = result {
+                return Err(self.throw_error(error, base, code_object, instruction_pc));
+            }
+        }
+    }
 
-                    let mut elements: Vec<Object> = Vec::with_capacity(count);
-                    for _ in 0..count {
-                        elements.push(self.stack.pop_require());
+
+    /// Consume the two top-most objects on the object stack and push the result of the operation
+    fn binary_op(&mut self, operation: fn(&Object, &Object) -> OpResult) -> Result<(), RuntimeError> {
+        let b = self.stack.pop_require();
+        let a = self.stack.pop_require();
+
+        let result = operation(self.deref_if_ref(&a), self.deref_if_ref(&b));
+        result.map(|obj| self.stack.push(obj))
+    }
+
+
+    /// Push a reference to the object at the given heap address.
+    /// A missing address means the symbol is used before its declaration was executed
+    fn push_ref(&mut self, address: Option<Address>) -> Result<(), RuntimeError> {
+        let address = address.ok_or_else(|| RuntimeError::with_message(
+            ErrorCode::UndeclaredSymbol,
+            "Symbol used before its declaration".to_owned(),
+        ))?;
+
+        let object_ref = self.heap.get_ref(address)?;
+        self.stack.push(object_ref);
+        Ok(())
+    }
+
+
+    /// Call the callable object and return its return value.
+    /// Arguments are passed by value.
+    /// Native functions use it to call the functions passed to them
+    pub fn call(&mut self, callable: &Object, arguments: Vec<Object>) -> OpResult {
+        if self.native_call_depth >= MAX_NATIVE_CALL_DEPTH {
+            return Err(RuntimeError::with_message(
+                ErrorCode::StackOverflow,
+                format!("Maximum depth of {} calls from native functions exceeded", MAX_NATIVE_CALL_DEPTH)
+            ));
+        }
+
+        match self.enter_call(callable, arguments, Vec::new())? {
+            Some(return_value) => Ok(return_value),
+            None => {
+                self.native_call_depth += 1;
+                let return_value = self.run_frames(self.call_stack.len() - 1);
+                self.native_call_depth -= 1;
+                return_value
+            },
+        }
+    }
+
+
+    /// Call a native function and return its return value, or push the frame of a function and return None.
+    /// The frame is then executed by the caller.
+    /// Arguments are passed by value
+    fn enter_call(&mut self, callable: &Object, arguments: Vec<Object>, keywords: Vec<(Rc<str>, Object)>) -> Result<Option<Object>, RuntimeError> {
+        let arguments: Vec<Object> = arguments.iter().map(
+            |argument| self.deref_if_ref(argument).clone()
+        ).collect();
+        let keywords: Vec<(Rc<str>, Object)> = keywords.into_iter().map(
+            |(name, argument)| (name, self.deref_if_ref(&argument).clone())
+        ).collect();
+
+        match self.deref_if_ref(callable) {
+            Object { type_code: TypeCode::Function, value: Value::Function(function), .. } => {
+                self.push_frame(function.code_object, function.captures.clone(), arguments, keywords).map(|_| None)
+            },
+            Object { type_code: TypeCode::Function, value: Value::Native(native), .. } => {
+                let native = *native;
+
+                if !keywords.is_empty() {
+                    return Err(RuntimeError::with_message(
+                        ErrorCode::TypeError,
+                        format!("{}() doesn't take keyword arguments", native.name)
+                    ));
+                }
+
+                (native.function)(self, &arguments).map(Some).inspect_err(|_| {
+                    if self.verbose {
+                        eprintln!("Error in native function {}()", native.name);
                     }
+                })
+            },
+            callable => Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("{} is not callable", callable.type_code.name())
+            )),
+        }
+    }
 
-                    let list_obj = Object::new(TypeCode::List, Value::List(elements));
-                    self.stack.push(list_obj);
-                },
 
+    /// Call the callable object in place of the current function, whose frame and scopes are replaced by the callee's.
+    /// Native functions are called like by enter_call, since they don't have a frame
+    fn enter_tail_call(&mut self, callable: &Object, arguments: Vec<Object>, keywords: Vec<(Rc<str>, Object)>) -> Result<Option<Object>, RuntimeError> {
+        let callable = self.deref_if_ref(callable).clone();
+        if !matches!(callable.value, Value::Function(_)) {
+            return self.enter_call(&callable, arguments, keywords);
+        }
+
+        // The arguments may reference the locals of the current function, so they are copied before its scopes are left
+        let arguments: Vec<Object> = arguments.iter().map(
+            |argument| self.deref_if_ref(argument).clone()
+        ).collect();
+        let keywords: Vec<(Rc<str>, Object)> = keywords.into_iter().map(
+            |(name, value)| (name, self.deref_if_ref(&value).clone())
+        ).collect();
+
+        let frame = self.call_stack.pop().unwrap();
+        self.stack.pop_scopes_to(frame.scope_depth);
+
+        self.enter_call(&callable, arguments, keywords)
+    }
+
+
+    /// Pop the frame of the returning function, including any scope a return jumped out of.
+    /// Return the return value if the frame was the one at the given height of the call stack.
+    /// Otherwise, push the return value for the calling function and return None
+    fn leave_frame(&mut self, base: usize, return_value: Object) -> Option<Object> {
+        let frame = self.call_stack.pop().unwrap();
+        self.stack.pop_scopes_to(frame.scope_depth);
+
+        if self.call_stack.len() == base {
+            return Some(return_value);
+        }
+
+        self.stack.push(return_value);
+        None
+    }
+
+
+    /// Pop the arguments of a CallFunctionEx or TailCallEx: the list of positional arguments, then a name and a value for each keyword argument.
+    /// The keyword arguments are popped in reverse order, each value before its name
+    fn pop_arguments_ex(&mut self, keyword_count: usize) -> Result<Arguments, RuntimeError> {
+        let mut keywords: Vec<(Rc<str>, Object)> = Vec::with_capacity(keyword_count);
+        for _ in 0..keyword_count {
+            let value = self.stack.pop_require();
+            let name = match self.stack.pop_require() {
+                Object { type_code: TypeCode::String, value: Value::String(name), .. } => name,
+                object => return Err(RuntimeError::with_message(
+                    ErrorCode::TypeError,
+                    format!("Keyword argument names must be String, got {}", object.type_code.name())
+                )),
+            };
+            keywords.push((name, value));
+        }
+        keywords.reverse();
+
+        match self.stack.pop_require() {
+            Object { type_code: TypeCode::List, value: Value::List(arguments), .. } => Ok((arguments, keywords)),
+            object => Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("The positional arguments must be collected in a List, got {}", object.type_code.name())
+            )),
+        }
+    }
+
+
+    /// Bind the arguments to the parameters of the function and push its frame to the call stack.
+    /// Raise a StackOverflow if the call stack is full
+    fn push_frame(&mut self, function_ptr: *const CodeObject, captures: Rc<[*mut Object]>, arguments: Vec<Object>, keywords: Vec<(Rc<str>, Object)>) -> Result<(), RuntimeError> {
+        let function = unsafe { &*function_ptr };
+
+        if self.call_stack.len() >= self.max_call_depth {
+            return Err(RuntimeError::with_message(
+                ErrorCode::StackOverflow,
+                format!("Maximum call depth of {} exceeded when calling {}()", self.max_call_depth, function.name)
+            ));
+        }
+
+        let (arguments, missing_args) = bind_arguments(function, arguments, keywords)?;
+
+        if !function.is_compiled() {
+            // The function may be declared in another module
+            let function_source = unsafe {&*self.jit}.get_module(function.module_id).get_source();
+
+            // The function is compiled the first time it's called in lazy mode, setting its compiled code in place
+            let options = unsafe {&*self.jit}.options;
+            let constants = unsafe { &mut (*self.jit).constants };
+            if let Err(errors) = codegen::compile_function(function, function_source, options, constants) {
+                error::report_compile_errors(&errors);
             }
 
+            let jit = unsafe {&*self.jit};
+            jit.verify_code_object(function, &jit.get_module(function.module_id).path);
+        }
+
+        self.call_stack.push(Frame::new(function_ptr, self.stack.scope_depth(), missing_args, captures));
+
+        // The parameters are the first locals of the function body
+        self.stack.push_scope();
+        for argument in arguments {
+            let address = self.heap.allocate_object(argument);
+            self.stack.push_heap_address(address);
         }
 
+        Ok(())
+    }
+
+
+    /// Push a reference to the member of the object with the given name
+    fn get_member(&mut self, obj: &Object, name: &str) -> Result<(), RuntimeError> {
+        let address: Address = match self.deref_if_ref(obj) {
+            Object { type_code: TypeCode::Module, value: Value::Module(namespace), .. } => {
+                if let Some(address) = namespace.members.get(name) {
+                    *address
+                } else {
+                    return Err(RuntimeError::with_message(
+                        ErrorCode::AttributeError,
+                        format!("Module '{}' has no member '{}'", namespace.name, name)
+                    ));
+                }
+            },
+            obj => {
+                return Err(RuntimeError::with_message(
+                    ErrorCode::AttributeError,
+                    format!("{} has no member '{}'", obj.type_code.name(), name)
+                ));
+            }
+        };
 
+        self.push_ref(Some(address))
     }
 
 
-    fn throw_error(&mut self, error: RuntimeError, node: &CodeNode, source: &str) -> ! {
+    /// Return the namespace of the given module.
+    /// The module's top-level code is executed only the first time it's imported.
+    fn import_module(&mut self, module_id: ModuleID) -> Result<Namespace, RuntimeError> {
+        if let Some(namespace) = self.namespaces.get(&module_id) {
+            return Ok(namespace.clone());
+        }
+
+        let module = unsafe {&*self.jit}.get_module(module_id);
+
+        let namespace = match &module.content {
+
+            ModuleContent::Source { root, .. } => {
+                // Run the module on its own scope stack so that its global ids start at 0
+                let importer_stack = std::mem::replace(&mut self.stack, ScopeStack::new());
+                self.stack.push_scope();
+
+                let result = self.run(root);
+
+                let module_stack = std::mem::replace(&mut self.stack, importer_stack);
+                result?;
+                let globals = module_stack.into_heap_index();
+
+                // Export all the module's globals
+                let namespace = Namespace {
+                    name: module.name.clone(),
+                    members: root.body_scope().get_local_symbols().iter().map(
+                        |(name, symbol)| (name.clone(), globals[symbol.local_id])
+                    ).collect(),
+                };
+
+                self.module_globals.insert(module_id, globals);
+                namespace
+            },
+
+            ModuleContent::Native { init } => {
+                // Store the native members on the heap like any other global
+                Namespace {
+                    name: module.name.clone(),
+                    members: init().into_iter().map(
+                        |(name, obj)| (name.to_string(), self.heap.allocate_object(obj))
+                    ).collect(),
+                }
+            },
+        };
+
+        self.namespaces.insert(module_id, namespace.clone());
+
+        Ok(namespace)
+    }
+
+
+    /// Report where the error happened in verbose mode, and leave the frames from the given height of the call stack.
+    /// Return the error, for the caller of the frames
+    fn throw_error(&mut self, error: RuntimeError, base: usize, code_object: &CodeObject, pc: usize) -> RuntimeError {
         if self.verbose {
-            eprintln!("Error at line {}:\n\n{}", node.syntax_node.get_line(), get_lines(source, node.syntax_node.get_line(), 2));
+            let module = unsafe {&*self.jit}.get_module(code_object.module_id);
+            let line = code_object.get_line(pc);
+            eprintln!("Error in {} at line {}:\n\n{}", module.path.display(), line, get_lines(module.get_source(), line, 2));
         }
 
-        error.raise();
+        if let Some(frame) = self.call_stack.get(base) {
+            self.stack.pop_scopes_to(frame.scope_depth);
+        }
+        self.call_stack.truncate(base);
+        error
     }
 
 
     /// Return the referenced object if the given object is a reference.
     /// Return the object itself otherwise
-    fn deref_if_ref<'a>(&'a self, object_ref: &'a Object) -> &Object {
+    fn deref_if_ref<'a>(&'a self, object_ref: &'a Object) -> &'a Object {
         match object_ref {
             Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => {
                 unsafe {
@@ -548,7 +923,26 @@ impl Vm {
     }
 
 
-    fn assign_ref(&mut self, target_ref: &mut Object, value: Object) -> Result<(), RuntimeError> {
+    /// Assign the value to the element of the referenced list found by following the indices
+    fn assign_element(&mut self, target_ref: &Object, indices: &[Object], value: Object) -> Result<(), RuntimeError> {
+        let mut element = match target_ref {
+            Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => unsafe { &mut **object_ptr },
+            _ => return Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                "Cannot assign to non-reference".to_owned(),
+            )),
+        };
+
+        for index in indices {
+            element = element.subscript_mut(self.deref_if_ref(index))?;
+        }
+
+        *element = value;
+        Ok(())
+    }
+
+
+    fn assign_ref(&mut self, target_ref: &Object, value: Object) -> Result<(), RuntimeError> {
         if let Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } = target_ref {
             unsafe {
                 **object_ptr = value;
@@ -564,3 +958,77 @@ impl Vm {
 
 }
 
+
+/// Return the instruction index the jump lands on
+fn jump_target(pc: usize, offset: JumpOffset) -> usize {
+    pc.wrapping_add_signed(offset as isize)
+}
+
+
+/// Match the arguments of a call to the parameters of the function.
+/// Return the value of each parameter, in order, and the indices of the parameters with a default value that weren't passed,
+/// which are set to None until the function initializes them.
+/// Raise a TypeError if an argument is missing, unexpected or passed twice
+fn bind_arguments(function: &CodeObject, arguments: Vec<Object>, keywords: Vec<(Rc<str>, Object)>) -> Result<(Vec<Object>, Vec<usize>), RuntimeError> {
+    let named_params = function.named_params();
+    let given = arguments.len();
+    let mut values: Vec<Option<Object>> = vec![None; named_params];
+
+    let mut arguments = arguments.into_iter();
+    for (value, argument) in values.iter_mut().zip(arguments.by_ref()) {
+        *value = Some(argument);
+    }
+    let extra: Vec<Object> = arguments.collect();
+
+    if !extra.is_empty() && !function.has_rest {
+        let expected = if function.required_params == named_params {
+            format!("{} argument{}", named_params, if named_params == 1 { "" } else { "s" })
+        } else {
+            format!("from {} to {} arguments", function.required_params, named_params)
+        };
+        return Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("{}() takes {} but {} were given", function.name, expected, given)
+        ));
+    }
+
+    // The rest parameter can't be passed by name
+    for (name, argument) in keywords {
+        match function.params[.. named_params].iter().position(|param| *param == name) {
+            Some(index) if values[index].is_some() => return Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("{}() got multiple values for argument '{}'", function.name, name)
+            )),
+            Some(index) => values[index] = Some(argument),
+            None => return Err(RuntimeError::with_message(
+                ErrorCode::TypeError,
+                format!("{}() got an unexpected keyword argument '{}'", function.name, name)
+            )),
+        }
+    }
+
+    let missing: Vec<String> = function.params[.. function.required_params].iter().zip(&values)
+        .filter(|(_, value)| value.is_none())
+        .map(|(param, _)| format!("'{}'", param))
+        .collect();
+    if !missing.is_empty() {
+        return Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("{}() missing {} required argument{}: {}", function.name, missing.len(), if missing.len() == 1 { "" } else { "s" }, missing.join(", "))
+        ));
+    }
+
+    let mut missing_args: Vec<usize> = Vec::new();
+    let mut params: Vec<Object> = values.into_iter().enumerate().map(|(index, value)| {
+        value.unwrap_or_else(|| {
+            missing_args.push(index);
+            Object::none()
+        })
+    }).collect();
+
+    if function.has_rest {
+        params.push(Object::new(TypeCode::List, Value::List(extra)));
+    }
+
+    Ok((params, missing_args))
+}
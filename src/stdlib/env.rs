@@ -0,0 +1,46 @@
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, expect_string};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Return the members of the env module
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("get", NativeFunction::new_object("get", get)),
+        ("args", NativeFunction::new_object("args", args)),
+    ]
+}
+
+
+/// Return the value of an environment variable, or None if it's not set.
+/// Requires the env capability
+fn get(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("get", args, 1)?;
+    let name = expect_string("get", &args[0])?;
+
+    vm.capabilities().check_env("get")?;
+
+    match std::env::var(name) {
+        Ok(value) => Ok(Object::new(TypeCode::String, Value::String(value.into()))),
+        Err(std::env::VarError::NotPresent) => Ok(Object::none()),
+        Err(std::env::VarError::NotUnicode(_)) => Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("get() environment variable {} is not valid UTF-8", name)
+        )),
+    }
+}
+
+
+/// Return the command line arguments passed to the program after the script path.
+/// The arguments are chosen by whoever runs the script, so no capability is required
+fn args(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("args", args, 0)?;
+
+    Ok(Object::new(
+        TypeCode::List,
+        Value::List(vm.script_args().iter().map(
+            |arg| Object::new(TypeCode::String, Value::String(arg.as_str().into()))
+        ).collect())
+    ))
+}
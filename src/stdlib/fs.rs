@@ -0,0 +1,116 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, expect_string};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Return the members of the fs module.
+/// Every function is gated by the read or write capability of the VM
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("read", NativeFunction::new_object("read", read)),
+        ("write", NativeFunction::new_object("write", write)),
+        ("append", NativeFunction::new_object("append", append)),
+        ("exists", NativeFunction::new_object("exists", exists)),
+        ("list_dir", NativeFunction::new_object("list_dir", list_dir)),
+    ]
+}
+
+
+fn io_error(function: &str, path: &Path, error: std::io::Error) -> RuntimeError {
+    RuntimeError::with_message(
+        ErrorCode::IOError,
+        format!("{}() failed on \"{}\": {}", function, path.display(), error)
+    )
+}
+
+
+fn new_string(value: String) -> Object {
+    Object::new(TypeCode::String, Value::String(value.into()))
+}
+
+
+/// Return the content of a text file
+fn read(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("read", args, 1)?;
+    let path = Path::new(expect_string("read", &args[0])?);
+
+    vm.capabilities().check_read("read", path)?;
+
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(new_string(content)),
+        Err(error) => Err(io_error("read", path, error)),
+    }
+}
+
+
+/// Write the string to a file, replacing its content
+fn write(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("write", args, 2)?;
+    let path = Path::new(expect_string("write", &args[0])?);
+    let content = expect_string("write", &args[1])?;
+
+    vm.capabilities().check_write("write", path)?;
+
+    match fs::write(path, content) {
+        Ok(()) => Ok(Object::none()),
+        Err(error) => Err(io_error("write", path, error)),
+    }
+}
+
+
+/// Write the string at the end of a file, creating the file if it doesn't exist
+fn append(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("append", args, 2)?;
+    let path = Path::new(expect_string("append", &args[0])?);
+    let content = expect_string("append", &args[1])?;
+
+    vm.capabilities().check_write("append", path)?;
+
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()));
+
+    match result {
+        Ok(()) => Ok(Object::none()),
+        Err(error) => Err(io_error("append", path, error)),
+    }
+}
+
+
+fn exists(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("exists", args, 1)?;
+    let path = Path::new(expect_string("exists", &args[0])?);
+
+    vm.capabilities().check_read("exists", path)?;
+
+    Ok(Object::new(TypeCode::Bool, Value::Bool(path.exists())))
+}
+
+
+/// Return the sorted list of the names of the entries in a directory
+fn list_dir(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("list_dir", args, 1)?;
+    let path = Path::new(expect_string("list_dir", &args[0])?);
+
+    vm.capabilities().check_read("list_dir", path)?;
+
+    let mut names: Vec<String> = Vec::new();
+    let entries = fs::read_dir(path).map_err(|error| io_error("list_dir", path, error))?;
+    for entry in entries {
+        let entry = entry.map_err(|error| io_error("list_dir", path, error))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+
+    Ok(Object::new(
+        TypeCode::List,
+        Value::List(names.into_iter().map(new_string).collect())
+    ))
+}
@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use crate::lang::object::{Object, OpResult};
+use crate::lang::native::NativeFunction;
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Return the members of the io module
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("print", NativeFunction::new_object("print", print)),
+    ]
+}
+
+
+/// Write the arguments to the standard output, separated by spaces and followed by a newline
+fn print(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    let line = args.iter().map(Object::to_string).collect::<Vec<String>>().join(" ");
+
+    match writeln!(std::io::stdout(), "{}", line) {
+        Ok(()) => Ok(Object::none()),
+        Err(error) => Err(RuntimeError::with_message(
+            ErrorCode::IOError,
+            format!("print() could not write to the standard output: {}", error)
+        )),
+    }
+}
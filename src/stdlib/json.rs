@@ -0,0 +1,431 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, expect_arg_range, expect_string, wrong_arg_type};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Maximum nesting of lists accepted by the parser, so that untrusted input cannot overflow the native stack
+const MAX_DEPTH: usize = 512;
+
+/// Maximum number of spaces per level when pretty printing, so that a large indent raises an error instead of exhausting the memory
+const MAX_INDENT: usize = 64;
+
+
+/// Return the members of the json module
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("parse", NativeFunction::new_object("parse", parse)),
+        ("stringify", NativeFunction::new_object("stringify", stringify)),
+    ]
+}
+
+
+/// Parse a JSON document into an object
+fn parse(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("parse", args, 1)?;
+    let text = expect_string("parse", &args[0])?;
+
+    let mut parser = Parser::new(text);
+    parser.skip_whitespace();
+    let value = parser.parse_value(0)?;
+    parser.skip_whitespace();
+
+    if let Some(ch) = parser.peek() {
+        return Err(parser.error(&format!("unexpected '{}' after the end of the document", ch)));
+    }
+
+    Ok(value)
+}
+
+
+/// Convert an object to a JSON string.
+/// If indent is given and not 0, pretty print with the given number of spaces per level
+fn stringify(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_range("stringify", args, 1, 2)?;
+
+    let indent = match args.get(1) {
+        None | Some(Object { type_code: TypeCode::None, .. }) => None,
+        Some(Object { type_code: TypeCode::Int, value: Value::Int(indent), .. }) => {
+            match usize::try_from(*indent) {
+                // Like None, an indent of 0 gives the compact output
+                Ok(0) => None,
+                Ok(indent) if indent <= MAX_INDENT => Some(indent),
+                Ok(_) => return Err(RuntimeError::with_message(
+                    ErrorCode::ValueError,
+                    format!("stringify() indent cannot be more than {}, got {}", MAX_INDENT, indent)
+                )),
+                Err(_) => return Err(RuntimeError::with_message(
+                    ErrorCode::ValueError,
+                    format!("stringify() indent cannot be negative, got {}", indent)
+                )),
+            }
+        },
+        Some(arg) => return Err(wrong_arg_type("stringify", arg, &[TypeCode::Int, TypeCode::None])),
+    };
+
+    let mut output = String::new();
+    write_value(&args[0], indent, 0, &mut output)?;
+
+    Ok(Object::new(TypeCode::String, Value::String(output.into())))
+}
+
+
+fn write_value(obj: &Object, indent: Option<usize>, depth: usize, output: &mut String) -> Result<(), RuntimeError> {
+    match obj {
+
+        Object { type_code: TypeCode::None, .. } => output.push_str("null"),
+
+        Object { type_code: TypeCode::Bool, value: Value::Bool(value), .. } => {
+            output.push_str(if *value { "true" } else { "false" });
+        },
+
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => {
+            output.push_str(&value.to_string());
+        },
+
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => {
+            if !value.is_finite() {
+                return Err(RuntimeError::with_message(
+                    ErrorCode::ValueError,
+                    format!("stringify() cannot represent {} in JSON", value)
+                ));
+            }
+            // Keep a fractional part so that the value is parsed back as a Float
+            if value.fract() == 0.0 && value.abs() < 1e16 {
+                output.push_str(&format!("{:.1}", value));
+            } else {
+                output.push_str(&value.to_string());
+            }
+        },
+
+        Object { type_code: TypeCode::String, value: Value::String(value), .. } => {
+            write_string(value, output);
+        },
+
+        Object { type_code: TypeCode::List, value: Value::List(elements), .. } => {
+            if elements.is_empty() {
+                output.push_str("[]");
+                return Ok(());
+            }
+
+            output.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_newline(indent, depth + 1, output)?;
+                write_value(element, indent, depth + 1, output)?;
+            }
+            write_newline(indent, depth, output)?;
+            output.push(']');
+        },
+
+        Object { type_code: TypeCode::Ref, value: Value::Ref(object_ptr), .. } => {
+            write_value(unsafe { &**object_ptr }, indent, depth, output)?;
+        },
+
+        _ => return Err(RuntimeError::with_message(
+            ErrorCode::TypeError,
+            format!("stringify() cannot convert {} to JSON", obj.type_code.name())
+        )),
+    }
+
+    Ok(())
+}
+
+
+/// Start a new line at the given depth if pretty printing
+fn write_newline(indent: Option<usize>, depth: usize, output: &mut String) -> Result<(), RuntimeError> {
+    if let Some(indent) = indent {
+        let width = indent.checked_mul(depth).ok_or_else(|| RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("stringify() cannot indent {} levels deep", depth)
+        ))?;
+        output.push('\n');
+        output.push_str(&" ".repeat(width));
+    }
+    Ok(())
+}
+
+
+pub fn write_string(value: &str, output: &mut String) {
+    output.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{08}' => output.push_str("\\b"),
+            '\u{0c}' => output.push_str("\\f"),
+            ch if (ch as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+}
+
+
+/// A recursive descent JSON parser that keeps track of the position in the document for error messages
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+
+impl Parser<'_> {
+
+    fn new(text: &str) -> Parser<'_> {
+        Parser {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+
+    fn error(&self, message: &str) -> RuntimeError {
+        RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("parse() invalid JSON at line {}, column {}: {}", self.line, self.column, message)
+        )
+    }
+
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.next();
+        }
+    }
+
+
+    fn expect_char(&mut self, expected: char) -> Result<(), RuntimeError> {
+        match self.peek() {
+            Some(ch) if ch == expected => {
+                self.next();
+                Ok(())
+            },
+            Some(ch) => Err(self.error(&format!("expected '{}', found '{}'", expected, ch))),
+            None => Err(self.error(&format!("expected '{}', found the end of the document", expected))),
+        }
+    }
+
+
+    fn parse_value(&mut self, depth: usize) -> OpResult {
+        match self.peek() {
+            Some('n') => self.parse_literal("null", Object::none()),
+            Some('t') => self.parse_literal("true", Object::new(TypeCode::Bool, Value::Bool(true))),
+            Some('f') => self.parse_literal("false", Object::new(TypeCode::Bool, Value::Bool(false))),
+            Some('"') => Ok(Object::new(TypeCode::String, Value::String(self.parse_string()?.into()))),
+            Some('[') => self.parse_list(depth),
+            Some('-' | '0'..='9') => self.parse_number(),
+            // There is no map type to represent JSON objects yet
+            Some('{') => Err(self.error("JSON objects are not supported yet")),
+            Some(ch) => Err(self.error(&format!("unexpected '{}'", ch))),
+            None => Err(self.error("unexpected end of the document")),
+        }
+    }
+
+
+    fn parse_literal(&mut self, literal: &str, value: Object) -> OpResult {
+        for expected in literal.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("invalid literal, expected \"{}\"", literal)));
+            }
+            self.next();
+        }
+        Ok(value)
+    }
+
+
+    fn parse_list(&mut self, depth: usize) -> OpResult {
+        if depth >= MAX_DEPTH {
+            return Err(self.error(&format!("lists are nested more than {} levels deep", MAX_DEPTH)));
+        }
+
+        self.expect_char('[')?;
+        self.skip_whitespace();
+
+        let mut elements: Vec<Object> = Vec::new();
+
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Object::new(TypeCode::List, Value::List(elements)));
+        }
+
+        loop {
+            self.skip_whitespace();
+            elements.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => break,
+                Some(ch) => return Err(self.error(&format!("expected ',' or ']' in list, found '{}'", ch))),
+                None => return Err(self.error("unterminated list")),
+            }
+        }
+
+        Ok(Object::new(TypeCode::List, Value::List(elements)))
+    }
+
+
+    fn parse_string(&mut self) -> Result<String, RuntimeError> {
+        self.expect_char('"')?;
+
+        let mut value = String::new();
+
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => {
+                    let ch = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{08}',
+                        Some('f') => '\u{0c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode_escape()?,
+                        Some(ch) => return Err(self.error(&format!("invalid escape sequence '\\{}'", ch))),
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    value.push(ch);
+                },
+                Some(ch) if (ch as u32) < 0x20 => {
+                    return Err(self.error("control characters must be escaped in strings"));
+                },
+                Some(ch) => value.push(ch),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        Ok(value)
+    }
+
+
+    fn parse_hex4(&mut self) -> Result<u32, RuntimeError> {
+        let mut code: u32 = 0;
+        for _ in 0..4 {
+            match self.next().and_then(|ch| ch.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error("expected 4 hexadecimal digits in unicode escape")),
+            }
+        }
+        Ok(code)
+    }
+
+
+    /// Parse the digits of a \u escape, combining UTF-16 surrogate pairs
+    fn parse_unicode_escape(&mut self) -> Result<char, RuntimeError> {
+        let high = self.parse_hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err(self.error("expected a low surrogate after a high surrogate"));
+            }
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("invalid low surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+
+    /// Parse a number. Numbers without a fraction or exponent are Ints, unless they don't fit
+    fn parse_number(&mut self) -> OpResult {
+        let mut text = String::new();
+        let mut is_float = false;
+
+        if self.peek() == Some('-') {
+            text.push('-');
+            self.next();
+        }
+
+        match self.peek() {
+            Some('0') => {
+                text.push('0');
+                self.next();
+                if let Some('0'..='9') = self.peek() {
+                    return Err(self.error("leading zeros are not allowed in numbers"));
+                }
+            },
+            Some('1'..='9') => self.push_digits(&mut text),
+            _ => return Err(self.error("expected a digit")),
+        }
+
+        if self.peek() == Some('.') {
+            is_float = true;
+            text.push('.');
+            self.next();
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected a digit after the decimal point"));
+            }
+            self.push_digits(&mut text);
+        }
+
+        if let Some('e' | 'E') = self.peek() {
+            is_float = true;
+            text.push('e');
+            self.next();
+            if let Some(sign @ ('+' | '-')) = self.peek() {
+                text.push(sign);
+                self.next();
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+            self.push_digits(&mut text);
+        }
+
+        if !is_float {
+            if let Ok(value) = text.parse::<i64>() {
+                return Ok(Object::new(TypeCode::Int, Value::Int(value)));
+            }
+        }
+
+        // The text is valid JSON number syntax, which is always a valid float, but it may be too large for one
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Object::new(TypeCode::Float, Value::Float(value))),
+            _ => Err(self.error(&format!("number {} is too large", text))),
+        }
+    }
+
+
+    fn push_digits(&mut self, text: &mut String) {
+        while let Some(ch @ '0'..='9') = self.peek() {
+            text.push(ch);
+            self.next();
+        }
+    }
+
+}
@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, wrong_arg_type};
+use crate::runtime::error_codes::RuntimeError;
+use crate::runtime::vm::Vm;
+
+
+/// Return the members of the list module.
+/// The functions passed to them are called with one element at a time
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("map", NativeFunction::new_object("map", map)),
+        ("filter", NativeFunction::new_object("filter", filter)),
+        ("sort", NativeFunction::new_object("sort", sort)),
+        ("unique", NativeFunction::new_object("unique", unique)),
+    ]
+}
+
+
+/// Return the elements of the list argument, or raise a TypeError if it's not a list
+fn expect_list<'a>(function: &str, arg: &'a Object) -> Result<&'a [Object], RuntimeError> {
+    match arg {
+        Object { type_code: TypeCode::List, value: Value::List(elements), .. } => Ok(elements),
+        _ => Err(wrong_arg_type(function, arg, &[TypeCode::List])),
+    }
+}
+
+
+/// Return a new list with the results of calling the function on each element of the list
+fn map(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("map", args, 2)?;
+    let elements = expect_list("map", &args[0])?;
+
+    let mut results: Vec<Object> = Vec::with_capacity(elements.len());
+    for element in elements {
+        results.push(vm.call(&args[1], vec![element.clone()])?);
+    }
+
+    Ok(Object::new(TypeCode::List, Value::List(results)))
+}
+
+
+/// Return a new list with the elements of the list for which the function returns a true value
+fn filter(vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("filter", args, 2)?;
+    let elements = expect_list("filter", &args[0])?;
+
+    let mut results: Vec<Object> = Vec::new();
+    for element in elements {
+        if vm.call(&args[1], vec![element.clone()])?.to_bool()? {
+            results.push(element.clone());
+        }
+    }
+
+    Ok(Object::new(TypeCode::List, Value::List(results)))
+}
+
+
+/// Return a new list with the elements of the list in ascending order.
+/// The sort is stable. Unordered elements such as NaN count as equal to the others, so the list may not be fully sorted around them
+fn sort(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("sort", args, 1)?;
+    let elements = expect_list("sort", &args[0])?;
+
+    Ok(Object::new(TypeCode::List, Value::List(merge_sort(elements.to_vec())?)))
+}
+
+
+/// Sort the elements with a merge sort, which stops at the first pair of elements that can't be compared
+fn merge_sort(mut elements: Vec<Object>) -> Result<Vec<Object>, RuntimeError> {
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+
+    let right = merge_sort(elements.split_off(elements.len() / 2))?;
+    let left = merge_sort(elements)?;
+
+    let mut merged: Vec<Object> = Vec::with_capacity(left.len() + right.len());
+    let mut right = right.into_iter().peekable();
+    for element in left {
+        // An element of the right half only goes first if it's strictly less, so that the sort is stable
+        while let Some(next) = right.peek() {
+            if Object::compare(next, &element)? != Some(Ordering::Less) {
+                break;
+            }
+            merged.push(right.next().unwrap());
+        }
+        merged.push(element);
+    }
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+
+/// Return a new list with the elements of the list that are not equal to a previous element
+fn unique(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("unique", args, 1)?;
+    let elements = expect_list("unique", &args[0])?;
+
+    // The elements kept so far, grouped by hash
+    let mut kept: HashMap<u64, Vec<&Object>> = HashMap::new();
+    let mut results: Vec<Object> = Vec::new();
+    for element in elements {
+        let same_hash = kept.entry(Object::hash(element)?).or_default();
+        if !same_hash.iter().any(|other| Object::eq(element, other)) {
+            same_hash.push(element);
+            results.push(element.clone());
+        }
+    }
+
+    Ok(Object::new(TypeCode::List, Value::List(results)))
+}
@@ -0,0 +1,254 @@
+use std::num::IntErrorKind;
+
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, expect_min_arg_count, wrong_arg_type};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Return the members of the math module
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("pi", Object::new(TypeCode::Float, Value::Float(std::f64::consts::PI))),
+        ("sqrt", NativeFunction::new_object("sqrt", sqrt)),
+        ("pow", NativeFunction::new_object("pow", pow)),
+        ("floor", NativeFunction::new_object("floor", floor)),
+        ("ceil", NativeFunction::new_object("ceil", ceil)),
+        ("abs", NativeFunction::new_object("abs", abs)),
+        ("min", NativeFunction::new_object("min", min)),
+        ("max", NativeFunction::new_object("max", max)),
+        ("sin", NativeFunction::new_object("sin", sin)),
+        ("cos", NativeFunction::new_object("cos", cos)),
+        ("int", NativeFunction::new_object("int", int)),
+        ("float", NativeFunction::new_object("float", float)),
+    ]
+}
+
+
+/// Convert a numeric argument to a float
+fn to_float(function: &str, arg: &Object) -> Result<f64, RuntimeError> {
+    match arg {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => Ok(*value as f64),
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => Ok(*value),
+        _ => Err(wrong_arg_type(function, arg, &[TypeCode::Int, TypeCode::Float])),
+    }
+}
+
+
+/// Convert an integral float to an Int.
+/// Raise an OverflowError if the float is out of the Int range.
+fn float_to_int(function: &str, value: f64) -> Result<i64, RuntimeError> {
+    if value.is_nan() {
+        return Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("{}() cannot convert NaN to Int", function)
+        ));
+    }
+
+    // i64::MAX as f64 rounds up to 2^63, which is out of range
+    if value < i64::MIN as f64 || value >= i64::MAX as f64 {
+        return Err(RuntimeError::with_message(
+            ErrorCode::OverflowError,
+            format!("{}() cannot convert {} to Int: integer overflow", function, value)
+        ));
+    }
+
+    Ok(value as i64)
+}
+
+
+fn sqrt(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("sqrt", args, 1)?;
+    let value = to_float("sqrt", &args[0])?;
+
+    if value < 0.0 {
+        return Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("sqrt() of negative number {}", value)
+        ));
+    }
+
+    Ok(Object::new(TypeCode::Float, Value::Float(value.sqrt())))
+}
+
+
+fn pow(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("pow", args, 2)?;
+
+    match (&args[0], &args[1]) {
+
+        // Integer powers with a non-negative exponent stay integers
+        (Object { type_code: TypeCode::Int, value: Value::Int(base), .. }, Object { type_code: TypeCode::Int, value: Value::Int(exponent), .. })
+         if *exponent >= 0 => {
+            match u32::try_from(*exponent).ok().and_then(|exponent| base.checked_pow(exponent)) {
+                Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                None => Err(RuntimeError::with_message(
+                    ErrorCode::OverflowError,
+                    format!("pow() cannot raise {} to the power of {}: integer overflow", base, exponent)
+                )),
+            }
+        },
+
+        (base, exponent) => {
+            let base = to_float("pow", base)?;
+            let exponent = to_float("pow", exponent)?;
+            Ok(Object::new(TypeCode::Float, Value::Float(base.powf(exponent))))
+        },
+    }
+}
+
+
+fn floor(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("floor", args, 1)?;
+
+    match &args[0] {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => {
+            Ok(Object::new(TypeCode::Int, Value::Int(*value)))
+        },
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => {
+            Ok(Object::new(TypeCode::Int, Value::Int(float_to_int("floor", value.floor())?)))
+        },
+        arg => Err(wrong_arg_type("floor", arg, &[TypeCode::Int, TypeCode::Float])),
+    }
+}
+
+
+fn ceil(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("ceil", args, 1)?;
+
+    match &args[0] {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => {
+            Ok(Object::new(TypeCode::Int, Value::Int(*value)))
+        },
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => {
+            Ok(Object::new(TypeCode::Int, Value::Int(float_to_int("ceil", value.ceil())?)))
+        },
+        arg => Err(wrong_arg_type("ceil", arg, &[TypeCode::Int, TypeCode::Float])),
+    }
+}
+
+
+fn abs(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("abs", args, 1)?;
+
+    match &args[0] {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => {
+            match value.checked_abs() {
+                Some(result) => Ok(Object::new(TypeCode::Int, Value::Int(result))),
+                None => Err(RuntimeError::with_message(
+                    ErrorCode::OverflowError,
+                    format!("abs() of {}: integer overflow", value)
+                )),
+            }
+        },
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => {
+            Ok(Object::new(TypeCode::Float, Value::Float(value.abs())))
+        },
+        arg => Err(wrong_arg_type("abs", arg, &[TypeCode::Int, TypeCode::Float])),
+    }
+}
+
+
+/// Return the element that is preferred over all the others.
+/// The elements are either the arguments or the elements of a single list argument.
+fn select(function: &str, args: &[Object], prefer: fn(&Object, &Object) -> OpResult) -> OpResult {
+    expect_min_arg_count(function, args, 1)?;
+
+    let elements: &[Object] = match args {
+        [Object { type_code: TypeCode::List, value: Value::List(elements), .. }] => elements,
+        _ => args,
+    };
+
+    let mut selected = if let Some(first) = elements.first() {
+        first
+    } else {
+        return Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("{}() of an empty list", function)
+        ));
+    };
+
+    for element in &elements[1..] {
+        if let Value::Bool(true) = prefer(element, selected)?.value {
+            selected = element;
+        }
+    }
+
+    Ok(selected.clone())
+}
+
+
+fn min(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    select("min", args, Object::less)
+}
+
+
+fn max(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    select("max", args, Object::greater)
+}
+
+
+fn sin(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("sin", args, 1)?;
+    let value = to_float("sin", &args[0])?;
+    Ok(Object::new(TypeCode::Float, Value::Float(value.sin())))
+}
+
+
+fn cos(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("cos", args, 1)?;
+    let value = to_float("cos", &args[0])?;
+    Ok(Object::new(TypeCode::Float, Value::Float(value.cos())))
+}
+
+
+/// Convert the argument to an Int, truncating floats towards zero
+fn int(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("int", args, 1)?;
+
+    let value = match &args[0] {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => *value,
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => float_to_int("int", value.trunc())?,
+        Object { type_code: TypeCode::Bool, value: Value::Bool(value), .. } => *value as i64,
+        Object { type_code: TypeCode::String, value: Value::String(value), .. } => {
+            match value.trim().parse::<i64>() {
+                Ok(value) => value,
+                Err(error) if matches!(error.kind(), IntErrorKind::PosOverflow | IntErrorKind::NegOverflow) => {
+                    return Err(RuntimeError::with_message(
+                        ErrorCode::OverflowError,
+                        format!("int() cannot convert \"{}\" to Int: integer overflow", value)
+                    ));
+                },
+                Err(_) => {
+                    return Err(RuntimeError::with_message(
+                        ErrorCode::ValueError,
+                        format!("int() cannot convert \"{}\" to Int", value)
+                    ));
+                },
+            }
+        },
+        arg => return Err(wrong_arg_type("int", arg, &[TypeCode::Int, TypeCode::Float, TypeCode::Bool, TypeCode::String])),
+    };
+
+    Ok(Object::new(TypeCode::Int, Value::Int(value)))
+}
+
+
+fn float(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("float", args, 1)?;
+
+    let value = match &args[0] {
+        Object { type_code: TypeCode::Int, value: Value::Int(value), .. } => *value as f64,
+        Object { type_code: TypeCode::Float, value: Value::Float(value), .. } => *value,
+        Object { type_code: TypeCode::Bool, value: Value::Bool(value), .. } => *value as i64 as f64,
+        Object { type_code: TypeCode::String, value: Value::String(value), .. } => {
+            value.trim().parse::<f64>().map_err(|_| RuntimeError::with_message(
+                ErrorCode::ValueError,
+                format!("float() cannot convert \"{}\" to Float", value)
+            ))?
+        },
+        arg => return Err(wrong_arg_type("float", arg, &[TypeCode::Int, TypeCode::Float, TypeCode::Bool, TypeCode::String])),
+    };
+
+    Ok(Object::new(TypeCode::Float, Value::Float(value)))
+}
@@ -0,0 +1,29 @@
+pub mod env;
+pub mod fs;
+pub mod io;
+pub mod json;
+pub mod list;
+pub mod math;
+pub mod string;
+
+use crate::lang::object::Object;
+
+
+/// Returns the members of a built-in module
+pub type NativeModuleInit = fn() -> Vec<(&'static str, Object)>;
+
+
+/// Return the initializer of the built-in module with the given name, if it exists.
+/// Built-in modules take precedence over source files with the same name.
+pub fn get_builtin_module(name: &str) -> Option<NativeModuleInit> {
+    match name {
+        "env" => Some(env::members),
+        "fs" => Some(fs::members),
+        "io" => Some(io::members),
+        "json" => Some(json::members),
+        "list" => Some(list::members),
+        "math" => Some(math::members),
+        "string" => Some(string::members),
+        _ => None,
+    }
+}
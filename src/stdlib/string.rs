@@ -0,0 +1,270 @@
+use crate::lang::object::{Object, OpResult, TypeCode, Value};
+use crate::lang::native::{NativeFunction, expect_arg_count, expect_arg_range, expect_int, expect_string, wrong_arg_type};
+use crate::runtime::error_codes::{ErrorCode, RuntimeError};
+use crate::runtime::vm::Vm;
+
+
+/// Maximum length in bytes of a string built by repeat, so that a large count raises an error instead of exhausting the memory
+const MAX_REPEAT_LENGTH: usize = 1 << 30;
+
+
+/// Return the members of the string module.
+/// All indices and lengths count Unicode characters, not bytes
+pub fn members() -> Vec<(&'static str, Object)> {
+    vec![
+        ("len", NativeFunction::new_object("len", len)),
+        ("chars", NativeFunction::new_object("chars", chars)),
+        ("slice", NativeFunction::new_object("slice", slice)),
+        ("split", NativeFunction::new_object("split", split)),
+        ("join", NativeFunction::new_object("join", join)),
+        ("trim", NativeFunction::new_object("trim", trim)),
+        ("replace", NativeFunction::new_object("replace", replace)),
+        ("starts_with", NativeFunction::new_object("starts_with", starts_with)),
+        ("ends_with", NativeFunction::new_object("ends_with", ends_with)),
+        ("contains", NativeFunction::new_object("contains", contains)),
+        ("find", NativeFunction::new_object("find", find)),
+        ("upper", NativeFunction::new_object("upper", upper)),
+        ("lower", NativeFunction::new_object("lower", lower)),
+        ("repeat", NativeFunction::new_object("repeat", repeat)),
+        ("char_code", NativeFunction::new_object("char_code", char_code)),
+        ("from_char_code", NativeFunction::new_object("from_char_code", from_char_code)),
+    ]
+}
+
+
+fn new_string(value: String) -> Object {
+    Object::new(TypeCode::String, Value::String(value.into()))
+}
+
+
+fn new_int(value: usize) -> Object {
+    Object::new(TypeCode::Int, Value::Int(value as i64))
+}
+
+
+fn new_bool(value: bool) -> Object {
+    Object::new(TypeCode::Bool, Value::Bool(value))
+}
+
+
+fn new_string_list(strings: impl Iterator<Item = String>) -> Object {
+    Object::new(TypeCode::List, Value::List(strings.map(new_string).collect()))
+}
+
+
+/// Return the number of characters in the string
+fn len(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("len", args, 1)?;
+    let string = expect_string("len", &args[0])?;
+    Ok(new_int(string.chars().count()))
+}
+
+
+/// Return the list of the characters in the string, each as a string of length 1
+fn chars(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("chars", args, 1)?;
+    let string = expect_string("chars", &args[0])?;
+    Ok(new_string_list(string.chars().map(String::from)))
+}
+
+
+/// Convert a character index to a position in 0..=length.
+/// Negative indices count from the end of the string and out of range indices are clamped
+fn clamp_index(index: i64, length: usize) -> usize {
+    if index < 0 {
+        length.saturating_sub(index.unsigned_abs() as usize)
+    } else {
+        (index as usize).min(length)
+    }
+}
+
+
+/// Return the characters from start up to, but excluding, end.
+/// If end is omitted, slice up to the end of the string
+fn slice(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_range("slice", args, 2, 3)?;
+    let string = expect_string("slice", &args[0])?;
+    let length = string.chars().count();
+
+    let start = clamp_index(expect_int("slice", &args[1])?, length);
+    let end = match args.get(2) {
+        Some(end) => clamp_index(expect_int("slice", end)?, length),
+        None => length,
+    };
+
+    if start >= end {
+        return Ok(new_string(String::new()));
+    }
+
+    Ok(new_string(string.chars().skip(start).take(end - start).collect()))
+}
+
+
+/// Split the string on a separator, or on whitespace if no separator is given
+fn split(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_range("split", args, 1, 2)?;
+    let string = expect_string("split", &args[0])?;
+
+    match args.get(1) {
+        None => Ok(new_string_list(string.split_whitespace().map(String::from))),
+        Some(separator) => {
+            let separator = expect_string("split", separator)?;
+            if separator.is_empty() {
+                return Err(RuntimeError::with_message(
+                    ErrorCode::ValueError,
+                    "split() separator cannot be empty".to_string()
+                ));
+            }
+            Ok(new_string_list(string.split(separator).map(String::from)))
+        },
+    }
+}
+
+
+/// Concatenate a list of strings, inserting the separator between them
+fn join(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("join", args, 2)?;
+
+    let elements = match &args[0] {
+        Object { type_code: TypeCode::List, value: Value::List(elements), .. } => elements,
+        arg => return Err(wrong_arg_type("join", arg, &[TypeCode::List])),
+    };
+    let separator = expect_string("join", &args[1])?;
+
+    let strings = elements.iter()
+        .map(|element| expect_string("join", element))
+        .collect::<Result<Vec<&str>, RuntimeError>>()?;
+
+    Ok(new_string(strings.join(separator)))
+}
+
+
+/// Remove leading and trailing whitespace
+fn trim(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("trim", args, 1)?;
+    let string = expect_string("trim", &args[0])?;
+    Ok(new_string(string.trim().to_string()))
+}
+
+
+/// Replace all the occurrences of a substring
+fn replace(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("replace", args, 3)?;
+    let string = expect_string("replace", &args[0])?;
+    let from = expect_string("replace", &args[1])?;
+    let to = expect_string("replace", &args[2])?;
+
+    if from.is_empty() {
+        return Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            "replace() pattern cannot be empty".to_string()
+        ));
+    }
+
+    Ok(new_string(string.replace(from, to)))
+}
+
+
+fn starts_with(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("starts_with", args, 2)?;
+    let string = expect_string("starts_with", &args[0])?;
+    let prefix = expect_string("starts_with", &args[1])?;
+    Ok(new_bool(string.starts_with(prefix)))
+}
+
+
+fn ends_with(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("ends_with", args, 2)?;
+    let string = expect_string("ends_with", &args[0])?;
+    let suffix = expect_string("ends_with", &args[1])?;
+    Ok(new_bool(string.ends_with(suffix)))
+}
+
+
+fn contains(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("contains", args, 2)?;
+    let string = expect_string("contains", &args[0])?;
+    let substring = expect_string("contains", &args[1])?;
+    Ok(new_bool(string.contains(substring)))
+}
+
+
+/// Return the character index of the first occurrence of the substring, or None if it's not found
+fn find(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("find", args, 2)?;
+    let string = expect_string("find", &args[0])?;
+    let substring = expect_string("find", &args[1])?;
+
+    match string.find(substring) {
+        // Convert the byte offset to a character index
+        Some(byte_index) => Ok(new_int(string[..byte_index].chars().count())),
+        None => Ok(Object::none()),
+    }
+}
+
+
+fn upper(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("upper", args, 1)?;
+    let string = expect_string("upper", &args[0])?;
+    Ok(new_string(string.to_uppercase()))
+}
+
+
+fn lower(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("lower", args, 1)?;
+    let string = expect_string("lower", &args[0])?;
+    Ok(new_string(string.to_lowercase()))
+}
+
+
+/// Return the string repeated n times
+fn repeat(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("repeat", args, 2)?;
+    let string = expect_string("repeat", &args[0])?;
+    let count = expect_int("repeat", &args[1])?;
+
+    let count = usize::try_from(count).map_err(|_| RuntimeError::with_message(
+        ErrorCode::ValueError,
+        format!("repeat() count cannot be negative, got {}", count)
+    ))?;
+
+    if string.len().checked_mul(count).is_none_or(|length| length > MAX_REPEAT_LENGTH) {
+        return Err(RuntimeError::with_message(
+            ErrorCode::OverflowError,
+            format!("repeat() of a {}-byte string {} times is longer than the maximum of {} bytes", string.len(), count, MAX_REPEAT_LENGTH)
+        ));
+    }
+
+    Ok(new_string(string.repeat(count)))
+}
+
+
+/// Return the Unicode code point of a single character string
+fn char_code(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("char_code", args, 1)?;
+    let string = expect_string("char_code", &args[0])?;
+
+    let mut chars = string.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(Object::new(TypeCode::Int, Value::Int(ch as i64))),
+        _ => Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("char_code() expected a single character, got a string of length {}", string.chars().count())
+        )),
+    }
+}
+
+
+/// Return the single character string with the given Unicode code point
+fn from_char_code(_vm: &mut Vm, args: &[Object]) -> OpResult {
+    expect_arg_count("from_char_code", args, 1)?;
+    let code = expect_int("from_char_code", &args[0])?;
+
+    match u32::try_from(code).ok().and_then(char::from_u32) {
+        Some(ch) => Ok(new_string(ch.to_string())),
+        None => Err(RuntimeError::with_message(
+            ErrorCode::ValueError,
+            format!("from_char_code() {} is not a valid Unicode code point", code)
+        )),
+    }
+}
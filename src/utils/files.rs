@@ -1,12 +1,24 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 
 pub fn load_file(path: &PathBuf) -> String {
     let mut contents = fs::read_to_string(path)
-        .expect(format!("Could not read file: {}", path.display()).as_str());
+        .unwrap_or_else(|error| panic!("Could not read file: {}: {:?}", path.display(), error));
     // Add a newline to the end of the script so that the last line is tokenized
     contents.push('\n');
     contents
 }
 
+
+
+pub fn load_bytes(path: &Path) -> Vec<u8> {
+    fs::read(path)
+        .unwrap_or_else(|error| panic!("Could not read file: {}: {:?}", path.display(), error))
+}
+
+
+pub fn save_bytes(path: &Path, bytes: &[u8]) {
+    fs::write(path, bytes)
+        .unwrap_or_else(|error| panic!("Could not write file: {}: {:?}", path.display(), error))
+}
@@ -0,0 +1,25 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+
+/// Stores a single shared copy of each distinct string
+#[derive(Default)]
+pub struct Interner {
+    strings: HashSet<Rc<str>>,
+}
+
+
+impl Interner {
+
+    /// Return the shared copy of the string, creating it the first time the string is seen
+    pub fn intern(&mut self, string: &str) -> Rc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return Rc::clone(interned);
+        }
+
+        let interned: Rc<str> = Rc::from(string);
+        self.strings.insert(Rc::clone(&interned));
+        interned
+    }
+
+}
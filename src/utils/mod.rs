@@ -1,3 +1,4 @@
 pub mod string;
 pub mod files;
 pub mod byte_code;
+pub mod interner;
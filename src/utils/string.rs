@@ -6,7 +6,7 @@ pub fn get_lines(string: &str, line_number: usize, radius: usize) -> String {
     for (index, line) in string.lines().enumerate() {
 
         // Skip the lines before
-        if index < line_number - radius {
+        if index < line_number.saturating_sub(radius) {
             continue;
         }
 
@@ -0,0 +1,68 @@
+mod common;
+
+use common::run_file;
+use std::path::PathBuf;
+
+
+/// Dump the syntax tree of tests/ast/<name>.ws as JSON and compare it with tests/ast/<name>.json,
+/// or compare the errors with tests/ast/<name>.err if the file doesn't parse.
+/// Set UPDATE_GOLDEN=1 to write the current output as the expected one
+fn check_golden(name: &str) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/ast");
+    let output = run_file(&dir.join(name).with_extension("ws"), &["--dump-ast", "--dump-format", "json"]);
+
+    let (actual, extension) = if output.status.success() {
+        (output.stdout, "json")
+    } else {
+        (output.stderr, "err")
+    };
+    // The quoted source lines are indented, ignore the trailing whitespace
+    let actual = String::from_utf8(actual).unwrap()
+        .lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect::<String>();
+
+    let golden = dir.join(name).with_extension(extension);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&golden, &actual).expect("Could not write the golden file");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&golden)
+        .unwrap_or_else(|_| panic!("Could not read {}, the output was:\n{}", golden.display(), actual));
+    assert_eq!(actual, expected, "The output of {} doesn't match {}", name, golden.display());
+}
+
+
+#[test]
+fn binary_operators_follow_precedence() {
+    check_golden("precedence");
+}
+
+
+#[test]
+fn binary_operators_are_left_associative() {
+    check_golden("associativity");
+}
+
+
+#[test]
+fn minus_is_unary_at_the_start_of_an_operand() {
+    check_golden("unary_minus");
+}
+
+
+#[test]
+fn calls_subscripts_and_members_chain() {
+    check_golden("postfix");
+}
+
+
+#[test]
+fn syntax_errors_report_what_was_expected() {
+    check_golden("missing_operand");
+    check_golden("missing_comma");
+    check_golden("missing_brace");
+    check_golden("missing_member_name");
+    check_golden("let_without_name");
+}
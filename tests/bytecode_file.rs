@@ -0,0 +1,91 @@
+mod common;
+
+use std::process::Output;
+
+use common::{compile, run_bytes};
+
+
+/// Check that the file was rejected with the given message, without panicking
+fn assert_rejected(output: Output, message: &str) {
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Could not load compiled file"), "{}", errors);
+    assert!(errors.contains(message), "{}", errors);
+    assert!(!errors.contains("panicked"), "{}", errors);
+}
+
+
+const SCRIPT: &str = r#"
+from io import print
+fun add(a, b=2, ...rest) {
+    return a + b
+}
+print(add(1), "done")
+"#;
+
+
+#[test]
+fn compiled_files_run() {
+    let bytes = compile("bytecode_run", SCRIPT);
+    let output = run_bytes("bytecode_run", &bytes);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "3 done\n");
+}
+
+
+#[test]
+fn truncated_files_are_rejected() {
+    let bytes = compile("bytecode_truncated", SCRIPT);
+
+    for length in 0 .. bytes.len() {
+        let output = run_bytes("bytecode_truncated", &bytes[.. length]);
+        assert!(!output.status.success(), "truncated to {} bytes", length);
+        let errors = String::from_utf8(output.stderr).unwrap();
+        assert!(errors.contains("Could not load compiled file"), "truncated to {} bytes: {}", length, errors);
+        assert!(!errors.contains("panicked"), "truncated to {} bytes: {}", length, errors);
+    }
+}
+
+
+#[test]
+fn huge_counts_are_rejected() {
+    let mut bytes = compile("bytecode_huge_count", SCRIPT);
+
+    // The constant count follows the 9 bytes of the header
+    bytes[9 .. 17].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+    assert_rejected(run_bytes("bytecode_huge_count", &bytes), &format!("count {} is larger than the rest of the file", u64::MAX / 2));
+
+    bytes[9 .. 17].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_rejected(run_bytes("bytecode_huge_count", &bytes), "is larger than the rest of the file");
+}
+
+
+#[test]
+fn required_parameter_counts_cannot_overflow() {
+    let header = &compile("bytecode_required", SCRIPT)[.. 9];
+
+    fn push_usize(bytes: &mut Vec<u8>, value: u64) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    fn push_str(bytes: &mut Vec<u8>, value: &str) {
+        push_usize(bytes, value.len() as u64);
+        bytes.extend_from_slice(value.as_bytes());
+    }
+
+    let mut bytes = header.to_vec();
+    // No constants and a single source module
+    push_usize(&mut bytes, 0);
+    push_usize(&mut bytes, 1);
+    push_str(&mut bytes, "main");
+    push_str(&mut bytes, "main.ws");
+    bytes.push(0);
+    push_str(&mut bytes, "");
+    // A code object without parameters, but with the largest required parameter count and a rest parameter
+    push_str(&mut bytes, "main");
+    push_usize(&mut bytes, 0);
+    push_usize(&mut bytes, u64::MAX);
+    bytes.push(1);
+    bytes.extend_from_slice(&[0; 64]);
+
+    assert_rejected(run_bytes("bytecode_required", &bytes), "function main has more required parameters than parameters");
+}
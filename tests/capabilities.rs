@@ -0,0 +1,69 @@
+mod common;
+
+use common::{run, run_err, temp_path};
+
+
+#[test]
+fn fs_and_env_are_denied_by_default() {
+    let errors = run_err("deny_read", "import fs\nfs.exists(\"data.txt\")\n", &[]);
+    assert!(errors.contains("PermissionDenied: exists() requires read access to"), "{}", errors);
+    assert!(errors.contains("run again with --allow-read to allow it"), "{}", errors);
+
+    let errors = run_err("deny_write", "import fs\nfs.write(\"data.txt\", \"x\")\n", &["--allow-read"]);
+    assert!(errors.contains("PermissionDenied: write() requires write access to"), "{}", errors);
+    assert!(errors.contains("--allow-write"), "{}", errors);
+
+    let errors = run_err("deny_env", "import env\nenv.get(\"HOME\")\n", &[]);
+    assert!(errors.contains("PermissionDenied: get() requires access to the environment, run again with --allow-env to allow it"), "{}", errors);
+}
+
+
+#[test]
+fn errors_in_callbacks_and_modules_stop_the_program() {
+    let errors = run_err("deny_in_callback", r#"
+import fs
+import list
+list.map(["a.txt"], fun(path) { return fs.exists(path) })
+"#, &[]);
+    assert!(errors.contains("PermissionDenied: exists() requires read access to"), "{}", errors);
+}
+
+
+#[test]
+fn allowed_directories_cannot_be_escaped() {
+    let root = temp_path("allow_read_escape", "d");
+    let allowed = root.join("allowed");
+    std::fs::create_dir_all(&allowed).expect("Could not create the test directories");
+    std::fs::write(allowed.join("public.txt"), "public").expect("Could not write the test data");
+    std::fs::write(root.join("secret.txt"), "secret").expect("Could not write the test data");
+    let flag = format!("--allow-read={}", allowed.display());
+
+    let read = |name: &str, path: &std::path::Path| {
+        run(name, &format!("from io import print\nimport fs\nprint(fs.read(\"{}\"))\n", path.display()), &[&flag])
+    };
+
+    let output = read("allow_read_inside", &allowed.join("public.txt"));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "public\n");
+
+    let output = read("allow_read_parent", &allowed.join("..").join("secret.txt"));
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("PermissionDenied: read() requires read access to"), "{}", errors);
+    assert!(!errors.contains("secret\n"), "{}", errors);
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(root.join("secret.txt"), allowed.join("link.txt")).expect("Could not create the link");
+        std::os::unix::fs::symlink(&root, allowed.join("up")).expect("Could not create the link");
+
+        for (name, path) in [("allow_read_file_link", allowed.join("link.txt")), ("allow_read_dir_link", allowed.join("up/secret.txt"))] {
+            let output = read(name, &path);
+            assert!(!output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+            let errors = String::from_utf8(output.stderr).unwrap();
+            assert!(errors.contains("PermissionDenied: read() requires read access to"), "{}", errors);
+        }
+    }
+
+    std::fs::remove_dir_all(&root).ok();
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::run_ok;
+
+
+#[test]
+fn functions_capture_the_locals_of_enclosing_functions() {
+    let output = run_ok("captures", r#"
+from io import print
+import list
+fun scale_all(xs, k) {
+    return list.map(xs, fun(x) { return x * k })
+}
+fun make_adders() {
+    let adders = []
+    for n in [1, 2] {
+        adders = adders + [fun(x) { return x + n }]
+    }
+    return adders
+}
+fun outer(a) {
+    let b = a * 2
+    fun middle(c) {
+        let inner = fun(d, e=c) { return a + b + d + e }
+        return inner(1000)
+    }
+    return middle(100)
+}
+let adders = make_adders()
+print(scale_all([1, 2, 3], 10), adders[0](10), adders[1](10), outer(1))
+"#);
+
+    assert_eq!(output, "[10, 20, 30] 11 12 1103\n");
+}
+
+
+#[test]
+fn captured_variables_are_shared_with_the_enclosing_scope() {
+    let output = run_ok("capture_references", r#"
+from io import print
+fun run() {
+    let items = [0]
+    let count = 0
+    let bump = fun() {
+        items[0] += 1
+        count = count + 1
+        return items[0]
+    }
+    items[0] = 10
+    bump()
+    return [bump(), items[0], count]
+}
+print(run())
+"#);
+
+    assert_eq!(output, "[12, 12, 2]\n");
+}
+
+
+#[test]
+fn captured_block_locals_are_not_stale() {
+    let output = run_ok("capture_block_locals", r#"
+from io import print
+if true {
+    let v = 1
+    fun a() { return v }
+    v = 5
+    print(a())
+}
+fun run() {
+    let w = 1
+    let b = fun() { return w }
+    w = 7
+    return b()
+}
+print(run())
+"#);
+
+    assert_eq!(output, "5\n7\n");
+}
+
+
+#[test]
+fn nested_functions_can_call_themselves() {
+    let output = run_ok("capture_self", r#"
+from io import print
+fun run(n) {
+    fun count_down(k) {
+        if k == 0 {
+            return 0
+        }
+        return 1 + count_down(k - 1)
+    }
+    return count_down(n)
+}
+if true {
+    fun fact(k) {
+        if k <= 1 {
+            return 1
+        }
+        return k * fact(k - 1)
+    }
+    print(run(4), fact(5))
+}
+"#);
+
+    assert_eq!(output, "4 120\n");
+}
+
+
+#[test]
+fn each_loop_iteration_has_its_own_variables() {
+    let output = run_ok("capture_loop_variables", r#"
+from io import print
+let getters = []
+for x in [1, 2, 3, 4] {
+    if x == 2 {
+        continue
+    }
+    if x == 4 {
+        break
+    }
+    getters = getters + [fun() { return x }]
+}
+print(getters[0](), getters[1]())
+"#);
+
+    assert_eq!(output, "1 3\n");
+}
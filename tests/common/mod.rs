@@ -0,0 +1,93 @@
+// Each test file only uses some of the helpers
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+
+/// Return a path in the temporary directory that is unique to the test and the test process
+pub fn temp_path(name: &str, extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("waterscript_test_{}_{}.{}", name, std::process::id(), extension))
+}
+
+
+/// Run the source as a script with the given flags, and return the interpreter's output
+pub fn run(name: &str, source: &str, flags: &[&str]) -> Output {
+    let path = temp_path(name, "ws");
+    std::fs::write(&path, source).expect("Could not write the test script");
+
+    let output = run_file(&path, flags);
+
+    std::fs::remove_file(&path).ok();
+    output
+}
+
+
+/// Run the script file with the given flags, and return the interpreter's output
+pub fn run_file(path: &Path, flags: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_waterscript"))
+        .arg("-q")
+        .args(flags)
+        .arg(path)
+        .output()
+        .expect("Could not run the interpreter")
+}
+
+
+/// Compile the source into a bytecode file and return its bytes
+pub fn compile(name: &str, source: &str) -> Vec<u8> {
+    let source_path = temp_path(name, "ws");
+    let bytecode_path = temp_path(name, "wsc");
+    std::fs::write(&source_path, source).expect("Could not write the test script");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_waterscript"))
+        .arg("compile")
+        .arg(&source_path)
+        .arg("-o")
+        .arg(&bytecode_path)
+        .output()
+        .expect("Could not run the compiler");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let bytes = std::fs::read(&bytecode_path).expect("Could not read the compiled file");
+    std::fs::remove_file(&source_path).ok();
+    std::fs::remove_file(&bytecode_path).ok();
+    bytes
+}
+
+
+/// Write the bytes into a bytecode file, run it and return the interpreter's output
+pub fn run_bytes(name: &str, bytes: &[u8]) -> Output {
+    let path = temp_path(name, "wsc");
+    std::fs::write(&path, bytes).expect("Could not write the compiled file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_waterscript"))
+        .arg("-q")
+        .arg(&path)
+        .output()
+        .expect("Could not run the interpreter");
+
+    std::fs::remove_file(&path).ok();
+    output
+}
+
+
+/// Run the script in eager and lazy mode, check that it succeeds and return its standard output
+pub fn run_ok(name: &str, source: &str) -> String {
+    let eager = run(name, source, &[]);
+    assert!(eager.status.success(), "{}", String::from_utf8_lossy(&eager.stderr));
+
+    let lazy = run(name, source, &["--lazy"]);
+    assert!(lazy.status.success(), "{}", String::from_utf8_lossy(&lazy.stderr));
+    assert_eq!(eager.stdout, lazy.stdout);
+
+    String::from_utf8(eager.stdout).unwrap()
+}
+
+
+/// Run the script with the given flags, check that it fails and return its error output
+pub fn run_err(name: &str, source: &str, flags: &[&str]) -> String {
+    let output = run(name, source, flags);
+    assert!(!output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    String::from_utf8(output.stderr).unwrap()
+}
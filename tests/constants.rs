@@ -0,0 +1,69 @@
+mod common;
+
+use common::{run, run_ok};
+
+
+/// Write a module next to the test scripts, and return the path to import it with
+fn write_module(name: &str, source: &str) -> String {
+    let file_name = format!("waterscript_test_module_{}_{}.ws", name, std::process::id());
+    std::fs::write(std::env::temp_dir().join(&file_name), source).expect("Could not write the test module");
+    file_name
+}
+
+
+/// Check that the script fails to compile because it assigns to the constant
+fn assert_assigns_to_constant(name: &str, source: &str, constant: &str) {
+    for flags in [&[][..], &["--lazy"]] {
+        let output = run(name, source, flags);
+        assert!(!output.status.success());
+
+        let errors = String::from_utf8(output.stderr).unwrap();
+        assert!(errors.contains(&format!("Cannot assign to constant '{}'", constant)), "{}", errors);
+    }
+}
+
+
+#[test]
+fn imported_constants_cannot_be_assigned() {
+    let module = write_module("limits", "const LIMIT = 5\nlet count = 1\n");
+
+    assert_assigns_to_constant("member", &format!(r#"
+import "{}" as limits
+limits.count = 2
+limits.LIMIT = 10
+"#, module), "limits.LIMIT");
+
+    assert_assigns_to_constant("member_in_function", &format!(r#"
+import "{}" as limits
+fun grow() {{
+    limits.LIMIT += 1
+}}
+grow()
+"#, module), "limits.LIMIT");
+
+    assert_assigns_to_constant("import_from", &format!(r#"
+from "{}" import LIMIT, count
+count = 3
+LIMIT = 99
+"#, module), "LIMIT");
+
+    std::fs::remove_file(std::env::temp_dir().join(module)).ok();
+}
+
+
+#[test]
+fn imported_variables_can_be_assigned() {
+    let module = write_module("counts", "const LIMIT = 5\nlet count = 1\n");
+
+    let output = run_ok("imported_variables", &format!(r#"
+from io import print
+import "{0}" as counts
+from "{0}" import count
+counts.count = 7
+count = 4
+print(counts.count, count, counts.LIMIT)
+"#, module));
+
+    assert_eq!(output, "7 4 5\n");
+    std::fs::remove_file(std::env::temp_dir().join(module)).ok();
+}
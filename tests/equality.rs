@@ -0,0 +1,83 @@
+mod common;
+
+use common::run_ok;
+
+
+#[test]
+fn lists_are_compared_structurally() {
+    let output = run_ok("list_equality", r#"
+from io import print
+import list
+print([1, [2, "a"]] == [1, [2, "a"]], [1, 2] == [1, 2, 3], [1] == [1.0], [1] == ["1"])
+print([1, 2] < [1, 3], [1, 2] < [1, 2, 0], [2] > [1, 9], ["b"] > ["a", "z"], [] < [[]])
+print(list.sort([[2], [1, 5], [1], []]))
+"#);
+
+    assert_eq!(output, "true false true false\ntrue true true true true\n[[], [1], [1, 5], [2]]\n");
+}
+
+
+#[test]
+fn ints_and_floats_are_compared_exactly() {
+    let output = run_ok("number_equality", r#"
+from io import print
+import list
+# 2^53 + 1 has no Float representation, and rounds to 2^53
+let big = 9007199254740993
+let rounded = 9007199254740992.0
+print(big == rounded, rounded == 9007199254740992, big > rounded, rounded < big)
+print(9223372036854775807 == 9223372036854775808.0, 9223372036854775807 < 9223372036854775808.0)
+print(1 == 1.0, 1 < 1.5, 2 > 1.5, -1 > -1.5, -2 < -1.5, 0 == -0.0)
+let nan = 1e308 * 10.0 - 1e308 * 10.0
+print(1 == nan, 1 < nan, 1 >= nan, nan == nan)
+print(list.sort([3, 2.5, big, rounded, -1]))
+"#);
+
+    assert_eq!(output, concat!(
+        "false true true true\n",
+        "false true\n",
+        "true true true true true true\n",
+        "false false false false\n",
+        "[-1, 2.5, 3, 9007199254740992.0, 9007199254740993]\n",
+    ));
+}
+
+
+#[test]
+fn equal_numbers_have_the_same_hash() {
+    let output = run_ok("number_hash", r#"
+from io import print
+import list
+print(list.unique([1, 1.0, 0, -0.0, 0.0, 9007199254740992, 9007199254740992.0, 9007199254740993, 2.5, 2.5]))
+print(list.unique([[1, 2.0], [1.0, 2], [1, 2.5]]))
+"#);
+
+    assert_eq!(output, "[1, 0, 9007199254740992, 9007199254740993, 2.5]\n[[1, 2.0], [1, 2.5]]\n");
+}
+
+
+#[test]
+fn functions_are_compared_by_identity() {
+    let output = run_ok("function_equality", r#"
+from io import print
+import list
+fun f() {
+    return 1
+}
+fun g() {
+    return 1
+}
+let h = f
+fun make(n) {
+    return fun() { return n }
+}
+let one = make(1)
+print(f == f, f == g, h == f, one == one, one == make(1), list.map == list.map, list.map == list.filter)
+print(list.unique([f, g, h, one, make(1), list.map, list.map]))
+"#);
+
+    assert_eq!(output, concat!(
+        "true false true true false true false\n",
+        "[<function f>, <function g>, <function <lambda>>, <function <lambda>>, <native function map>]\n",
+    ));
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{run, run_file, temp_path};
+use std::path::PathBuf;
+
+
+/// Write the files into a new directory unique to the test, and return the directory
+fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_path(name, "d");
+    std::fs::create_dir_all(&dir).expect("Could not create the test directory");
+    for (file, content) in files {
+        std::fs::write(dir.join(file), content).expect("Could not write the test file");
+    }
+    dir
+}
+
+
+#[test]
+fn only_source_files_can_be_imported() {
+    let data = std::env::temp_dir().join(format!("waterscript_test_data_{}.txt", std::process::id()));
+    std::fs::write(&data, "secret content\n").expect("Could not write the test data");
+
+    let output = run("import_data", &format!("import \"{}\" as data\n", data.display()), &[]);
+    std::fs::remove_file(&data).ok();
+
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Only source files with the .ws extension can be imported"), "{}", errors);
+    assert!(!errors.contains("secret content"), "{}", errors);
+}
+
+
+#[test]
+fn modules_outside_the_script_directory_cannot_be_imported() {
+    // The test scripts are in the temporary directory, away from the examples
+    let module = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/modules/shapes.ws");
+    let source = format!("import \"{}\" as shapes\n", module.display());
+
+    let output = run("import_outside", &source, &[]);
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Modules can only be imported from the directory of the script and the module paths"), "{}", errors);
+
+    // Unless the module's directory is a module path
+    let output = run("import_module_path", &source, &["-I", module.parent().unwrap().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+
+#[test]
+fn imports_cannot_escape_the_script_directory() {
+    let root = temp_path("import_escape", "d");
+    let scripts = root.join("scripts");
+    std::fs::create_dir_all(&scripts).expect("Could not create the test directories");
+    std::fs::write(root.join("outside.ws"), "let x = 1\n").expect("Could not write the test module");
+    let script = scripts.join("main.ws");
+
+    std::fs::write(&script, "import \"../outside.ws\" as outside\n").expect("Could not write the test script");
+    let output = run_file(&script, &[]);
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Modules can only be imported from the directory of the script and the module paths"), "{}", errors);
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(root.join("outside.ws"), scripts.join("link.ws")).expect("Could not create the link");
+        std::fs::write(&script, "import \"link.ws\" as outside\n").expect("Could not write the test script");
+        let output = run_file(&script, &[]);
+        assert!(!output.status.success());
+        let errors = String::from_utf8(output.stderr).unwrap();
+        assert!(errors.contains("Modules can only be imported from the directory of the script and the module paths"), "{}", errors);
+    }
+
+    std::fs::remove_dir_all(&root).ok();
+}
+
+
+#[test]
+fn importing_a_missing_member_is_a_compile_error() {
+    let dir = write_files("import_missing_member", &[
+        ("m.ws", "let x = 1\n"),
+        ("main.ws", "from io import print\nfrom \"m.ws\" import x, missing\nprint(\"ran\")\n"),
+    ]);
+
+    let output = run_file(&dir.join("main.ws"), &[]);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Module 'm.ws' has no member 'missing' at line 1"), "{}", errors);
+    assert!(!errors.contains("'x'"), "{}", errors);
+
+    let output = run("import_missing_native_member", "from math import sqrt, nope\n", &[]);
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Module 'math' has no member 'nope' at line 0"), "{}", errors);
+}
+
+
+#[test]
+fn modules_are_loaded_and_run_once() {
+    let dir = write_files("import_cache", &[
+        ("shared.ws", "from io import print\nprint(\"running shared\")\nlet items = [0]\n"),
+        ("a.ws", "import \"shared.ws\" as shared\nfun bump() { shared.items[0] += 1 }\n"),
+        ("main.ws", r#"
+from io import print
+import "a.ws" as a
+import "shared.ws" as shared
+from "shared.ws" import items
+a.bump()
+a.bump()
+print(shared.items)
+"#),
+    ]);
+
+    for flags in [&[][..], &["--lazy"][..]] {
+        let output = run_file(&dir.join("main.ws"), flags);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "running shared\n[2]\n");
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+
+#[test]
+fn cycles_through_other_modules_are_compile_errors() {
+    let dir = write_files("import_cycle", &[
+        ("a.ws", "import \"b.ws\" as b\n"),
+        ("b.ws", "import \"c.ws\" as c\n"),
+        ("c.ws", "import \"a.ws\" as a\n"),
+        ("main.ws", "import \"a.ws\" as a\n"),
+    ]);
+
+    let output = run_file(&dir.join("main.ws"), &[]);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(output.status.code(), Some(1));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Circular import at line 0"), "{}", errors);
+    let chain = ["a.ws", "b.ws", "c.ws", "a.ws"];
+    let positions: Vec<usize> = chain.iter().scan(0, |start, file| {
+        let position = *start + errors[*start ..].find(file)?;
+        *start = position + file.len();
+        Some(position)
+    }).collect();
+    assert_eq!(positions.len(), chain.len(), "The import chain should be a -> b -> c -> a: {}", errors);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{run, run_ok};
+
+
+#[test]
+fn print_separates_its_arguments_with_spaces() {
+    let output = run_ok("print", r#"
+from io import print
+print(1, 2.5, "text", true, None)
+print([1, "a", [2.0, "b"]])
+print()
+let values = [3, 4]
+print(values[0] + values[1])
+"#);
+
+    assert_eq!(output, "1 2.5 text true None\n[1, \"a\", [2.0, \"b\"]]\n\n7\n");
+}
+
+
+#[test]
+fn print_must_be_imported() {
+    let output = run("print_undeclared", "print(1)\n", &[]);
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("Undeclared symbol 'print'"), "{}", errors);
+}
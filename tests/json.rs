@@ -0,0 +1,82 @@
+mod common;
+
+use common::{run_err, run_ok};
+
+
+#[test]
+fn parse_and_stringify_round_trip() {
+    let output = run_ok("json_round_trip", r#"
+from io import print
+import json
+let value = json.parse("[1, -2.5, \"a\\nb\", true, null, [], [[3]], 1e2, 9223372036854775808]")
+print(value)
+print(json.stringify(value))
+print(json.parse(json.stringify(value)) == value)
+"#);
+
+    assert_eq!(output, concat!(
+        "[1, -2.5, \"a\\nb\", true, None, [], [[3]], 100.0, 9.223372036854776e18]\n",
+        "[1,-2.5,\"a\\nb\",true,null,[],[[3]],100.0,9223372036854776000]\n",
+        "true\n",
+    ));
+}
+
+
+#[test]
+fn stringify_pretty_prints_with_the_indent() {
+    let output = run_ok("json_indent", r#"
+from io import print
+import json
+print(json.stringify([1, [2], []], 2))
+print(json.stringify([1, [2]], 0))
+print(json.stringify([1, [2]], None))
+"#);
+
+    assert_eq!(output, "[\n  1,\n  [\n    2\n  ],\n  []\n]\n[1,[2]]\n[1,[2]]\n");
+}
+
+
+#[test]
+fn stringify_rejects_invalid_indents() {
+    let errors = run_err("json_indent_cap", "import json\njson.stringify([1], 65)\n", &[]);
+    assert!(errors.contains("ValueError: stringify() indent cannot be more than 64, got 65"), "{}", errors);
+
+    let errors = run_err("json_indent_huge", "import json\njson.stringify([[1]], 9223372036854775807)\n", &[]);
+    assert!(errors.contains("ValueError: stringify() indent cannot be more than 64"), "{}", errors);
+
+    let errors = run_err("json_indent_negative", "import json\njson.stringify([1], -1)\n", &[]);
+    assert!(errors.contains("ValueError: stringify() indent cannot be negative, got -1"), "{}", errors);
+}
+
+
+#[test]
+fn stringify_rejects_values_without_a_json_form() {
+    let errors = run_err("json_nan", "import json\njson.stringify([1e308 * 10.0])\n", &[]);
+    assert!(errors.contains("ValueError: stringify() cannot represent inf in JSON"), "{}", errors);
+
+    let errors = run_err("json_function", "import json\nfun f() {\n    return 1\n}\njson.stringify(f)\n", &[]);
+    assert!(errors.contains("TypeError: stringify() cannot convert Function to JSON"), "{}", errors);
+}
+
+
+#[test]
+fn parse_rejects_numbers_out_of_the_float_range() {
+    let errors = run_err("json_too_large", "import json\njson.parse(\"1e400\")\n", &[]);
+    assert!(errors.contains("ValueError: parse() invalid JSON at line 1, column 6: number 1e400 is too large"), "{}", errors);
+
+    let errors = run_err("json_too_small", "import json\njson.parse(\"[1,\\n -1e400]\")\n", &[]);
+    assert!(errors.contains("at line 2, column 8: number -1e400 is too large"), "{}", errors);
+}
+
+
+#[test]
+fn parse_reports_the_position_of_invalid_json() {
+    let errors = run_err("json_trailing_comma", "import json\njson.parse(\"[1,]\")\n", &[]);
+    assert!(errors.contains("ValueError: parse() invalid JSON at line 1, column 4: unexpected ']'"), "{}", errors);
+
+    let errors = run_err("json_leading_zero", "import json\njson.parse(\"01\")\n", &[]);
+    assert!(errors.contains("at line 1, column 2: leading zeros are not allowed in numbers"), "{}", errors);
+
+    let errors = run_err("json_trailing_data", "import json\njson.parse(\"1 2\")\n", &[]);
+    assert!(errors.contains("unexpected '2' after the end of the document"), "{}", errors);
+}
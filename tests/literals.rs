@@ -0,0 +1,80 @@
+mod common;
+
+use common::{run_err, run_ok};
+
+
+#[test]
+fn integers_can_be_written_in_other_bases() {
+    let output = run_ok("literal_bases", r#"
+from io import print
+print(0x1F, 0XfF, 0b101, 0B1, 0o17, 0O7, 0x7FFFFFFFFFFFFFFF)
+"#);
+
+    assert_eq!(output, "31 255 5 1 15 7 9223372036854775807\n");
+}
+
+
+#[test]
+fn digits_can_be_grouped_with_separators() {
+    let output = run_ok("literal_separators", r#"
+from io import print
+print(1_000_000, 0xFF_FF, 0b1010_1010, 1_0.2_5, 1_0e1_0)
+"#);
+
+    assert_eq!(output, "1000000 65535 170 10.25 100000000000.0\n");
+}
+
+
+#[test]
+fn floats_can_have_exponents() {
+    let output = run_ok("literal_exponents", r#"
+from io import print
+print(1e3, 2.5E-3, 1e+2, 0.5e1)
+"#);
+
+    assert_eq!(output, "1000.0 0.0025 100.0 5.0\n");
+}
+
+
+#[test]
+fn the_smallest_integer_can_be_written_negated() {
+    let output = run_ok("literal_min_int", r#"
+from io import print
+let min = -9223372036854775808
+print(min, -0x8000000000000000, [-9223372036854775808], min == -9223372036854775807 - 1)
+"#);
+
+    assert_eq!(output, "-9223372036854775808 -9223372036854775808 [-9223372036854775808] true\n");
+
+    // Subtracting the literal isn't a negation, so the literal is too large
+    let errors = run_err("literal_min_int_subtraction", "let a = 1\nlet b = a -9223372036854775808\n", &[]);
+    assert!(errors.contains("The integer is too large"), "{}", errors);
+}
+
+
+#[test]
+fn invalid_literals_are_compile_errors() {
+    let cases = [
+        ("0b12", "'2' is not a valid digit in base 2."),
+        ("0o8", "'8' is not a valid digit in base 8."),
+        ("0xG", "'G' is not a valid digit in base 16."),
+        ("0x", "Expected digits after the '0x' prefix."),
+        ("1_", "The '_' separator can only be used between two digits."),
+        ("1__0", "The '_' separator can only be used between two digits."),
+        ("0x_1", "The '_' separator can only be used between two digits."),
+        ("1.2.3", "A number can only have one decimal point."),
+        ("1.", "Expected digits after the decimal point."),
+        ("1e", "Expected digits in the exponent."),
+        ("1e+", "Expected digits in the exponent."),
+        ("12abc", "Unexpected character 'a' in the number."),
+        ("9223372036854775808", "The integer is too large."),
+        ("0x10000000000000000", "The integer is too large."),
+        ("1e400", "The float is too large."),
+    ];
+
+    for (literal, hint) in cases {
+        let errors = run_err("literal_invalid", &format!("let x = {}\n", literal), &[]);
+        assert!(errors.contains(&format!("Invalid numeric literal '{}' at line 0", literal)), "{}: {}", literal, errors);
+        assert!(errors.contains(hint), "{}: {}", literal, errors);
+    }
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{run_err, run_ok};
+
+
+const MIN: &str = "(-9223372036854775807 - 1)";
+
+
+/// Run the expression after importing math, and return the error it raises
+fn math_err(name: &str, expression: &str) -> String {
+    run_err(name, &format!("import math\nlet x = {}\n", expression), &[])
+}
+
+
+#[test]
+fn integer_arithmetic_raises_on_overflow() {
+    let cases = [
+        ("9223372036854775807 + 1", "OverflowError: Cannot add 9223372036854775807 and 1: integer overflow"),
+        ("-9223372036854775807 - 2", "OverflowError: Cannot subtract -9223372036854775807 and 2: integer overflow"),
+        ("9223372036854775807 * 2", "OverflowError: Cannot multiply 9223372036854775807 and 2: integer overflow"),
+        (&format!("{} / -1", MIN), "OverflowError: Cannot divide -9223372036854775808 and -1: integer overflow"),
+        (&format!("{} % -1", MIN), "OverflowError: Cannot take the remainder of -9223372036854775808 and -1: integer overflow"),
+        (&format!("-{}", MIN), "OverflowError: Cannot negate -9223372036854775808: integer overflow"),
+    ];
+
+    for (expression, expected) in cases {
+        let errors = math_err("int_overflow", expression);
+        assert!(errors.contains(expected), "{}: {}", expression, errors);
+    }
+}
+
+
+#[test]
+fn integer_arithmetic_at_the_limits() {
+    let output = run_ok("int_limits", &format!(r#"
+from io import print
+print(9223372036854775806 + 1, {min} + 1, {min} / 1, 7 % -1, -7 / 2, 9223372036854775807 * -1)
+"#, min = MIN));
+
+    assert_eq!(output, "9223372036854775807 -9223372036854775807 -9223372036854775808 0 -3 -9223372036854775807\n");
+}
+
+
+#[test]
+fn abs_and_pow_raise_on_overflow() {
+    let errors = math_err("abs_overflow", &format!("math.abs({})", MIN));
+    assert!(errors.contains("OverflowError: abs() of -9223372036854775808: integer overflow"), "{}", errors);
+
+    let errors = math_err("pow_overflow", "math.pow(2, 63)");
+    assert!(errors.contains("OverflowError: pow() cannot raise 2 to the power of 63: integer overflow"), "{}", errors);
+
+    let errors = math_err("pow_overflow_large", "math.pow(3, 40)");
+    assert!(errors.contains("OverflowError: pow() cannot raise 3 to the power of 40: integer overflow"), "{}", errors);
+
+    let output = run_ok("abs_pow_limits", r#"
+from io import print
+import math
+print(math.abs(-9223372036854775807), math.pow(2, 62), math.pow(-2, 63), math.pow(2, -1))
+"#);
+    assert_eq!(output, "9223372036854775807 4611686018427387904 -9223372036854775808 0.5\n");
+}
+
+
+#[test]
+fn floats_that_are_not_ints_cannot_be_converted() {
+    let nan = "(1e308 * 10.0 - 1e308 * 10.0)";
+    let inf = "(1e308 * 10.0)";
+    let cases = [
+        (format!("math.floor({})", nan), "ValueError: floor() cannot convert NaN to Int"),
+        (format!("math.int({})", nan), "ValueError: int() cannot convert NaN to Int"),
+        (format!("math.floor({})", inf), "OverflowError: floor() cannot convert inf to Int: integer overflow"),
+        (format!("math.ceil(-{})", inf), "OverflowError: ceil() cannot convert -inf to Int: integer overflow"),
+        (format!("math.int({})", inf), "OverflowError: int() cannot convert inf to Int: integer overflow"),
+        ("math.int(1e19)".to_string(), "OverflowError: int() cannot convert 10000000000000000000 to Int: integer overflow"),
+        ("math.int(-1e19)".to_string(), "OverflowError: int() cannot convert -10000000000000000000 to Int: integer overflow"),
+        ("math.floor(9223372036854775808.0)".to_string(), "OverflowError: floor() cannot convert 9223372036854776000 to Int: integer overflow"),
+    ];
+
+    for (expression, expected) in cases {
+        let errors = math_err("float_to_int", &expression);
+        assert!(errors.contains(expected), "{}: {}", expression, errors);
+    }
+
+    // The smallest Int is exactly representable as a Float
+    let output = run_ok("float_to_int_limits", r#"
+from io import print
+import math
+print(math.int(-9223372036854775808.0), math.floor(-2.5), math.ceil(-2.5), math.int(2.9))
+"#);
+    assert_eq!(output, "-9223372036854775808 -3 -2 2\n");
+}
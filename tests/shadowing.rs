@@ -0,0 +1,156 @@
+mod common;
+
+use common::{run, run_ok};
+
+
+#[test]
+fn redeclaration_in_the_same_scope() {
+    let output = run_ok("same_scope", r#"
+from io import print
+let a = 1
+let a = a + 1
+let b = "b"
+let a = a * 10
+print(a, b)
+"#);
+
+    assert_eq!(output, "20 b\n");
+}
+
+
+#[test]
+fn redeclaration_in_nested_scopes() {
+    let output = run_ok("nested_scopes", r#"
+from io import print
+let x = "global"
+{
+    let before = x
+    let x = "block"
+    {
+        let x = x + " inner"
+        print(x)
+    }
+    print(before, x)
+}
+for x in [1, 2] {
+    let x = x * 10
+    print(x)
+}
+print(x)
+"#);
+
+    assert_eq!(output, "block inner\nglobal block\n10\n20\nglobal\n");
+}
+
+
+#[test]
+fn redeclaration_in_functions() {
+    let output = run_ok("functions", r#"
+from io import print
+fun scale(value, factor) {
+    let value = value * factor
+    let extra = 1
+    let value = value + extra
+    {
+        let value = "hidden"
+    }
+    return value
+}
+print(scale(3, 2))
+"#);
+
+    assert_eq!(output, "7\n");
+}
+
+
+#[test]
+fn functions_declared_before_a_redeclaration() {
+    let output = run_ok("declared_before", r#"
+from io import print
+let name = "a"
+fun greet() {
+    return "hi " + name
+}
+print(greet())
+let name = "b"
+print(greet(), name)
+let apply = fun(x) { return name + x }
+let name = "c"
+print(apply("!"))
+"#);
+
+    assert_eq!(output, "hi a\nhi a b\nb!\n");
+}
+
+
+#[test]
+fn functions_declared_in_a_block_before_a_local() {
+    let output = run_ok("block_function", r#"
+from io import print
+let name = "global"
+{
+    fun show() {
+        return name
+    }
+    let name = "local"
+    print(show(), name)
+}
+"#);
+
+    assert_eq!(output, "global local\n");
+}
+
+
+#[test]
+fn functions_calling_functions_declared_later() {
+    let output = run_ok("declared_later", r#"
+from io import print
+fun is_even(n) {
+    if n == 0 {
+        return true
+    }
+    return is_odd(n - 1)
+}
+fun is_odd(n) {
+    if n == 0 {
+        return false
+    }
+    return is_even(n - 1)
+}
+print(is_even(10), is_odd(7))
+"#);
+
+    assert_eq!(output, "true true\n");
+}
+
+
+#[test]
+fn warn_shadowing() {
+    let source = r#"
+let a = 1
+let a = 2
+{
+    let a = 3
+}
+fun f(a) {
+    let b = a
+    return b
+}
+"#;
+
+    // Lines are numbered from 0, and the source starts with an empty line
+    let output = run("warn", source, &["--warn-shadowing"]);
+    assert!(output.status.success());
+    let warnings = String::from_utf8(output.stderr).unwrap();
+
+    assert!(warnings.contains("Warning: Declaration of 'a' shadows a previous declaration in the same scope at line 2"), "{}", warnings);
+    assert!(warnings.contains("Warning: Declaration of 'a' shadows a declaration of an enclosing scope at line 4"), "{}", warnings);
+    assert!(warnings.contains("Warning: Declaration of 'a' shadows a declaration of an enclosing scope at line 6"), "{}", warnings);
+    assert!(!warnings.contains("'b'"), "{}", warnings);
+    assert_eq!(warnings.matches("Warning:").count(), 3, "{}", warnings);
+
+    // The lint is off by default
+    let output = run("no_warn", source, &[]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{run, run_err, run_ok};
+
+
+#[test]
+fn repeat_is_capped_at_one_gibibyte() {
+    // Exactly at the cap, only run eagerly because the string is large
+    let output = run("repeat_at_cap", r#"
+from io import print
+import string
+print(string.ends_with(string.repeat("ab", 536870912), "ab"))
+"#, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "true\n");
+
+    let cases = [
+        ("string.repeat(\"ab\", 536870913)", "OverflowError: repeat() of a 2-byte string 536870913 times is longer than the maximum of 1073741824 bytes"),
+        // The cap counts bytes, not characters
+        ("string.repeat(\"é\", 536870913)", "OverflowError: repeat() of a 2-byte string 536870913 times is longer than the maximum of 1073741824 bytes"),
+        ("string.repeat(\"a\", 9223372036854775807)", "OverflowError: repeat() of a 1-byte string 9223372036854775807 times is longer than the maximum of 1073741824 bytes"),
+    ];
+    for (expression, expected) in cases {
+        let errors = run_err("repeat_over_cap", &format!("import string\nlet s = {}\n", expression), &[]);
+        assert!(errors.contains(expected), "{}: {}", expression, errors);
+    }
+}
+
+
+#[test]
+fn repeat_counts_cannot_be_negative() {
+    let errors = run_err("repeat_negative", "import string\nlet s = string.repeat(\"a\", -1)\n", &[]);
+    assert!(errors.contains("ValueError: repeat() count cannot be negative, got -1"), "{}", errors);
+
+    let output = run_ok("repeat_limits", r#"
+from io import print
+import string
+print(string.repeat("ab", 0), string.len(string.repeat("", 9223372036854775807)), string.repeat("é", 3))
+"#);
+    assert_eq!(output, " 0 ééé\n");
+}
+
+
+#[test]
+fn strings_are_indexed_by_character() {
+    let output = run_ok("string_index", r#"
+from io import print
+import string
+print("héllo"[1], "héllo"[-1], "日本語"[2], string.len("日本語"), string.find("日本語", "語"), string.chars("añb"))
+"#);
+    assert_eq!(output, "é o 語 3 2 [\"a\", \"ñ\", \"b\"]\n");
+
+    let errors = run_err("string_index_out_of_range", "let c = \"日本語\"[3]\n", &[]);
+    assert!(errors.contains("IndexError: Index 3 is out of range for length 3"), "{}", errors);
+
+    let errors = run_err("string_negative_index_out_of_range", "let c = \"日本語\"[-4]\n", &[]);
+    assert!(errors.contains("IndexError: Index -4 is out of range for length 3"), "{}", errors);
+}
+
+
+#[test]
+fn slices_count_characters_and_clamp_the_bounds() {
+    let output = run_ok("string_slice", r#"
+from io import print
+import string
+print(string.slice("héllo wörld", 1, 4), string.slice("héllo wörld", -5), string.slice("日本語", -2, 100))
+print(string.slice("日本語", 2, 1) == "", string.slice("日本語", -100, 1), string.slice("日本語", 3) == "")
+"#);
+    assert_eq!(output, "éll wörld 本語\ntrue 日 true\n");
+}
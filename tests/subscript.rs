@@ -0,0 +1,55 @@
+mod common;
+
+use common::run_ok;
+
+
+#[test]
+fn reading_an_element_while_the_list_is_reassigned() {
+    let output = run_ok("reassigned", r#"
+from io import print
+let xs = [1, 2]
+fun clobber() {
+    xs = [9]
+    return 0
+}
+fun first(a, b) {
+    return a
+}
+print(first(xs[0], clobber()), xs)
+"#);
+
+    assert_eq!(output, "1 [9]\n");
+}
+
+
+#[test]
+fn storing_an_element_while_the_list_grows() {
+    let output = run_ok("grows", r#"
+from io import print
+let xs = [[1, 2], 3]
+fun grow() {
+    xs = xs + [4, 5, 6, 7, 8]
+    return 1
+}
+xs[0][grow()] = "a"
+xs[1] += 10
+print(xs)
+"#);
+
+    assert_eq!(output, "[[1, \"a\"], 13, 4, 5, 6, 7, 8]\n");
+}
+
+
+#[test]
+fn elements_are_copied() {
+    let output = run_ok("copied", r#"
+from io import print
+let xs = [[1], [2]]
+let first = xs[0]
+first[0] = "changed"
+[xs[0][0], xs[1][0]] = ["a", "b"]
+print(xs, first)
+"#);
+
+    assert_eq!(output, "[[\"a\"], [\"b\"]] [\"changed\"]\n");
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::run_ok;
+
+
+#[test]
+fn tail_calls_with_keyword_and_spread_arguments() {
+    // Deeper than the maximum call depth, so the calls must reuse the frame
+    let output = run_ok("tail_calls_ex", r#"
+from io import print
+fun count(n, total=0) {
+    if n == 0 {
+        return total
+    }
+    return count(n - 1, total: total + 1)
+}
+fun spread(n, ...rest) {
+    if n == 0 {
+        return rest
+    }
+    return spread(...[n - 1, n])
+}
+fun add(a, b) {
+    return a - b
+}
+fun call_add() {
+    let x = 1
+    return add(b: x, a: 3)
+}
+print(count(20000), spread(20000), call_add())
+"#);
+
+    assert_eq!(output, "20000 [1] 2\n");
+}
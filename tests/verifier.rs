@@ -0,0 +1,165 @@
+mod common;
+
+use common::{compile, run_bytes};
+
+
+// The op codes used by the tests, numbered in the order of OpCode
+const LOAD_CONST: u8 = 4;
+const MAKE_FUNCTION: u8 = 7;
+const RETURN: u8 = 20;
+const IMPORT_MODULE: u8 = 28;
+const POP: u8 = 30;
+const JUMP: u8 = 31;
+const JUMP_IF_FALSE: u8 = 32;
+
+
+fn push_usize(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend_from_slice(&(value as u64).to_le_bytes());
+}
+
+
+fn push_str(bytes: &mut Vec<u8>, value: &str) {
+    push_usize(bytes, value.len());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+
+fn push_code_object(bytes: &mut Vec<u8>, name: &str, is_function: bool, code: &[u8], functions: &[&[u8]]) {
+    push_str(bytes, name);
+    // No parameters, required parameters, rest parameter or captures
+    push_usize(bytes, 0);
+    push_usize(bytes, 0);
+    bytes.push(0);
+    push_usize(bytes, 0);
+    bytes.push(is_function as u8);
+    push_usize(bytes, 0);
+
+    push_usize(bytes, code.len());
+    bytes.extend_from_slice(code);
+    // No line table or symbols
+    push_usize(bytes, 0);
+    push_usize(bytes, 0);
+
+    push_usize(bytes, functions.len());
+    for function in functions {
+        push_code_object(bytes, "inner", true, function, &[]);
+    }
+}
+
+
+/// Build a compiled file with a single module running the given code, a constant pool holding one Int,
+/// and a function declared in the top-level code for each given function code
+fn program(code: &[u8], functions: &[&[u8]]) -> Vec<u8> {
+    // The header depends on the version of the interpreter
+    let mut bytes = compile("verifier_header", "")[.. 9].to_vec();
+
+    // A single Int constant
+    push_usize(&mut bytes, 1);
+    bytes.push(0);
+    bytes.extend_from_slice(&7i64.to_le_bytes());
+
+    // A single source module, whose source is empty
+    push_usize(&mut bytes, 1);
+    push_str(&mut bytes, "main");
+    push_str(&mut bytes, "main.ws");
+    bytes.push(0);
+    push_str(&mut bytes, "");
+    push_code_object(&mut bytes, "main", false, code, functions);
+    bytes
+}
+
+
+/// The instruction followed by its pointer-sized operand
+fn with_operand(op_code: u8, operand: usize) -> Vec<u8> {
+    let mut instruction = vec![op_code];
+    instruction.extend_from_slice(&(operand as u64).to_le_bytes());
+    instruction
+}
+
+
+/// The jump instruction followed by its offset, relative to the end of the instruction
+fn jump(op_code: u8, offset: i32) -> Vec<u8> {
+    let mut instruction = vec![op_code];
+    instruction.extend_from_slice(&offset.to_le_bytes());
+    instruction
+}
+
+
+/// Check that the program is rejected by the verifier with the given message, without panicking
+fn assert_rejected(name: &str, code: &[u8], functions: &[&[u8]], message: &str) {
+    let output = run_bytes(name, &program(code, functions));
+    assert!(!output.status.success());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("VerifyError in "), "{}", errors);
+    assert!(errors.contains(message), "{}", errors);
+    assert!(!errors.contains("panicked"), "{}", errors);
+}
+
+
+#[test]
+fn valid_programs_are_accepted() {
+    let code = [with_operand(LOAD_CONST, 0), vec![POP]].concat();
+    let output = run_bytes("verifier_valid", &program(&code, &[&[RETURN]]));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+
+#[test]
+fn truncated_operands_are_rejected() {
+    assert_rejected("verifier_truncated", &[LOAD_CONST, 0, 0], &[], "main at instruction 0: LoadConst expects 8 bytes of operands, but the code ends after 2");
+    assert_rejected("verifier_truncated_jump", &[POP, JUMP, 0], &[], "main at instruction 1: Jump expects 4 bytes of operands, but the code ends after 1");
+}
+
+
+#[test]
+fn jumps_outside_the_code_are_rejected() {
+    assert_rejected("verifier_jump_after", &jump(JUMP, 100), &[], "at instruction 0: jump target is out of the code");
+    assert_rejected("verifier_jump_before", &[vec![POP], jump(JUMP, -10)].concat(), &[], "at instruction 1: jump target is out of the code");
+}
+
+
+#[test]
+fn jumps_into_an_instruction_are_rejected() {
+    // The jump lands on the operand of the LoadConst after it
+    let code = [jump(JUMP, 1), with_operand(LOAD_CONST, 0), vec![POP]].concat();
+    assert_rejected("verifier_jump_inside", &code, &[], "at instruction 0: jump target 6 is not the start of an instruction");
+}
+
+
+#[test]
+fn indices_out_of_range_are_rejected() {
+    assert_rejected("verifier_constant", &[with_operand(LOAD_CONST, 5), vec![POP]].concat(), &[], "constant index 5 is out of range for a pool of 1 constants");
+    assert_rejected("verifier_function", &[with_operand(MAKE_FUNCTION, 3), vec![POP]].concat(), &[&[RETURN]], "function index 3 is out of range for 1 declared functions");
+    assert_rejected("verifier_module", &[with_operand(IMPORT_MODULE, 7), vec![POP]].concat(), &[], "module id 7 is out of range for 1 modules");
+}
+
+
+#[test]
+fn stack_underflows_are_rejected() {
+    assert_rejected("verifier_underflow", &[POP], &[], "main at instruction 0: Pop pops 1 objects, but only 0 are on the stack");
+
+    // The functions are verified too
+    let code = [with_operand(MAKE_FUNCTION, 0), vec![POP]].concat();
+    assert_rejected("verifier_underflow_function", &code, &[&[POP, RETURN]], "inner at instruction 0: Pop pops 1 objects, but only 0 are on the stack");
+}
+
+
+#[test]
+fn mismatched_stack_depths_are_rejected() {
+    // The jump skips the LoadConst, so the Pop is reached with 0 or 1 objects on the stack
+    let code = [
+        with_operand(LOAD_CONST, 0),
+        jump(JUMP_IF_FALSE, 9),
+        with_operand(LOAD_CONST, 0),
+        vec![POP],
+    ].concat();
+    assert_rejected("verifier_depths", &code, &[], "at instruction 23: the instruction is reached with different stack depths");
+
+    // Both paths reach the end of the code, one with an object left on the stack
+    let code = [
+        with_operand(LOAD_CONST, 0),
+        jump(JUMP_IF_FALSE, 9),
+        with_operand(LOAD_CONST, 0),
+    ].concat();
+    assert_rejected("verifier_end_depth", &code, &[], "the code ends with 1 objects and 0 scopes left on the stack");
+}